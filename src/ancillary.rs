@@ -6,14 +6,46 @@
 
 use core::convert::TryFrom;
 use core::marker::PhantomData;
-use core::mem::{size_of, zeroed};
+use core::mem::{align_of, size_of, zeroed, MaybeUninit};
 use core::ptr::read_unaligned;
 use core::slice::from_raw_parts;
-use std::os::unix::io::RawFd;
-
 #[cfg(any(target_os = "android", target_os = "linux",))]
+use std::ffi::{OsStr, OsString};
+#[cfg(any(target_os = "android", target_os = "linux",))]
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+#[cfg(any(
+	target_os = "android",
+	target_os = "dragonfly",
+	target_os = "freebsd",
+	target_os = "linux",
+))]
 use libc::{gid_t, pid_t, uid_t};
 
+/// The raw credentials struct used in credential control messages.
+#[cfg(any(target_os = "android", target_os = "linux",))]
+type RawCred = libc::ucred;
+
+/// The raw credentials struct used in credential control messages.
+#[cfg(any(target_os = "dragonfly", target_os = "freebsd",))]
+type RawCred = libc::cmsgcred;
+
+/// The control message type for credentials.
+#[cfg(any(target_os = "android", target_os = "linux",))]
+const SCM_CREDS_TYPE: libc::c_int = libc::SCM_CREDENTIALS;
+
+/// The control message type for credentials.
+#[cfg(any(target_os = "dragonfly", target_os = "freebsd",))]
+const SCM_CREDS_TYPE: libc::c_int = libc::SCM_CREDS;
+
+/// The control message type for security labels.
+///
+/// Not all targets have this constant in `libc`, but it has the same value on all architectures.
+#[cfg(any(target_os = "android", target_os = "linux",))]
+const SCM_SECURITY: libc::c_int = 0x03;
+
 #[cfg(any(target_os = "android", all(target_os = "linux", target_env = "gnu")))]
 pub(crate) type CmsgLen = usize;
 
@@ -28,7 +60,7 @@ pub(crate) type CmsgLen = usize;
 pub(crate) type CmsgLen = libc::socklen_t;
 
 fn add_to_ancillary_data<T>(
-	buffer: &mut [u8],
+	buffer: &mut [MaybeUninit<u8>],
 	length: &mut usize,
 	source: &[T],
 	cmsg_level: libc::c_int,
@@ -58,7 +90,7 @@ fn add_to_ancillary_data<T>(
 		}
 
 		for byte in &mut buffer[*length..new_length] {
-			*byte = 0;
+			*byte = MaybeUninit::new(0);
 		}
 
 		*length = new_length;
@@ -125,9 +157,21 @@ impl<'a, T> Iterator for AncillaryDataIter<'a, T> {
 }
 
 /// Unix credential.
-#[cfg(any(doc, target_os = "android", target_os = "linux",))]
+///
+/// On Linux and Android this wraps a `ucred` struct and is sent as a `SCM_CREDENTIALS` control message.
+/// On FreeBSD and DragonFly this wraps a `cmsgcred` struct and is sent as a `SCM_CREDS` control message.
+/// On those platforms, the kernel always fills in the credentials of the sending process,
+/// so the values set on the struct are ignored when sending.
+#[cfg(any(
+	doc,
+	target_os = "android",
+	target_os = "dragonfly",
+	target_os = "freebsd",
+	target_os = "linux",
+))]
 #[derive(Clone)]
-pub struct SocketCred(libc::ucred);
+#[repr(transparent)]
+pub struct SocketCred(RawCred);
 
 #[cfg(any(doc, target_os = "android", target_os = "linux",))]
 impl SocketCred {
@@ -139,6 +183,19 @@ impl SocketCred {
 		SocketCred(libc::ucred { pid: 0, uid: 0, gid: 0 })
 	}
 
+	/// Create a Unix credential struct with the credentials of the current process.
+	///
+	/// This uses the PID, real UID and real GID of the current process.
+	pub fn from_process() -> SocketCred {
+		unsafe {
+			SocketCred(libc::ucred {
+				pid: libc::getpid(),
+				uid: libc::getuid(),
+				gid: libc::getgid(),
+			})
+		}
+	}
+
 	/// Set the PID.
 	pub fn set_pid(&mut self, pid: pid_t) {
 		self.0.pid = pid;
@@ -170,26 +227,161 @@ impl SocketCred {
 	}
 }
 
+#[cfg(any(target_os = "dragonfly", target_os = "freebsd",))]
+impl SocketCred {
+	/// Create a Unix credential struct.
+	///
+	/// All fields are set to 0.
+	#[allow(clippy::new_without_default)]
+	pub fn new() -> SocketCred {
+		SocketCred(unsafe { zeroed() })
+	}
+
+	/// Create a Unix credential struct with the credentials of the current process.
+	///
+	/// This uses the PID, real UID, effective UID and real GID of the current process.
+	pub fn from_process() -> SocketCred {
+		let mut cred = Self::new();
+		unsafe {
+			cred.0.cmcred_pid = libc::getpid();
+			cred.0.cmcred_uid = libc::getuid();
+			cred.0.cmcred_euid = libc::geteuid();
+			cred.0.cmcred_gid = libc::getgid();
+		}
+		cred
+	}
+
+	/// Set the PID.
+	pub fn set_pid(&mut self, pid: pid_t) {
+		self.0.cmcred_pid = pid;
+	}
+
+	/// Get the current PID.
+	pub fn get_pid(&self) -> pid_t {
+		self.0.cmcred_pid
+	}
+
+	/// Set the UID.
+	pub fn set_uid(&mut self, uid: uid_t) {
+		self.0.cmcred_uid = uid;
+	}
+
+	/// Get the current UID.
+	pub fn get_uid(&self) -> uid_t {
+		self.0.cmcred_uid
+	}
+
+	/// Set the GID.
+	pub fn set_gid(&mut self, gid: gid_t) {
+		self.0.cmcred_gid = gid;
+	}
+
+	/// Get the current GID.
+	pub fn get_gid(&self) -> gid_t {
+		self.0.cmcred_gid
+	}
+}
+
+#[cfg(any(
+	doc,
+	target_os = "android",
+	target_os = "dragonfly",
+	target_os = "freebsd",
+	target_os = "linux",
+))]
+impl SocketCred {
+	/// Convert the credentials to a [`UCred`](crate::UCred).
+	///
+	/// This is useful for comparing credentials received in a control message
+	/// with the credentials of a peer from [`UCred::from_socket_peer()`](crate::UCred::from_socket_peer).
+	pub fn to_ucred(&self) -> crate::UCred {
+		crate::UCred::from_raw(Some(self.get_pid()), self.get_uid(), self.get_gid())
+	}
+}
+
+#[cfg(any(
+	doc,
+	target_os = "android",
+	target_os = "dragonfly",
+	target_os = "freebsd",
+	target_os = "linux",
+))]
+impl From<SocketCred> for crate::UCred {
+	fn from(other: SocketCred) -> Self {
+		other.to_ucred()
+	}
+}
+
+#[cfg(any(
+	doc,
+	target_os = "android",
+	target_os = "dragonfly",
+	target_os = "freebsd",
+	target_os = "linux",
+))]
+impl std::fmt::Debug for SocketCred {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		f.debug_struct("SocketCred")
+			.field("pid", &self.get_pid())
+			.field("uid", &self.get_uid())
+			.field("gid", &self.get_gid())
+			.finish()
+	}
+}
+
 /// This control message contains file descriptors.
 ///
 /// The level is equal to `SOL_SOCKET` and the type is equal to `SCM_RIGHTS`.
-pub struct ScmRights<'a>(AncillaryDataIter<'a, RawFd>);
+///
+/// For received messages, the caller takes ownership of each file descriptor yielded by this iterator,
+/// and is responsible for closing it.
+/// The [`SocketAncillary`] does not close yielded file descriptors, and does not yield them again.
+/// File descriptors that have already been taken are skipped.
+pub struct ScmRights<'a> {
+	fds: AncillaryDataIter<'a, RawFd>,
+	released: Option<&'a ReleasedFds>,
+}
 
 impl<'a> Iterator for ScmRights<'a> {
 	type Item = RawFd;
 
 	fn next(&mut self) -> Option<RawFd> {
-		self.0.next()
+		loop {
+			let fd = self.fds.next()?;
+			if fd == CLAIMED_FD {
+				continue;
+			}
+			if let Some(released) = self.released {
+				let mut released = lock_released(released);
+				if released.contains(&fd) {
+					continue;
+				}
+				released.push(fd);
+			}
+			return Some(fd);
+		}
 	}
 }
 
 /// This control message contains unix credentials.
 ///
 /// The level is equal to `SOL_SOCKET` and the type is equal to `SCM_CREDENTIALS` or `SCM_CREDS`.
-#[cfg(any(doc, target_os = "android", target_os = "linux",))]
-pub struct ScmCredentials<'a>(AncillaryDataIter<'a, libc::ucred>);
+#[cfg(any(
+	doc,
+	target_os = "android",
+	target_os = "dragonfly",
+	target_os = "freebsd",
+	target_os = "linux",
+))]
+pub struct ScmCredentials<'a>(AncillaryDataIter<'a, RawCred>);
 
-#[cfg(any(doc, target_os = "android", target_os = "linux",))]
+#[cfg(any(
+	doc,
+	target_os = "android",
+	target_os = "dragonfly",
+	target_os = "freebsd",
+	target_os = "linux",
+))]
 impl<'a> Iterator for ScmCredentials<'a> {
 	type Item = SocketCred;
 
@@ -198,7 +390,23 @@ impl<'a> Iterator for ScmCredentials<'a> {
 	}
 }
 
+#[cfg(any(
+	doc,
+	target_os = "android",
+	target_os = "dragonfly",
+	target_os = "freebsd",
+	target_os = "linux",
+))]
+impl<'a> ScmCredentials<'a> {
+	/// Iterate over the received credentials as [`UCred`](crate::UCred) values.
+	pub fn ucreds(self) -> impl Iterator<Item = crate::UCred> + 'a {
+		self.map(|cred| cred.to_ucred())
+	}
+}
+
 /// The error type which is returned from parsing the type a control message.
+///
+/// Use [`SocketAncillary::raw_messages()`] to access the data of control messages that are not recognized.
 #[non_exhaustive]
 #[derive(Debug)]
 pub enum AncillaryError {
@@ -213,13 +421,30 @@ pub enum AncillaryError {
 }
 
 /// This enum represent one control message of variable type.
+///
+/// More control message types may be added in the future, so matching on it requires a wildcard arm.
+#[non_exhaustive]
 pub enum AncillaryData<'a> {
 	/// Ancillary data holding file descriptors.
 	ScmRights(ScmRights<'a>),
 
 	/// Ancillary data holding unix credentials.
-	#[cfg(any(doc, target_os = "android", target_os = "linux",))]
+	#[cfg(any(
+		doc,
+		target_os = "android",
+		target_os = "dragonfly",
+		target_os = "freebsd",
+		target_os = "linux",
+	))]
 	ScmCredentials(ScmCredentials<'a>),
+
+	/// Ancillary data holding the security label of the sending process.
+	///
+	/// The kernel only attaches the label if `SO_PASSSEC` is enabled on the receiving socket,
+	/// for example with [`UnixSeqpacketBuilder::passsec()`](crate::UnixSeqpacketBuilder::passsec),
+	/// and if a Linux Security Module like SELinux or AppArmor is active.
+	#[cfg(any(doc, target_os = "android", target_os = "linux",))]
+	ScmSecurity(&'a OsStr),
 }
 
 impl<'a> AncillaryData<'a> {
@@ -229,11 +454,13 @@ impl<'a> AncillaryData<'a> {
 	///
 	/// `data` must contain a valid control message and the control message must be type of
 	/// `SOL_SOCKET` and level of `SCM_RIGHTS`.
+	///
+	/// If `released` is given, the file descriptors are owned by the ancillary data,
+	/// and yielded file descriptors are recorded in it.
 	#[allow(clippy::wrong_self_convention)]
-	unsafe fn as_rights(data: &'a [u8]) -> Self {
-		let ancillary_data_iter = AncillaryDataIter::new(data);
-		let scm_rights = ScmRights(ancillary_data_iter);
-		AncillaryData::ScmRights(scm_rights)
+	unsafe fn as_rights(data: &'a [u8], released: Option<&'a ReleasedFds>) -> Self {
+		let fds = AncillaryDataIter::new(data);
+		AncillaryData::ScmRights(ScmRights { fds, released })
 	}
 
 	/// Create a `AncillaryData::ScmCredentials` variant.
@@ -241,8 +468,8 @@ impl<'a> AncillaryData<'a> {
 	/// # Safety
 	///
 	/// `data` must contain a valid control message and the control message must be type of
-	/// `SOL_SOCKET` and level of `SCM_CREDENTIALS` or `SCM_CREDENTIALS`.
-	#[cfg(any(doc, target_os = "android", target_os = "linux",))]
+	/// `SOL_SOCKET` and level of `SCM_CREDENTIALS` or `SCM_CREDS`.
+	#[cfg(any(target_os = "android", target_os = "dragonfly", target_os = "freebsd", target_os = "linux",))]
 	#[allow(clippy::wrong_self_convention)]
 	unsafe fn as_credentials(data: &'a [u8]) -> Self {
 		let ancillary_data_iter = AncillaryDataIter::new(data);
@@ -250,18 +477,27 @@ impl<'a> AncillaryData<'a> {
 		AncillaryData::ScmCredentials(scm_credentials)
 	}
 
-	fn try_from_cmsghdr(cmsg: &'a libc::cmsghdr) -> Result<Self, AncillaryError> {
+	/// Create a `AncillaryData::ScmSecurity` variant.
+	///
+	/// The trailing null byte of the security label is removed, if present.
+	#[cfg(any(target_os = "android", target_os = "linux",))]
+	#[allow(clippy::wrong_self_convention)]
+	fn as_security(data: &'a [u8]) -> Self {
+		let data = data.strip_suffix(&[0]).unwrap_or(data);
+		AncillaryData::ScmSecurity(OsStr::from_bytes(data))
+	}
+
+	fn try_from_cmsghdr(cmsg: &'a libc::cmsghdr, released: Option<&'a ReleasedFds>) -> Result<Self, AncillaryError> {
 		unsafe {
-			let cmsg_len_zero = libc::CMSG_LEN(0) as CmsgLen;
-			let data_len = (*cmsg).cmsg_len - cmsg_len_zero;
-			let data = libc::CMSG_DATA(cmsg).cast();
-			let data = from_raw_parts(data, data_len as usize);
-
-			match (*cmsg).cmsg_level {
-				libc::SOL_SOCKET => match (*cmsg).cmsg_type {
-					libc::SCM_RIGHTS => Ok(AncillaryData::as_rights(data)),
+			let data = cmsg_data(cmsg);
+
+			match cmsg.cmsg_level {
+				libc::SOL_SOCKET => match cmsg.cmsg_type {
+					libc::SCM_RIGHTS => Ok(AncillaryData::as_rights(data, released)),
+					#[cfg(any(target_os = "android", target_os = "dragonfly", target_os = "freebsd", target_os = "linux",))]
+					SCM_CREDS_TYPE => Ok(AncillaryData::as_credentials(data)),
 					#[cfg(any(target_os = "android", target_os = "linux",))]
-					libc::SCM_CREDENTIALS => Ok(AncillaryData::as_credentials(data)),
+					SCM_SECURITY => Ok(AncillaryData::as_security(data)),
 					cmsg_type => Err(AncillaryError::Unknown {
 						cmsg_level: libc::SOL_SOCKET,
 						cmsg_type,
@@ -269,53 +505,497 @@ impl<'a> AncillaryData<'a> {
 				},
 				cmsg_level => Err(AncillaryError::Unknown {
 					cmsg_level,
-					cmsg_type: (*cmsg).cmsg_type,
+					cmsg_type: cmsg.cmsg_type,
 				}),
 			}
 		}
 	}
 }
 
+/// Get the data of a control message.
+///
+/// # Safety
+///
+/// `cmsg` must be a valid control message header followed by its data.
+unsafe fn cmsg_data(cmsg: &libc::cmsghdr) -> &[u8] {
+	let cmsg_len_zero = libc::CMSG_LEN(0) as CmsgLen;
+	let data_len = cmsg.cmsg_len.saturating_sub(cmsg_len_zero);
+	let data = libc::CMSG_DATA(cmsg).cast();
+	from_raw_parts(data, data_len as usize)
+}
+
+/// Get the control message header after `current` in `buffer`, or the first one if `current` is `None`.
+fn next_cmsghdr<'a>(buffer: &'a [u8], current: &mut Option<&'a libc::cmsghdr>) -> Option<&'a libc::cmsghdr> {
+	unsafe {
+		let mut msg: libc::msghdr = zeroed();
+		msg.msg_control = buffer.as_ptr() as *mut _;
+		msg.msg_controllen = buffer.len() as CmsgLen;
+
+		let cmsg = if let Some(current) = *current {
+			libc::CMSG_NXTHDR(&msg, current)
+		} else {
+			libc::CMSG_FIRSTHDR(&msg)
+		};
+
+		let cmsg = cmsg.as_ref()?;
+		*current = Some(cmsg);
+		Some(cmsg)
+	}
+}
+
 /// This struct is used to iterate through the control messages.
 pub struct Messages<'a> {
 	buffer: &'a [u8],
 	current: Option<&'a libc::cmsghdr>,
+	released: Option<&'a ReleasedFds>,
 }
 
 impl<'a> Iterator for Messages<'a> {
 	type Item = Result<AncillaryData<'a>, AncillaryError>;
 
 	fn next(&mut self) -> Option<Self::Item> {
-		unsafe {
-			let mut msg: libc::msghdr = zeroed();
-			msg.msg_control = self.buffer.as_ptr() as *mut _;
-			msg.msg_controllen = self.buffer.len() as CmsgLen;
+		let cmsg = next_cmsghdr(self.buffer, &mut self.current)?;
+		Some(AncillaryData::try_from_cmsghdr(cmsg, self.released))
+	}
+}
+
+/// A control message that has not been decoded.
+///
+/// This gives access to control messages that are not supported by [`AncillaryData`],
+/// such as `SCM_TIMESTAMPING` or protocol specific messages.
+#[derive(Debug, Clone, Copy)]
+pub struct RawMessage<'a> {
+	cmsg_level: i32,
+	cmsg_type: i32,
+	data: &'a [u8],
+}
 
-			let cmsg = if let Some(current) = self.current {
-				libc::CMSG_NXTHDR(&msg, current)
-			} else {
-				libc::CMSG_FIRSTHDR(&msg)
-			};
+impl<'a> RawMessage<'a> {
+	/// The `cmsg_level` field of the control message.
+	pub fn cmsg_level(&self) -> i32 {
+		self.cmsg_level
+	}
 
-			let cmsg = cmsg.as_ref()?;
-			self.current = Some(cmsg);
-			let ancillary_result = AncillaryData::try_from_cmsghdr(cmsg);
-			Some(ancillary_result)
+	/// The `cmsg_type` field of the control message.
+	pub fn cmsg_type(&self) -> i32 {
+		self.cmsg_type
+	}
+
+	/// The data of the control message, without the header and padding.
+	pub fn data(&self) -> &'a [u8] {
+		self.data
+	}
+}
+
+/// This struct is used to iterate through the control messages without decoding them.
+///
+/// Returned by [`SocketAncillary::raw_messages()`].
+pub struct RawMessages<'a> {
+	buffer: &'a [u8],
+	current: Option<&'a libc::cmsghdr>,
+}
+
+impl<'a> Iterator for RawMessages<'a> {
+	type Item = RawMessage<'a>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let cmsg = next_cmsghdr(self.buffer, &mut self.current)?;
+		Some(RawMessage {
+			cmsg_level: cmsg.cmsg_level,
+			cmsg_type: cmsg.cmsg_type,
+			data: unsafe { cmsg_data(cmsg) },
+		})
+	}
+}
+
+/// A control message with owned contents.
+///
+/// Returned by the iterator from [`SocketAncillary::into_messages()`].
+///
+/// More control message types may be added in the future, so matching on it requires a wildcard arm.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum OwnedAncillaryData {
+	/// Ancillary data holding file descriptors.
+	ScmRights(Vec<ReceivedFd>),
+
+	/// Ancillary data holding unix credentials.
+	#[cfg(any(
+		doc,
+		target_os = "android",
+		target_os = "dragonfly",
+		target_os = "freebsd",
+		target_os = "linux",
+	))]
+	ScmCredentials(Vec<SocketCred>),
+
+	/// Ancillary data holding the security label of the sending process.
+	#[cfg(any(doc, target_os = "android", target_os = "linux",))]
+	ScmSecurity(OsString),
+}
+
+impl OwnedAncillaryData {
+	/// Take ownership of the contents of a received control message.
+	///
+	/// # Safety
+	///
+	/// The file descriptors in the control message must have been received from the kernel and not be owned by anything else.
+	unsafe fn from_received(data: AncillaryData<'_>) -> Self {
+		match data {
+			AncillaryData::ScmRights(rights) => Self::ScmRights(rights.map(|fd| ReceivedFd(OwnedFd::from_raw_fd(fd))).collect()),
+			#[cfg(any(target_os = "android", target_os = "dragonfly", target_os = "freebsd", target_os = "linux",))]
+			AncillaryData::ScmCredentials(creds) => Self::ScmCredentials(creds.collect()),
+			#[cfg(any(target_os = "android", target_os = "linux",))]
+			AncillaryData::ScmSecurity(label) => Self::ScmSecurity(label.to_owned()),
 		}
 	}
 }
 
+/// A file descriptor received from a peer in a `SCM_RIGHTS` control message.
+///
+/// The file descriptor is closed when dropped.
+/// It can be converted into a more specific type with one of the `into_*()` functions,
+/// which verify that the file descriptor has the right type.
+/// If the conversion fails, the file descriptor is closed.
+#[derive(Debug)]
+pub struct ReceivedFd(OwnedFd);
+
+impl ReceivedFd {
+	/// Convert the file descriptor into an [`OwnedFd`] without any checks.
+	pub fn into_owned_fd(self) -> OwnedFd {
+		self.0
+	}
+
+	/// Convert the file descriptor into a [`std::fs::File`].
+	///
+	/// This fails if the file descriptor refers to a socket.
+	pub fn into_file(self) -> std::io::Result<std::fs::File> {
+		let mut stat: libc::stat = unsafe { zeroed() };
+		if unsafe { libc::fstat(self.0.as_raw_fd(), &mut stat) } != 0 {
+			return Err(std::io::Error::last_os_error());
+		}
+		if stat.st_mode & libc::S_IFMT == libc::S_IFSOCK {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::InvalidInput,
+				format!("file descriptor {} is a socket, not a file", self.0.as_raw_fd()),
+			));
+		}
+		Ok(self.0.into())
+	}
+
+	/// Convert the file descriptor into a blocking [`std::os::unix::net::UnixStream`].
+	///
+	/// This fails if the file descriptor is not a unix stream socket.
+	pub fn into_std_unix_stream(self) -> std::io::Result<std::os::unix::net::UnixStream> {
+		crate::check_socket_type(self.0.as_raw_fd(), libc::SOCK_STREAM)?;
+		Ok(self.0.into())
+	}
+
+	/// Convert the file descriptor into a [`tokio::net::UnixStream`] and register it with the tokio runtime.
+	///
+	/// This fails if the file descriptor is not a unix stream socket.
+	/// The socket is put in non-blocking mode.
+	pub fn into_unix_stream(self) -> std::io::Result<tokio::net::UnixStream> {
+		let stream = self.into_std_unix_stream()?;
+		stream.set_nonblocking(true)?;
+		tokio::net::UnixStream::from_std(stream)
+	}
+
+	/// Convert the file descriptor into a [`UnixSeqpacket`](crate::UnixSeqpacket) and register it with the tokio runtime.
+	///
+	/// See [`UnixSeqpacket::try_from_fd()`](crate::UnixSeqpacket::try_from_fd) for the checks that are performed.
+	pub fn into_seqpacket(self) -> std::io::Result<crate::UnixSeqpacket> {
+		crate::UnixSeqpacket::try_from_fd(self.0)
+	}
+}
+
+impl From<ReceivedFd> for OwnedFd {
+	fn from(other: ReceivedFd) -> Self {
+		other.0
+	}
+}
+
+impl AsFd for ReceivedFd {
+	fn as_fd(&self) -> BorrowedFd<'_> {
+		self.0.as_fd()
+	}
+}
+
+impl AsRawFd for ReceivedFd {
+	fn as_raw_fd(&self) -> RawFd {
+		self.0.as_raw_fd()
+	}
+}
+
+impl IntoRawFd for ReceivedFd {
+	fn into_raw_fd(self) -> RawFd {
+		self.0.into_raw_fd()
+	}
+}
+
+/// Iterator over received control messages that takes ownership of received file descriptors.
+///
+/// File descriptors in messages that are not consumed from the iterator are closed when it is dropped.
+#[derive(Debug)]
+pub struct OwnedMessages {
+	messages: std::vec::IntoIter<Result<OwnedAncillaryData, AncillaryError>>,
+}
+
+impl Iterator for OwnedMessages {
+	type Item = Result<OwnedAncillaryData, AncillaryError>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		self.messages.next()
+	}
+}
+
+/// Marker for received file descriptors that have been claimed by the user.
+const CLAIMED_FD: RawFd = -1;
+
+/// Received file descriptors that were handed out as raw file descriptors by [`ScmRights`].
+///
+/// The buffer can not be marked through a shared reference, so they are recorded here instead.
+type ReleasedFds = Mutex<Vec<RawFd>>;
+
+/// Lock the released file descriptors.
+///
+/// The list is always in a consistent state, so a poisoned lock is ignored.
+fn lock_released(released: &ReleasedFds) -> MutexGuard<'_, Vec<RawFd>> {
+	released.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The maximum number of file descriptors that Linux accepts in a single `SCM_RIGHTS` message.
+pub(crate) const SCM_MAX_FD: usize = 253;
+
+/// Get the buffer space needed for a control message with `data_len` bytes of data.
+const fn cmsg_space(data_len: usize) -> usize {
+	let data_len = if data_len > u32::MAX as usize {
+		u32::MAX
+	} else {
+		data_len as u32
+	};
+	unsafe { libc::CMSG_SPACE(data_len) as usize }
+}
+
+/// Get the buffer space needed for a control message holding `count` file descriptors.
+///
+/// This can be used to size a buffer for [`SocketAncillary::new()`],
+/// or to reserve space with [`SocketAncillary::with_capacity()`] or [`SocketAncillary::reserve()`].
+/// The space for multiple control messages is the sum of the space needed for each message.
+///
+/// # Example
+///
+/// ```no_run
+/// use tokio_seqpacket::ancillary::{ancillary_space_for_fds, SocketAncillary};
+///
+/// #[repr(align(8))]
+/// struct Buffer([u8; ancillary_space_for_fds(2)]);
+///
+/// let mut buffer = Buffer([0; ancillary_space_for_fds(2)]);
+/// let mut ancillary = SocketAncillary::new(&mut buffer.0);
+/// assert!(ancillary.add_fds(&[0, 1]));
+/// ```
+pub const fn ancillary_space_for_fds(count: usize) -> usize {
+	cmsg_space(count.saturating_mul(size_of::<RawFd>()))
+}
+
+/// Get the buffer space needed for a control message holding one set of credentials.
+///
+/// See [`ancillary_space_for_fds()`] for more information.
+#[cfg(any(
+	doc,
+	target_os = "android",
+	target_os = "dragonfly",
+	target_os = "freebsd",
+	target_os = "linux",
+))]
+pub const fn ancillary_space_for_creds() -> usize {
+	creds_space(1)
+}
+
+/// Get the buffer space needed for a control message holding `count` credentials.
+#[cfg(any(target_os = "android", target_os = "dragonfly", target_os = "freebsd", target_os = "linux",))]
+pub(crate) const fn creds_space(count: usize) -> usize {
+	cmsg_space(count.saturating_mul(size_of::<RawCred>()))
+}
+
+/// Heap allocated buffer for ancillary data, aligned for `cmsghdr`.
+#[derive(Debug)]
+pub(crate) struct AncillaryBuffer {
+	data: Vec<u64>,
+	len: usize,
+}
+
+impl AncillaryBuffer {
+	/// Allocate a zeroed buffer of `len` bytes.
+	pub(crate) fn new(len: usize) -> Self {
+		let words = len.div_ceil(size_of::<u64>());
+		Self { data: vec![0; words], len }
+	}
+
+	/// Get the buffer as a byte slice.
+	pub(crate) fn as_slice(&self) -> &[u8] {
+		unsafe { std::slice::from_raw_parts(self.data.as_ptr().cast(), self.len) }
+	}
+
+	/// Get the buffer as a mutable byte slice.
+	pub(crate) fn as_mut_slice(&mut self) -> &mut [u8] {
+		unsafe { std::slice::from_raw_parts_mut(self.data.as_mut_ptr().cast(), self.len) }
+	}
+}
+
+/// The buffer of a [`SocketAncillary`]: either borrowed from the caller or allocated on the heap.
+///
+/// A borrowed buffer may be uninitialized.
+/// Only the bytes written by the kernel or by [`add_to_ancillary_data()`] may be read.
+#[derive(Debug)]
+pub(crate) enum AncillaryStorage<'a> {
+	Borrowed(&'a mut [MaybeUninit<u8>]),
+	Owned(AncillaryBuffer),
+}
+
+impl<'a> AncillaryStorage<'a> {
+	/// Borrow a buffer, skipping leading bytes to align the start for `cmsghdr`.
+	fn borrow_aligned(buffer: &'a mut [MaybeUninit<u8>]) -> Self {
+		let offset = buffer.as_ptr().align_offset(align_of::<libc::cmsghdr>());
+		let offset = offset.min(buffer.len());
+		Self::Borrowed(&mut buffer[offset..])
+	}
+
+	/// Get the size of the buffer in bytes.
+	pub(crate) fn capacity(&self) -> usize {
+		match self {
+			Self::Borrowed(buffer) => buffer.len(),
+			Self::Owned(buffer) => buffer.as_slice().len(),
+		}
+	}
+
+	/// Get the buffer as a slice of possibly uninitialized bytes.
+	pub(crate) fn as_uninit_mut(&mut self) -> &mut [MaybeUninit<u8>] {
+		match self {
+			Self::Borrowed(buffer) => buffer,
+			Self::Owned(buffer) => {
+				// Safety: the buffer is initialized, and we never write uninitialized bytes to it.
+				let buffer = buffer.as_mut_slice();
+				unsafe { std::slice::from_raw_parts_mut(buffer.as_mut_ptr().cast(), buffer.len()) }
+			},
+		}
+	}
+
+	/// Get a pointer to the start of the buffer.
+	pub(crate) fn as_mut_ptr(&mut self) -> *mut u8 {
+		self.as_uninit_mut().as_mut_ptr().cast()
+	}
+
+	/// Get the first `len` bytes of the buffer.
+	///
+	/// # Safety
+	///
+	/// The first `len` bytes of the buffer must be initialized.
+	pub(crate) unsafe fn initialized(&self, len: usize) -> &[u8] {
+		match self {
+			Self::Borrowed(buffer) => from_raw_parts(buffer[..len].as_ptr().cast(), len),
+			Self::Owned(buffer) => &buffer.as_slice()[..len],
+		}
+	}
+
+	/// Get the first `len` bytes of the buffer as a mutable slice.
+	///
+	/// # Safety
+	///
+	/// The first `len` bytes of the buffer must be initialized.
+	pub(crate) unsafe fn initialized_mut(&mut self, len: usize) -> &mut [u8] {
+		let buffer = &mut self.as_uninit_mut()[..len];
+		std::slice::from_raw_parts_mut(buffer.as_mut_ptr().cast(), len)
+	}
+}
+
 /// A Unix socket Ancillary data struct.
+///
+/// The buffer for the control messages can be provided by the caller with [`Self::new()`],
+/// or it can be allocated on the heap with [`Self::with_capacity()`].
+/// A heap allocated buffer grows automatically when adding control messages,
+/// and can be grown explicitly with [`Self::reserve()`] before receiving.
+///
+/// File descriptors received from the kernel are owned by the ancillary data until they are taken
+/// with [`Self::take_fds()`] or [`Self::into_messages()`], or yielded by [`ScmRights`] from [`Self::messages()`].
+/// File descriptors that are not taken are closed when the ancillary data is dropped, cleared or reused.
+///
+/// # Reuse
+///
+/// The same ancillary data can be used for any number of send and receive calls.
+/// The buffer is never reallocated when it is reused, so heap allocated buffers keep their capacity.
+///
+/// * Each successful receive call replaces the previous contents, including the [`Self::truncated()`] flag.
+///   A failed receive call leaves the previous contents intact.
+/// * Adding control messages after a receive call discards the received control messages first.
+/// * Sending does not modify the control messages, so the same ancillary data can be sent again.
+///   Received control messages can be forwarded to another socket as long as no file descriptors were taken.
+/// * [`Self::clear()`] removes all control messages explicitly.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn foo(socket: tokio_seqpacket::UnixSeqpacket) -> std::io::Result<()> {
+/// use std::io::IoSliceMut;
+/// use tokio_seqpacket::ancillary::{ancillary_space_for_fds, SocketAncillary};
+///
+/// let mut ancillary = SocketAncillary::with_capacity(ancillary_space_for_fds(8));
+/// let mut buffer = [0; 1024];
+/// loop {
+///     let len = socket.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary).await?;
+///     let fds = ancillary.take_fds();
+///     // Process the message and file descriptors...
+/// }
+/// # }
+/// ```
 #[derive(Debug)]
 pub struct SocketAncillary<'a> {
-	pub(crate) buffer: &'a mut [u8],
+	pub(crate) buffer: AncillaryStorage<'a>,
 	pub(crate) length: usize,
 	pub(crate) truncated: bool,
+	pub(crate) received: bool,
+	released: ReleasedFds,
+}
+
+/// Ancillary data with a heap allocated buffer.
+///
+/// See [`SocketAncillary::with_capacity()`].
+pub type AncillaryBuf = SocketAncillary<'static>;
+
+impl SocketAncillary<'static> {
+	/// Create an ancillary data with a heap allocated buffer of `capacity` bytes.
+	///
+	/// The buffer is properly aligned for control messages.
+	/// It grows automatically when adding control messages that do not fit.
+	/// When receiving, the buffer does not grow, so make sure to reserve enough space up front.
+	///
+	/// # Example
+	///
+	/// ```no_run
+	/// use tokio_seqpacket::ancillary::SocketAncillary;
+	/// let mut ancillary = SocketAncillary::with_capacity(0);
+	/// ancillary.add_fds(&[0, 1, 2]);
+	/// ```
+	pub fn with_capacity(capacity: usize) -> Self {
+		SocketAncillary {
+			buffer: AncillaryStorage::Owned(AncillaryBuffer::new(capacity)),
+			length: 0,
+			truncated: false,
+			received: false,
+			released: Mutex::new(Vec::new()),
+		}
+	}
 }
 
 impl<'a> SocketAncillary<'a> {
 	/// Create an ancillary data with the given buffer.
 	///
+	/// Control messages must be aligned for `cmsghdr`.
+	/// If the buffer is not aligned, the leading bytes are skipped,
+	/// so the [capacity](Self::capacity) can be a few bytes less than the length of the buffer.
+	/// Use [`ancillary_space_for_fds()`] and [`ancillary_space_for_creds()`] to calculate the required size.
+	///
 	/// # Example
 	///
 	/// ```no_run
@@ -324,16 +1004,59 @@ impl<'a> SocketAncillary<'a> {
 	/// let mut ancillary = SocketAncillary::new(&mut ancillary_buffer[..]);
 	/// ```
 	pub fn new(buffer: &'a mut [u8]) -> Self {
+		// Safety: `MaybeUninit<u8>` has the same layout as `u8`, and we never write uninitialized bytes to the buffer.
+		let buffer = unsafe { std::slice::from_raw_parts_mut(buffer.as_mut_ptr().cast(), buffer.len()) };
+		Self::new_uninit(buffer)
+	}
+
+	/// Create an ancillary data with the given possibly uninitialized buffer.
+	///
+	/// The buffer does not need to be initialized, which avoids zeroing the buffer for every message.
+	/// Like [`Self::new()`], leading bytes are skipped if the buffer is not aligned for `cmsghdr`.
+	///
+	/// # Example
+	///
+	/// ```no_run
+	/// use std::mem::MaybeUninit;
+	/// use tokio_seqpacket::ancillary::{ancillary_space_for_fds, SocketAncillary};
+	///
+	/// let mut ancillary_buffer = [MaybeUninit::uninit(); ancillary_space_for_fds(4) + 8];
+	/// let mut ancillary = SocketAncillary::new_uninit(&mut ancillary_buffer);
+	/// assert!(ancillary.capacity() >= ancillary_space_for_fds(4));
+	/// ```
+	pub fn new_uninit(buffer: &'a mut [MaybeUninit<u8>]) -> Self {
 		SocketAncillary {
-			buffer,
+			buffer: AncillaryStorage::borrow_aligned(buffer),
 			length: 0,
 			truncated: false,
+			received: false,
+			released: Mutex::new(Vec::new()),
 		}
 	}
 
 	/// Returns the capacity of the buffer.
 	pub fn capacity(&self) -> usize {
-		self.buffer.len()
+		self.buffer.capacity()
+	}
+
+	/// Make sure the buffer has room for at least `additional` more bytes of control messages.
+	///
+	/// If the buffer is borrowed and too small, the contents are moved to a new heap allocated buffer.
+	/// Note that this changes where received data is stored: the borrowed buffer is no longer used.
+	pub fn reserve(&mut self, additional: usize) {
+		let required = self.length.saturating_add(additional);
+		if required <= self.capacity() {
+			return;
+		}
+		let capacity = required.max(self.capacity().saturating_mul(2));
+		let mut buffer = AncillaryBuffer::new(capacity);
+		buffer.as_mut_slice()[..self.length].copy_from_slice(unsafe { self.buffer.initialized(self.length) });
+		self.buffer = AncillaryStorage::Owned(buffer);
+	}
+
+	/// Check if the buffer is heap allocated and can grow automatically.
+	fn can_grow(&self) -> bool {
+		matches!(self.buffer, AncillaryStorage::Owned(_))
 	}
 
 	/// Returns the number of used bytes.
@@ -347,28 +1070,202 @@ impl<'a> SocketAncillary<'a> {
 	}
 
 	/// Returns the iterator of the control messages.
+	///
+	/// Received file descriptors yielded by [`ScmRights`] are owned by the caller from then on.
+	/// Other received file descriptors remain owned by the ancillary data.
+	/// They are closed when the ancillary data is dropped, cleared or reused,
+	/// unless they are taken with [`Self::take_fds()`] first.
 	pub fn messages(&self) -> Messages<'_> {
 		Messages {
-			buffer: &self.buffer[..self.length],
+			buffer: unsafe { self.buffer.initialized(self.length) },
+			current: None,
+			released: if self.received { Some(&self.released) } else { None },
+		}
+	}
+
+	/// Returns an iterator over the control messages that does not decode them.
+	///
+	/// Each item gives the `cmsg_level`, `cmsg_type` and data of a control message,
+	/// which is useful for message types that are not supported by [`Self::messages()`].
+	///
+	/// Note that received file descriptors in `SCM_RIGHTS` messages are still owned by the ancillary data.
+	/// File descriptors taken with [`Self::take_fds()`] or [`Self::into_messages()`] are reported as `-1`.
+	pub fn raw_messages(&self) -> RawMessages<'_> {
+		RawMessages {
+			buffer: unsafe { self.buffer.initialized(self.length) },
 			current: None,
 		}
 	}
 
+	/// Take ownership of all received file descriptors that have not been taken yet.
+	///
+	/// The file descriptors are no longer reported by [`Self::messages()`],
+	/// and they will not be closed when the ancillary data is dropped.
+	///
+	/// If the ancillary data was not received from a socket, no file descriptors are returned.
+	pub fn take_fds(&mut self) -> Vec<ReceivedFd> {
+		let mut fds = Vec::new();
+		// Safety: claimed file descriptors are marked in the buffer, so they can not be taken twice.
+		self.claim_fds(|fd| fds.push(ReceivedFd(unsafe { OwnedFd::from_raw_fd(fd) })));
+		fds
+	}
+
+	/// Count the file descriptors in all `SCM_RIGHTS` control messages.
+	#[cfg(any(feature = "metrics", feature = "tracing"))]
+	pub(crate) fn fd_count(&self) -> usize {
+		self.raw_messages()
+			.filter(|message| message.cmsg_level() == libc::SOL_SOCKET && message.cmsg_type() == libc::SCM_RIGHTS)
+			.map(|message| message.data().len() / std::mem::size_of::<RawFd>())
+			.sum()
+	}
+
+	/// Close all received file descriptors that have not been taken yet.
+	fn close_unclaimed_fds(&mut self) {
+		// Safety: we own the unclaimed file descriptors, and they are marked as claimed after closing.
+		self.claim_fds(|fd| drop(unsafe { OwnedFd::from_raw_fd(fd) }));
+	}
+
+	/// Get the received file descriptors that have not been taken yet, without claiming them.
+	///
+	/// The buffer is not modified, so the file descriptors are still owned by the ancillary data.
+	pub(crate) fn unclaimed_fds(&self) -> Vec<RawFd> {
+		if !self.received {
+			return Vec::new();
+		}
+		let released = lock_released(&self.released);
+		self.raw_messages()
+			.filter(|message| message.cmsg_level() == libc::SOL_SOCKET && message.cmsg_type() == libc::SCM_RIGHTS)
+			// Safety: the data of an `SCM_RIGHTS` message is an array of file descriptors.
+			.flat_map(|message| unsafe { AncillaryDataIter::<RawFd>::new(message.data()) })
+			.filter(|fd| *fd != CLAIMED_FD && !released.contains(fd))
+			.collect()
+	}
+
+	/// Record that the kernel overwrote the buffer with a received message.
+	///
+	/// `previous_fds` are the file descriptors from [`Self::unclaimed_fds()`] before receiving.
+	/// They belonged to the overwritten message, so they are closed.
+	pub(crate) fn set_received(&mut self, length: usize, truncated: bool, previous_fds: Vec<RawFd>) {
+		for fd in previous_fds {
+			// Safety: the file descriptors were owned by the ancillary data and are no longer in the buffer.
+			drop(unsafe { OwnedFd::from_raw_fd(fd) });
+		}
+		self.released.get_mut().unwrap_or_else(PoisonError::into_inner).clear();
+		self.length = length;
+		self.truncated = truncated;
+		self.received = true;
+	}
+
+	/// Call a function for each unclaimed received file descriptor and mark it as claimed.
+	///
+	/// File descriptors that were handed out by [`ScmRights`] are marked as claimed without calling the function.
+	fn claim_fds(&mut self, mut claim: impl FnMut(RawFd)) {
+		let released = std::mem::take(self.released.get_mut().unwrap_or_else(PoisonError::into_inner));
+		if !self.received {
+			return;
+		}
+
+		let buffer = unsafe { self.buffer.initialized_mut(self.length) };
+		unsafe {
+			let mut msg: libc::msghdr = zeroed();
+			msg.msg_control = buffer.as_mut_ptr().cast();
+			msg.msg_controllen = buffer.len() as CmsgLen;
+
+			let mut cmsg = libc::CMSG_FIRSTHDR(&msg);
+			while !cmsg.is_null() {
+				if (*cmsg).cmsg_level == libc::SOL_SOCKET && (*cmsg).cmsg_type == libc::SCM_RIGHTS {
+					let data: *mut RawFd = libc::CMSG_DATA(cmsg).cast();
+					let data_len = ((*cmsg).cmsg_len as usize).saturating_sub(libc::CMSG_LEN(0) as usize);
+					for i in 0..data_len / size_of::<RawFd>() {
+						let fd = data.add(i).read_unaligned();
+						if fd != CLAIMED_FD {
+							data.add(i).write_unaligned(CLAIMED_FD);
+							if !released.contains(&fd) {
+								claim(fd);
+							}
+						}
+					}
+				}
+				cmsg = libc::CMSG_NXTHDR(&msg, cmsg);
+			}
+		}
+	}
+
+	/// Consume the ancillary data and iterate over the control messages, taking ownership of received file descriptors.
+	///
+	/// File descriptors are returned as [`OwnedFd`], so they are closed when dropped.
+	/// File descriptors in messages that are not consumed from the iterator are closed when the iterator is dropped.
+	/// This prevents leaking file descriptors that were sent by the peer but are not used.
+	///
+	/// Only ancillary data filled in by a receive operation is decoded.
+	/// If the ancillary data was not received from a socket, the iterator is empty,
+	/// since the file descriptors are not owned by the ancillary data in that case.
+	///
+	/// # Example
+	/// ```no_run
+	/// # async fn foo(socket: tokio_seqpacket::UnixSeqpacket) -> std::io::Result<()> {
+	/// use std::io::IoSliceMut;
+	/// use tokio_seqpacket::ancillary::{OwnedAncillaryData, SocketAncillary};
+	///
+	/// let mut buffer = [0u8; 128];
+	/// let mut ancillary_buffer = [0u8; 128];
+	/// let mut ancillary = SocketAncillary::new(&mut ancillary_buffer);
+	/// socket.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary).await?;
+	/// for message in ancillary.into_messages() {
+	///     if let Ok(OwnedAncillaryData::ScmRights(fds)) = message {
+	///         println!("received {} file descriptors", fds.len());
+	///     }
+	/// }
+	/// # Ok(())
+	/// # }
+	/// ```
+	pub fn into_messages(mut self) -> OwnedMessages {
+		let messages = if self.received {
+			// Safety: the control messages were filled in by the kernel, so we own the unclaimed file descriptors.
+			// They are all decoded immediately, so they can not be taken twice.
+			let messages = self.messages().map(|message| message.map(|data| unsafe { OwnedAncillaryData::from_received(data) }));
+			let messages = messages.collect();
+			self.received = false;
+			self.clear();
+			messages
+		} else {
+			Vec::new()
+		};
+		OwnedMessages {
+			messages: messages.into_iter(),
+		}
+	}
+
 	/// Is `true` if during a recv operation the ancillary was truncated.
 	pub fn truncated(&self) -> bool {
 		self.truncated
 	}
 
+	/// Get details about why the ancillary data was truncated during the last recv operation.
+	///
+	/// Returns `None` if the ancillary data was not truncated.
+	pub fn truncation(&self) -> Option<crate::AncillaryTruncatedError> {
+		if self.truncated {
+			Some(crate::AncillaryTruncatedError::new(self.capacity(), self.length))
+		} else {
+			None
+		}
+	}
+
 	/// Add file descriptors to the ancillary data.
 	///
 	/// The function returns `true` if there was enough space in the buffer.
 	/// If there was not enough space then no file descriptors was appended.
+	/// A heap allocated buffer grows to make room for the file descriptors.
 	/// Technically, that means this operation adds a control message with the level `SOL_SOCKET`
 	/// and type `SCM_RIGHTS`.
 	pub fn add_fds(&mut self, fds: &[RawFd]) -> bool {
-		self.truncated = false;
+		self.discard_received();
+		if self.can_grow() {
+			self.reserve(ancillary_space_for_fds(fds.len()));
+		}
 		add_to_ancillary_data(
-			&mut self.buffer,
+			self.buffer.as_uninit_mut(),
 			&mut self.length,
 			fds,
 			libc::SOL_SOCKET,
@@ -380,24 +1277,162 @@ impl<'a> SocketAncillary<'a> {
 	///
 	/// The function returns `true` if there was enough space in the buffer.
 	/// If there was not enough space then no credentials was appended.
+	/// A heap allocated buffer grows to make room for the credentials.
 	/// Technically, that means this operation adds a control message with the level `SOL_SOCKET`
-	/// and type `SCM_CREDENTIALS` or `SCM_CREDS`.
+	/// and type `SCM_CREDENTIALS` (Linux and Android) or `SCM_CREDS` (FreeBSD and DragonFly).
 	///
-	#[cfg(any(doc, target_os = "android", target_os = "linux",))]
+	/// The credentials can be combined with file descriptors in the same message.
+	#[cfg(any(
+		doc,
+		target_os = "android",
+		target_os = "dragonfly",
+		target_os = "freebsd",
+		target_os = "linux",
+	))]
 	pub fn add_creds(&mut self, creds: &[SocketCred]) -> bool {
-		self.truncated = false;
+		self.discard_received();
+		if self.can_grow() {
+			self.reserve(creds_space(creds.len()));
+		}
 		add_to_ancillary_data(
-			&mut self.buffer,
+			self.buffer.as_uninit_mut(),
 			&mut self.length,
 			creds,
 			libc::SOL_SOCKET,
-			libc::SCM_CREDENTIALS,
+			SCM_CREDS_TYPE,
 		)
 	}
 
 	/// Clears the ancillary data, removing all values.
+	///
+	/// Received file descriptors that have not been taken are closed.
+	/// The capacity of the buffer is retained.
 	pub fn clear(&mut self) {
+		self.close_unclaimed_fds();
 		self.length = 0;
 		self.truncated = false;
+		self.received = false;
+	}
+
+	/// Discard received control messages, so that new control messages can be added.
+	///
+	/// Control messages that were added by the user are kept.
+	fn discard_received(&mut self) {
+		if self.received {
+			self.clear();
+		}
+		self.truncated = false;
+	}
+}
+
+impl<'a> Drop for SocketAncillary<'a> {
+	fn drop(&mut self) {
+		self.close_unclaimed_fds();
+	}
+}
+
+/// Builder to compose ancillary data for sending.
+///
+/// The builder collects the control messages and allocates a buffer of exactly the right size when it is built,
+/// so there is no need to check the result of [`SocketAncillary::add_fds()`] or [`SocketAncillary::add_creds()`].
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn foo(socket: tokio_seqpacket::UnixSeqpacket, file: std::fs::File) -> std::io::Result<()> {
+/// use std::io::IoSlice;
+/// use std::os::unix::io::AsFd;
+/// use tokio_seqpacket::ancillary::AncillaryBuilder;
+///
+/// let mut ancillary = AncillaryBuilder::new()
+///     .with_fd(file.as_fd())
+///     .build()?;
+/// socket.send_vectored_with_ancillary(&[IoSlice::new(b"hello")], &mut ancillary).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AncillaryBuilder<'a> {
+	fds: Vec<BorrowedFd<'a>>,
+	#[cfg(any(
+		target_os = "android",
+		target_os = "dragonfly",
+		target_os = "freebsd",
+		target_os = "linux",
+	))]
+	creds: Vec<SocketCred>,
+}
+
+impl<'a> AncillaryBuilder<'a> {
+	/// Create a new builder without any control messages.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Add file descriptors to send with the message.
+	///
+	/// All file descriptors are sent in a single `SCM_RIGHTS` control message.
+	pub fn with_fds(mut self, fds: &[BorrowedFd<'a>]) -> Self {
+		self.fds.extend_from_slice(fds);
+		self
+	}
+
+	/// Add a single file descriptor to send with the message.
+	pub fn with_fd(mut self, fd: BorrowedFd<'a>) -> Self {
+		self.fds.push(fd);
+		self
+	}
+
+	/// Add credentials to send with the message.
+	///
+	/// See [`SocketAncillary::add_creds()`] for more information.
+	#[cfg(any(
+		doc,
+		target_os = "android",
+		target_os = "dragonfly",
+		target_os = "freebsd",
+		target_os = "linux",
+	))]
+	pub fn with_creds(mut self, creds: SocketCred) -> Self {
+		self.creds.push(creds);
+		self
+	}
+
+	/// Get the buffer space needed for the control messages.
+	pub fn space(&self) -> usize {
+		let mut space = 0;
+		if !self.fds.is_empty() {
+			space += ancillary_space_for_fds(self.fds.len());
+		}
+		#[cfg(any(target_os = "android", target_os = "dragonfly", target_os = "freebsd", target_os = "linux",))]
+		if !self.creds.is_empty() {
+			space += creds_space(self.creds.len());
+		}
+		space
+	}
+
+	/// Create the ancillary data with a heap allocated buffer of the right size.
+	///
+	/// This fails if the control messages are too large to be represented.
+	/// The kernel may still refuse to send the message, for example if too many file descriptors are sent at once.
+	pub fn build(&self) -> std::io::Result<AncillaryBuf> {
+		let mut ancillary = SocketAncillary::with_capacity(self.space());
+		if !self.fds.is_empty() {
+			let fds: Vec<RawFd> = self.fds.iter().map(|fd| fd.as_raw_fd()).collect();
+			if !ancillary.add_fds(&fds) {
+				return Err(std::io::Error::new(
+					std::io::ErrorKind::InvalidInput,
+					"too many file descriptors for a single message",
+				));
+			}
+		}
+		#[cfg(any(target_os = "android", target_os = "dragonfly", target_os = "freebsd", target_os = "linux",))]
+		if !self.creds.is_empty() && !ancillary.add_creds(&self.creds) {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::InvalidInput,
+				"too many credentials for a single message",
+			));
+		}
+		Ok(ancillary)
 	}
 }
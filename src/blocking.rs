@@ -0,0 +1,188 @@
+//! Blocking seqpacket sockets that are not registered with a tokio runtime.
+//!
+//! These types can be used to create sockets before a tokio runtime is started,
+//! for example while the process still has elevated privileges.
+//! They can be converted into their asynchronous counterparts later.
+
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
+use std::os::unix::net::SocketAddr;
+use std::path::Path;
+
+/// Blocking listener for Unix seqpacket sockets.
+///
+/// Use [`crate::UnixSeqpacketListener::from_std()`] to convert it into an asynchronous listener.
+pub struct UnixSeqpacketListener {
+	socket: socket2::Socket,
+	backlog: Option<std::os::raw::c_int>,
+}
+
+impl std::fmt::Debug for UnixSeqpacketListener {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		f.debug_struct("UnixSeqpacketListener")
+			.field("fd", &self.socket.as_raw_fd())
+			.field("backlog", &self.backlog)
+			.finish()
+	}
+}
+
+impl UnixSeqpacketListener {
+	pub(crate) fn new(socket: socket2::Socket, backlog: Option<std::os::raw::c_int>) -> Self {
+		Self { socket, backlog }
+	}
+
+	pub(crate) fn into_parts(self) -> (socket2::Socket, Option<std::os::raw::c_int>) {
+		(self.socket, self.backlog)
+	}
+
+	/// Bind a new blocking seqpacket listener to the given address.
+	///
+	/// The listener is created with a backlog of [`crate::UnixSeqpacketListener::DEFAULT_BACKLOG`].
+	pub fn bind<P: AsRef<Path>>(address: P) -> std::io::Result<Self> {
+		Self::bind_with_backlog(address, crate::UnixSeqpacketListener::DEFAULT_BACKLOG)
+	}
+
+	/// Bind a new blocking seqpacket listener to the given address.
+	///
+	/// The `backlog` parameter is used to determine the size of connection queue.
+	/// See `man 3 listen` for more information.
+	pub fn bind_with_backlog<P: AsRef<Path>>(address: P, backlog: std::os::raw::c_int) -> std::io::Result<Self> {
+		let address = crate::path_sockaddr(address)?;
+		let socket = socket2::Socket::new(socket2::Domain::unix(), crate::blocking_socket_type(), None)?;
+		socket.bind(&address)?;
+		socket.listen(backlog)?;
+		Ok(Self::new(socket, Some(backlog)))
+	}
+
+	/// Accept a new incoming connection on the listener.
+	///
+	/// This blocks until a connection is available.
+	pub fn accept(&self) -> std::io::Result<(UnixSeqpacket, SocketAddr)> {
+		let (socket, address) = self.socket.accept()?;
+		let address = crate::sockaddr_as_unix(&address).unwrap();
+		Ok((UnixSeqpacket::new(socket), address))
+	}
+
+	/// Get the socket address the listener is bound to.
+	pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+		let addr = self.socket.local_addr()?;
+		Ok(crate::sockaddr_as_unix(&addr).unwrap())
+	}
+
+	/// Get the value of the `SO_ERROR` option.
+	pub fn take_error(&self) -> std::io::Result<Option<std::io::Error>> {
+		self.socket.take_error()
+	}
+}
+
+impl AsRawFd for UnixSeqpacketListener {
+	fn as_raw_fd(&self) -> RawFd {
+		self.socket.as_raw_fd()
+	}
+}
+
+impl AsFd for UnixSeqpacketListener {
+	fn as_fd(&self) -> BorrowedFd<'_> {
+		// The file descriptor remains open for as long as `self` is borrowed.
+		unsafe { BorrowedFd::borrow_raw(self.as_raw_fd()) }
+	}
+}
+
+impl From<UnixSeqpacketListener> for OwnedFd {
+	fn from(listener: UnixSeqpacketListener) -> Self {
+		unsafe { OwnedFd::from_raw_fd(listener.socket.into_raw_fd()) }
+	}
+}
+
+/// Blocking Unix seqpacket socket.
+///
+/// Use [`crate::UnixSeqpacket::from_std()`] to convert it into an asynchronous socket.
+pub struct UnixSeqpacket {
+	socket: socket2::Socket,
+}
+
+impl std::fmt::Debug for UnixSeqpacket {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		f.debug_struct("UnixSeqpacket")
+			.field("fd", &self.socket.as_raw_fd())
+			.finish()
+	}
+}
+
+impl UnixSeqpacket {
+	pub(crate) fn new(socket: socket2::Socket) -> Self {
+		Self { socket }
+	}
+
+	pub(crate) fn into_socket(self) -> socket2::Socket {
+		self.socket
+	}
+
+	/// Connect a new blocking seqpacket socket to the given address.
+	pub fn connect<P: AsRef<Path>>(address: P) -> std::io::Result<Self> {
+		let address = crate::path_sockaddr(address)?;
+		let socket = socket2::Socket::new(socket2::Domain::unix(), crate::blocking_socket_type(), None)?;
+		socket.connect(&address)?;
+		Ok(Self::new(socket))
+	}
+
+	/// Create a pair of connected blocking seqpacket sockets.
+	pub fn pair() -> std::io::Result<(Self, Self)> {
+		let (a, b) = socket2::Socket::pair(socket2::Domain::unix(), crate::blocking_socket_type(), None)?;
+		Ok((Self::new(a), Self::new(b)))
+	}
+
+	/// Get the socket address of the local half of this connection.
+	pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+		let addr = self.socket.local_addr()?;
+		Ok(crate::sockaddr_as_unix(&addr).unwrap())
+	}
+
+	/// Get the socket address of the remote half of this connection.
+	pub fn peer_addr(&self) -> std::io::Result<SocketAddr> {
+		let addr = self.socket.peer_addr()?;
+		Ok(crate::sockaddr_as_unix(&addr).unwrap())
+	}
+
+	/// Get the value of the `SO_ERROR` option.
+	pub fn take_error(&self) -> std::io::Result<Option<std::io::Error>> {
+		self.socket.take_error()
+	}
+
+	/// Send data on the socket to the connected peer.
+	///
+	/// This blocks until the message could be queued.
+	pub fn send(&self, buffer: &[u8]) -> std::io::Result<usize> {
+		self.socket.send(buffer)
+	}
+
+	/// Receive data on the socket from the connected peer.
+	///
+	/// This blocks until a message is available.
+	pub fn recv(&self, buffer: &mut [u8]) -> std::io::Result<usize> {
+		self.socket.recv(buffer)
+	}
+
+	/// Shuts down the read, write, or both halves of this connection.
+	pub fn shutdown(&self, how: std::net::Shutdown) -> std::io::Result<()> {
+		self.socket.shutdown(how)
+	}
+}
+
+impl AsRawFd for UnixSeqpacket {
+	fn as_raw_fd(&self) -> RawFd {
+		self.socket.as_raw_fd()
+	}
+}
+
+impl AsFd for UnixSeqpacket {
+	fn as_fd(&self) -> BorrowedFd<'_> {
+		// The file descriptor remains open for as long as `self` is borrowed.
+		unsafe { BorrowedFd::borrow_raw(self.as_raw_fd()) }
+	}
+}
+
+impl From<UnixSeqpacket> for OwnedFd {
+	fn from(socket: UnixSeqpacket) -> Self {
+		unsafe { OwnedFd::from_raw_fd(socket.socket.into_raw_fd()) }
+	}
+}
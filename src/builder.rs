@@ -0,0 +1,253 @@
+use std::os::unix::net::SocketAddr;
+use std::path::Path;
+
+use crate::{UnixSeqpacket, UnixSeqpacketListener};
+
+/// Builder to configure seqpacket sockets before they are connected or bound.
+///
+/// Some socket options only take effect when they are set before the socket is connected.
+/// The builder applies all configured options to a new socket before connecting or binding it.
+///
+/// # Example
+/// ```no_run
+/// # async fn foo() -> std::io::Result<()> {
+/// use tokio_seqpacket::UnixSeqpacketBuilder;
+///
+/// let socket = UnixSeqpacketBuilder::new()
+///     .send_buffer_size(1 << 20)
+///     .recv_buffer_size(1 << 20)
+///     .connect("/run/foo.sock")
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct UnixSeqpacketBuilder {
+	send_buffer_size: Option<usize>,
+	recv_buffer_size: Option<usize>,
+	#[cfg(any(target_os = "android", target_os = "linux"))]
+	passcred: Option<bool>,
+	#[cfg(any(target_os = "android", target_os = "linux"))]
+	passsec: Option<bool>,
+	#[cfg(target_os = "linux")]
+	mark: Option<u32>,
+	#[cfg(any(target_os = "android", target_os = "linux"))]
+	busy_poll: Option<u32>,
+	#[cfg(any(target_os = "android", target_os = "linux"))]
+	autobind: bool,
+	cloexec: bool,
+	backlog: std::os::raw::c_int,
+	mode: Option<u32>,
+}
+
+impl Default for UnixSeqpacketBuilder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl UnixSeqpacketBuilder {
+	/// Create a new builder with default options.
+	pub fn new() -> Self {
+		Self {
+			send_buffer_size: None,
+			recv_buffer_size: None,
+			#[cfg(any(target_os = "android", target_os = "linux"))]
+			passcred: None,
+			#[cfg(any(target_os = "android", target_os = "linux"))]
+			passsec: None,
+			#[cfg(target_os = "linux")]
+			mark: None,
+			#[cfg(any(target_os = "android", target_os = "linux"))]
+			busy_poll: None,
+			#[cfg(any(target_os = "android", target_os = "linux"))]
+			autobind: false,
+			cloexec: true,
+			backlog: UnixSeqpacketListener::DEFAULT_BACKLOG,
+			mode: None,
+		}
+	}
+
+	/// Set the size of the send buffer (`SO_SNDBUF`).
+	pub fn send_buffer_size(mut self, size: usize) -> Self {
+		self.send_buffer_size = Some(size);
+		self
+	}
+
+	/// Set the size of the receive buffer (`SO_RCVBUF`).
+	pub fn recv_buffer_size(mut self, size: usize) -> Self {
+		self.recv_buffer_size = Some(size);
+		self
+	}
+
+	/// Enable or disable receiving of `SCM_CREDENTIALS` messages (`SO_PASSCRED`).
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub fn passcred(mut self, passcred: bool) -> Self {
+		self.passcred = Some(passcred);
+		self
+	}
+
+	/// Enable or disable receiving of `SCM_SECURITY` messages (`SO_PASSSEC`).
+	///
+	/// The security label of the sender is only available if a Linux Security Module like SELinux or AppArmor is active.
+	/// See [`AncillaryData::ScmSecurity`](crate::ancillary::AncillaryData::ScmSecurity).
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub fn passsec(mut self, passsec: bool) -> Self {
+		self.passsec = Some(passsec);
+		self
+	}
+
+	/// Set the mark of the socket (`SO_MARK`).
+	///
+	/// Setting the mark requires the `CAP_NET_ADMIN` capability.
+	#[cfg(any(doc, target_os = "linux"))]
+	pub fn mark(mut self, mark: u32) -> Self {
+		self.mark = Some(mark);
+		self
+	}
+
+	/// Set the busy poll timeout of the socket in microseconds (`SO_BUSY_POLL`).
+	///
+	/// Increasing the timeout requires the `CAP_NET_ADMIN` capability.
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub fn busy_poll(mut self, microseconds: u32) -> Self {
+		self.busy_poll = Some(microseconds);
+		self
+	}
+
+	/// Bind connecting sockets to a unique address in the abstract namespace.
+	///
+	/// When enabled, the kernel picks a unique abstract name for the socket before it connects.
+	/// The peer can then see the name with [`UnixSeqpacket::peer_addr()`],
+	/// and the name can be retrieved locally with [`UnixSeqpacket::local_addr()`].
+	///
+	/// This option only affects [`Self::connect()`] and [`Self::connect_addr()`].
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub fn autobind(mut self, autobind: bool) -> Self {
+		self.autobind = autobind;
+		self
+	}
+
+	/// Set or clear the close-on-exec flag of the socket.
+	///
+	/// By default, the close-on-exec flag is set.
+	pub fn cloexec(mut self, cloexec: bool) -> Self {
+		self.cloexec = cloexec;
+		self
+	}
+
+	/// Set the backlog for listeners.
+	///
+	/// This option is ignored when connecting a socket.
+	/// See `man 3 listen` for more information.
+	pub fn backlog(mut self, backlog: std::os::raw::c_int) -> Self {
+		self.backlog = backlog;
+		self
+	}
+
+	/// Set the permissions of the socket file for listeners.
+	///
+	/// The permissions are applied before the listener starts listening.
+	/// This option is ignored when connecting a socket,
+	/// or when binding to an address that is not a filesystem path.
+	pub fn mode(mut self, mode: u32) -> Self {
+		self.mode = Some(mode);
+		self
+	}
+
+	/// Connect a new seqpacket socket to the given address.
+	pub async fn connect<P: AsRef<Path>>(&self, address: P) -> std::io::Result<UnixSeqpacket> {
+		let address = crate::path_sockaddr(address)?;
+		UnixSeqpacket::connect_socket(self.build_connect_socket()?, &address, None).await
+	}
+
+	/// Connect a new seqpacket socket to the given socket address.
+	///
+	/// See [`UnixSeqpacket::connect_addr()`] for more information.
+	pub async fn connect_addr(&self, address: &SocketAddr) -> std::io::Result<UnixSeqpacket> {
+		if address.is_unnamed() {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::InvalidInput,
+				"can not connect to an unnamed socket address",
+			));
+		}
+		let address = crate::unix_as_sockaddr(address)?;
+		UnixSeqpacket::connect_socket(self.build_connect_socket()?, &address, None).await
+	}
+
+	/// Create a pair of connected seqpacket sockets.
+	///
+	/// The configured options are applied to both sockets.
+	pub fn pair(&self) -> std::io::Result<(UnixSeqpacket, UnixSeqpacket)> {
+		let (a, b) = socket2::Socket::pair(socket2::Domain::unix(), crate::socket_type(), None)?;
+		self.apply(&a)?;
+		self.apply(&b)?;
+		Ok((UnixSeqpacket::new(a)?, UnixSeqpacket::new(b)?))
+	}
+
+	/// Bind a new seqpacket listener to the given address.
+	pub fn bind<P: AsRef<Path>>(&self, address: P) -> std::io::Result<UnixSeqpacketListener> {
+		UnixSeqpacketListener::bind_socket_to_path(self.build_socket()?, address.as_ref(), self.backlog, self.mode)
+	}
+
+	/// Bind a new seqpacket listener to the given socket address.
+	///
+	/// See [`UnixSeqpacketListener::bind_addr()`] for more information.
+	pub fn bind_addr(&self, address: &SocketAddr) -> std::io::Result<UnixSeqpacketListener> {
+		if let Some(path) = address.as_pathname() {
+			return self.bind(path);
+		}
+		let address = crate::unix_as_sockaddr(address)?;
+		UnixSeqpacketListener::bind_socket(self.build_socket()?, &address, self.backlog)
+	}
+
+	/// Create a new socket and apply the configured options.
+	pub(crate) fn build_socket(&self) -> std::io::Result<socket2::Socket> {
+		let socket = crate::new_socket()?;
+		self.apply(&socket)?;
+		Ok(socket)
+	}
+
+	/// Create a new socket for connecting, apply the configured options and autobind it if requested.
+	fn build_connect_socket(&self) -> std::io::Result<socket2::Socket> {
+		let socket = self.build_socket()?;
+		#[cfg(any(target_os = "android", target_os = "linux"))]
+		if self.autobind {
+			socket.bind(&crate::autobind_sockaddr())?;
+		}
+		Ok(socket)
+	}
+
+	/// Apply the configured socket options to an existing socket.
+	pub(crate) fn apply(&self, socket: &socket2::Socket) -> std::io::Result<()> {
+		use std::os::unix::io::AsRawFd;
+
+		// Sockets are always created with the close-on-exec flag set.
+		if !self.cloexec {
+			crate::set_cloexec(socket.as_raw_fd(), false)?;
+		}
+		if let Some(size) = self.send_buffer_size {
+			socket.set_send_buffer_size(size)?;
+		}
+		if let Some(size) = self.recv_buffer_size {
+			socket.set_recv_buffer_size(size)?;
+		}
+		#[cfg(any(target_os = "android", target_os = "linux"))]
+		if let Some(passcred) = self.passcred {
+			crate::set_socket_option_int(socket.as_raw_fd(), libc::SOL_SOCKET, libc::SO_PASSCRED, passcred as _)?;
+		}
+		#[cfg(any(target_os = "android", target_os = "linux"))]
+		if let Some(passsec) = self.passsec {
+			crate::set_socket_option_int(socket.as_raw_fd(), libc::SOL_SOCKET, crate::SO_PASSSEC, passsec as _)?;
+		}
+		#[cfg(target_os = "linux")]
+		if let Some(mark) = self.mark {
+			socket.set_mark(mark)?;
+		}
+		#[cfg(any(target_os = "android", target_os = "linux"))]
+		if let Some(busy_poll) = self.busy_poll {
+			crate::set_socket_option_int(socket.as_raw_fd(), libc::SOL_SOCKET, libc::SO_BUSY_POLL, busy_poll as _)?;
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,494 @@
+//! Error types that need more detail than a plain [`std::io::Error`].
+
+use std::path::Path;
+
+/// Error indicating that a socket path does not fit in a `sockaddr_un`.
+///
+/// The path of a unix socket is limited to a small number of bytes (107 on Linux).
+/// Functions that take a socket path report this error wrapped in a [`std::io::Error`] of kind [`std::io::ErrorKind::InvalidInput`].
+/// It can be retrieved with [`std::io::Error::get_ref()`] and downcasting.
+///
+/// Use [`check_socket_path()`] to validate a path before using it.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct PathTooLongError {
+	path_len: usize,
+	max_len: usize,
+}
+
+impl PathTooLongError {
+	/// Get the length of the rejected path in bytes.
+	pub fn path_len(&self) -> usize {
+		self.path_len
+	}
+
+	/// Get the maximum supported length of a socket path in bytes.
+	pub fn max_len(&self) -> usize {
+		self.max_len
+	}
+}
+
+impl std::fmt::Display for PathTooLongError {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(
+			f,
+			"socket path is too long: {} bytes, but the maximum is {} bytes",
+			self.path_len, self.max_len
+		)
+	}
+}
+
+impl std::error::Error for PathTooLongError {}
+
+impl From<PathTooLongError> for std::io::Error {
+	fn from(other: PathTooLongError) -> Self {
+		Self::new(std::io::ErrorKind::InvalidInput, other)
+	}
+}
+
+/// Error indicating that a received message did not fit in the buffer and was discarded.
+///
+/// Returned by [`UnixSeqpacket::recv_or_discard()`][crate::UnixSeqpacket::recv_or_discard]
+/// wrapped in a [`std::io::Error`] of kind [`std::io::ErrorKind::InvalidData`].
+/// It can be retrieved with [`std::io::Error::get_ref()`] and downcasting.
+///
+/// The message has been removed from the socket, so the next receive call returns the next message.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct MessageTooLargeError {
+	size: usize,
+	capacity: usize,
+}
+
+impl MessageTooLargeError {
+	/// Create a new error for a message of `size` bytes that did not fit in a buffer of `capacity` bytes.
+	pub(crate) fn new(size: usize, capacity: usize) -> Self {
+		Self { size, capacity }
+	}
+
+	/// Get the original size of the discarded message in bytes.
+	pub fn size(&self) -> usize {
+		self.size
+	}
+
+	/// Get the size of the buffer that the message did not fit in.
+	pub fn capacity(&self) -> usize {
+		self.capacity
+	}
+}
+
+impl std::fmt::Display for MessageTooLargeError {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(
+			f,
+			"discarded message of {} bytes that does not fit in a buffer of {} bytes",
+			self.size, self.capacity
+		)
+	}
+}
+
+impl std::error::Error for MessageTooLargeError {}
+
+impl From<MessageTooLargeError> for std::io::Error {
+	fn from(other: MessageTooLargeError) -> Self {
+		Self::new(std::io::ErrorKind::InvalidData, other)
+	}
+}
+
+/// Error indicating that the kernel truncated the ancillary data of a received message (`MSG_CTRUNC`).
+///
+/// Use [`SocketAncillary::truncation()`][crate::ancillary::SocketAncillary::truncation] to get the details after a receive call.
+/// It converts into a [`std::io::Error`] of kind [`std::io::ErrorKind::InvalidData`].
+///
+/// Control messages that did not fit are discarded by the kernel,
+/// and any file descriptors they carried are closed.
+/// The kernel does not report how many bytes of control data were lost.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct AncillaryTruncatedError {
+	capacity: usize,
+	used: usize,
+}
+
+/// The likely reason why ancillary data was truncated.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AncillaryTruncationReason {
+	/// The ancillary buffer was too small to hold all control messages.
+	BufferTooSmall,
+
+	/// The buffer had room left, but the kernel could not install all file descriptors in the receiving process.
+	///
+	/// This usually means the process reached its file descriptor limit (`RLIMIT_NOFILE`).
+	FdLimit,
+}
+
+impl AncillaryTruncatedError {
+	/// Create a new error for an ancillary buffer of `capacity` bytes of which `used` bytes were filled.
+	pub(crate) fn new(capacity: usize, used: usize) -> Self {
+		Self { capacity, used }
+	}
+
+	/// Get the capacity of the ancillary buffer in bytes.
+	pub fn capacity(&self) -> usize {
+		self.capacity
+	}
+
+	/// Get the number of bytes of control data that were received.
+	pub fn used(&self) -> usize {
+		self.used
+	}
+
+	/// Get the likely reason for the truncation.
+	///
+	/// If the remaining space could have held another file descriptor, the buffer was not the limiting factor.
+	pub fn reason(&self) -> AncillaryTruncationReason {
+		if self.capacity - self.used.min(self.capacity) >= crate::ancillary::ancillary_space_for_fds(1) {
+			AncillaryTruncationReason::FdLimit
+		} else {
+			AncillaryTruncationReason::BufferTooSmall
+		}
+	}
+}
+
+impl std::fmt::Display for AncillaryTruncatedError {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self.reason() {
+			AncillaryTruncationReason::BufferTooSmall => write!(
+				f,
+				"ancillary data was truncated: the buffer of {} bytes is too small ({} bytes used)",
+				self.capacity, self.used
+			),
+			AncillaryTruncationReason::FdLimit => write!(
+				f,
+				"ancillary data was truncated: file descriptors were dropped, possibly because the file descriptor limit was reached ({} of {} bytes used)",
+				self.used, self.capacity
+			),
+		}
+	}
+}
+
+impl std::error::Error for AncillaryTruncatedError {}
+
+impl From<AncillaryTruncatedError> for std::io::Error {
+	fn from(other: AncillaryTruncatedError) -> Self {
+		Self::new(std::io::ErrorKind::InvalidData, other)
+	}
+}
+
+/// Error returned by [`UnixSeqpacket::send_all()`][crate::UnixSeqpacket::send_all] when sending a message failed.
+///
+/// Messages before the failed one were sent, the failed message and the messages after it were not.
+/// It converts into a [`std::io::Error`] of the same kind as the underlying error.
+#[derive(Debug)]
+pub struct SendAllError {
+	sent: usize,
+	error: std::io::Error,
+}
+
+impl SendAllError {
+	/// Create a new error for a failure after `sent` messages were sent.
+	pub(crate) fn new(sent: usize, error: std::io::Error) -> Self {
+		Self { sent, error }
+	}
+
+	/// Get the number of messages that were sent before the error occurred.
+	pub fn sent(&self) -> usize {
+		self.sent
+	}
+
+	/// Get the error that occurred while sending the next message.
+	pub fn error(&self) -> &std::io::Error {
+		&self.error
+	}
+
+	/// Consume the error and return the underlying I/O error.
+	pub fn into_error(self) -> std::io::Error {
+		self.error
+	}
+}
+
+impl std::fmt::Display for SendAllError {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(f, "failed to send message after sending {} messages: {}", self.sent, self.error)
+	}
+}
+
+impl std::error::Error for SendAllError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		Some(&self.error)
+	}
+}
+
+impl From<SendAllError> for std::io::Error {
+	fn from(other: SendAllError) -> Self {
+		Self::new(other.error.kind(), other)
+	}
+}
+
+/// The operation that failed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Operation {
+	/// Connecting a socket.
+	Connect,
+
+	/// Binding a listener.
+	Bind,
+
+	/// Accepting a connection.
+	Accept,
+
+	/// Sending a message.
+	Send,
+
+	/// Receiving a message.
+	Recv,
+}
+
+impl std::fmt::Display for Operation {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			Self::Connect => write!(f, "connect"),
+			Self::Bind => write!(f, "bind"),
+			Self::Accept => write!(f, "accept"),
+			Self::Send => write!(f, "send"),
+			Self::Recv => write!(f, "receive"),
+		}
+	}
+}
+
+/// Helper to classify the errors reported by the crate.
+///
+/// All functions in this crate return a [`std::io::Error`], to remain compatible with the standard library and tokio.
+/// Convert such an error with [`Error::from()`] to classify it, or with [`Error::new()`] to also record the operation that failed.
+/// An error can also be classified by reference, in which case the [`std::io::Error`] is copied.
+///
+/// The conversion back to [`std::io::Error`] keeps the [`ErrorReason`], but not the operation.
+/// That means the `?` operator can be used in functions that return a [`std::io::Error`].
+///
+/// # Example
+/// ```no_run
+/// # async fn foo(socket: tokio_seqpacket::UnixSeqpacket) {
+/// use tokio_seqpacket::{Error, ErrorReason, Operation};
+///
+/// let mut buffer = [0u8; 1024];
+/// match socket.recv_or_discard(&mut buffer).await.map_err(|e| Error::new(Operation::Recv, e)) {
+///     Ok(len) => println!("received {} bytes", len),
+///     Err(e) => match e.reason() {
+///         ErrorReason::MessageTooLarge(e) => eprintln!("discarded message of {} bytes", e.size()),
+///         ErrorReason::Disconnected(_) => eprintln!("peer disconnected"),
+///         _ => eprintln!("{}", e),
+///     },
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Error {
+	operation: Option<Operation>,
+	reason: ErrorReason,
+}
+
+/// The reason why an operation failed.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ErrorReason {
+	/// A socket path was too long.
+	PathTooLong(PathTooLongError),
+
+	/// A received message did not fit in the buffer and was discarded.
+	MessageTooLarge(MessageTooLargeError),
+
+	/// The ancillary data of a received message was truncated.
+	AncillaryTruncated(AncillaryTruncatedError),
+
+	/// The connection was reset or closed by the peer, or the socket is not connected.
+	Disconnected(std::io::Error),
+
+	/// Any other I/O error.
+	Io(std::io::Error),
+}
+
+impl Error {
+	/// Create an error for a failed operation.
+	pub fn new(operation: Operation, error: std::io::Error) -> Self {
+		Self {
+			operation: Some(operation),
+			reason: error.into(),
+		}
+	}
+
+	/// Get the operation that failed, if it is known.
+	///
+	/// The operation is not known for errors converted from a [`std::io::Error`] with [`Error::from()`].
+	pub fn operation(&self) -> Option<Operation> {
+		self.operation
+	}
+
+	/// Get the reason why the operation failed.
+	pub fn reason(&self) -> &ErrorReason {
+		&self.reason
+	}
+
+	/// Consume the error and get the reason why the operation failed.
+	pub fn into_reason(self) -> ErrorReason {
+		self.reason
+	}
+
+	/// Get the [`std::io::ErrorKind`] of the error.
+	pub fn kind(&self) -> std::io::ErrorKind {
+		self.reason.kind()
+	}
+
+	/// Get the OS error code of the error, if it was caused by a system call.
+	pub fn raw_os_error(&self) -> Option<i32> {
+		match &self.reason {
+			ErrorReason::Disconnected(e) | ErrorReason::Io(e) => e.raw_os_error(),
+			_ => None,
+		}
+	}
+}
+
+impl ErrorReason {
+	/// Get the [`std::io::ErrorKind`] of the error.
+	pub fn kind(&self) -> std::io::ErrorKind {
+		match self {
+			Self::PathTooLong(_) => std::io::ErrorKind::InvalidInput,
+			Self::MessageTooLarge(_) => std::io::ErrorKind::InvalidData,
+			Self::AncillaryTruncated(_) => std::io::ErrorKind::InvalidData,
+			Self::Disconnected(e) => e.kind(),
+			Self::Io(e) => e.kind(),
+		}
+	}
+
+	/// Get the underlying error.
+	fn inner(&self) -> &(dyn std::error::Error + 'static) {
+		match self {
+			Self::PathTooLong(e) => e,
+			Self::MessageTooLarge(e) => e,
+			Self::AncillaryTruncated(e) => e,
+			Self::Disconnected(e) => e,
+			Self::Io(e) => e,
+		}
+	}
+}
+
+impl std::fmt::Display for Error {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self.operation {
+			Some(operation) => write!(f, "failed to {}: {}", operation, self.reason),
+			None => self.reason.fmt(f),
+		}
+	}
+}
+
+impl std::fmt::Display for ErrorReason {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		std::fmt::Display::fmt(self.inner(), f)
+	}
+}
+
+impl std::error::Error for Error {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		Some(self.reason.inner())
+	}
+}
+
+impl From<std::io::Error> for Error {
+	fn from(other: std::io::Error) -> Self {
+		Self {
+			operation: None,
+			reason: other.into(),
+		}
+	}
+}
+
+impl From<&std::io::Error> for Error {
+	fn from(other: &std::io::Error) -> Self {
+		Self {
+			operation: None,
+			reason: other.into(),
+		}
+	}
+}
+
+impl ErrorReason {
+	/// Get the typed error wrapped in an I/O error by this crate, if any.
+	fn from_custom(error: &std::io::Error) -> Option<Self> {
+		let inner = error.get_ref()?;
+		if let Some(e) = inner.downcast_ref::<PathTooLongError>() {
+			Some(Self::PathTooLong(*e))
+		} else if let Some(e) = inner.downcast_ref::<MessageTooLargeError>() {
+			Some(Self::MessageTooLarge(*e))
+		} else {
+			inner.downcast_ref::<AncillaryTruncatedError>().map(|e| Self::AncillaryTruncated(*e))
+		}
+	}
+}
+
+impl From<std::io::Error> for ErrorReason {
+	fn from(other: std::io::Error) -> Self {
+		if let Some(reason) = Self::from_custom(&other) {
+			return reason;
+		}
+		match other.kind() {
+			std::io::ErrorKind::ConnectionReset | std::io::ErrorKind::BrokenPipe | std::io::ErrorKind::NotConnected => {
+				Self::Disconnected(other)
+			},
+			_ => Self::Io(other),
+		}
+	}
+}
+
+impl From<&std::io::Error> for ErrorReason {
+	/// Classify an I/O error by reference.
+	///
+	/// OS errors are copied exactly.
+	/// Other errors keep their kind and message, but not the original inner error.
+	fn from(other: &std::io::Error) -> Self {
+		if let Some(reason) = Self::from_custom(other) {
+			return reason;
+		}
+		let copy = match (other.raw_os_error(), other.get_ref()) {
+			(Some(code), _) => std::io::Error::from_raw_os_error(code),
+			(None, Some(inner)) => std::io::Error::new(other.kind(), inner.to_string()),
+			(None, None) => other.kind().into(),
+		};
+		copy.into()
+	}
+}
+
+impl From<Error> for std::io::Error {
+	fn from(other: Error) -> Self {
+		other.reason.into()
+	}
+}
+
+impl From<ErrorReason> for std::io::Error {
+	fn from(other: ErrorReason) -> Self {
+		match other {
+			ErrorReason::PathTooLong(e) => e.into(),
+			ErrorReason::MessageTooLarge(e) => e.into(),
+			ErrorReason::AncillaryTruncated(e) => e.into(),
+			ErrorReason::Disconnected(e) => e,
+			ErrorReason::Io(e) => e,
+		}
+	}
+}
+
+/// Check if a path is short enough to be used as socket address.
+///
+/// This allows services that generate socket paths to report a clear error before trying to bind or connect.
+pub fn check_socket_path<P: AsRef<Path>>(path: P) -> Result<(), PathTooLongError> {
+	use std::os::unix::ffi::OsStrExt;
+
+	let path_len = path.as_ref().as_os_str().as_bytes().len();
+	let max_len = max_socket_path_len();
+	if path_len > max_len {
+		Err(PathTooLongError { path_len, max_len })
+	} else {
+		Ok(())
+	}
+}
+
+/// Get the maximum length of a socket path, excluding the terminating null byte.
+fn max_socket_path_len() -> usize {
+	let address: libc::sockaddr_un = unsafe { std::mem::zeroed() };
+	address.sun_path.len() - 1
+}
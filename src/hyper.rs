@@ -0,0 +1,95 @@
+//! Support for serving [`hyper`](https://docs.rs/hyper) connections over seqpacket sockets.
+//!
+//! This module is only available when the `hyper` feature is enabled.
+//!
+//! It implements [`hyper::server::accept::Accept`](::hyper::server::accept::Accept) for [`UnixSeqpacketListener`],
+//! so the listener can be passed directly to [`hyper::Server::builder()`](::hyper::Server::builder).
+//!
+//! # Message boundaries
+//!
+//! Hyper treats connections as byte streams, but seqpacket sockets transfer messages.
+//! Each write on a [`Connection`] is sent as a separate message,
+//! and each read receives a single message.
+//! If a message does not fit in the read buffer, the remainder of the message is discarded.
+//! Both sides of the connection must use large enough read buffers to avoid losing data.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::{UnixSeqpacket, UnixSeqpacketListener};
+
+/// A seqpacket connection that implements [`AsyncRead`] and [`AsyncWrite`].
+///
+/// See the [module documentation](self) for the semantics of reading and writing.
+#[derive(Debug)]
+pub struct Connection {
+	socket: UnixSeqpacket,
+}
+
+impl Connection {
+	/// Wrap a seqpacket socket in a connection.
+	pub fn new(socket: UnixSeqpacket) -> Self {
+		Self { socket }
+	}
+
+	/// Get a reference to the inner socket.
+	pub fn get_ref(&self) -> &UnixSeqpacket {
+		&self.socket
+	}
+
+	/// Unwrap the inner socket.
+	pub fn into_inner(self) -> UnixSeqpacket {
+		self.socket
+	}
+}
+
+impl From<UnixSeqpacket> for Connection {
+	fn from(socket: UnixSeqpacket) -> Self {
+		Self::new(socket)
+	}
+}
+
+impl AsyncRead for Connection {
+	fn poll_read(self: Pin<&mut Self>, cx: &mut Context, buf: &mut ReadBuf) -> Poll<std::io::Result<()>> {
+		let len = ready!(self.socket.poll_recv(cx, buf.initialize_unfilled())?);
+		buf.advance(len);
+		Poll::Ready(Ok(()))
+	}
+}
+
+impl AsyncWrite for Connection {
+	fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+		self.socket.poll_send(cx, buf)
+	}
+
+	fn poll_write_vectored(
+		self: Pin<&mut Self>,
+		cx: &mut Context,
+		bufs: &[std::io::IoSlice],
+	) -> Poll<std::io::Result<usize>> {
+		self.socket.poll_send_vectored(cx, bufs)
+	}
+
+	fn is_write_vectored(&self) -> bool {
+		true
+	}
+
+	fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<std::io::Result<()>> {
+		Poll::Ready(Ok(()))
+	}
+
+	fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<std::io::Result<()>> {
+		Poll::Ready(self.socket.shutdown(std::net::Shutdown::Write))
+	}
+}
+
+impl ::hyper::server::accept::Accept for UnixSeqpacketListener {
+	type Conn = Connection;
+	type Error = std::io::Error;
+
+	fn poll_accept(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<std::io::Result<Self::Conn>>> {
+		let (socket, _addr) = ready!(UnixSeqpacketListener::poll_accept(&self, cx)?);
+		Poll::Ready(Some(Ok(Connection::new(socket))))
+	}
+}
@@ -25,6 +25,19 @@
 //! As such, all I/O functions now take `&self` instead of `&mut self`,
 //! and the `split()` API has been deprecated.
 //!
+//! # Optional features
+//!
+//! * `bytes`: receive messages directly into a `BytesMut` with [`UnixSeqpacket::recv_buf()`],
+//!   and send chained buffers with [`UnixSeqpacket::send_buf()`].
+//! * `hyper`: implement the `Accept` trait from [hyper](https://docs.rs/hyper) for [`UnixSeqpacketListener`].
+//! * `metrics`: report sent and received messages, truncations and blocked operations to a recorder, see the [`metrics`] module.
+//! * `process`: spawn child processes connected to the parent with a seqpacket socket, see the [`process`] module.
+//! * `serde`: implement `Serialize` and `Deserialize` from [serde](https://docs.rs/serde) for [`UCred`].
+//! * `socket2-06`: borrow sockets as a `SockRef` from [socket2](https://docs.rs/socket2) 0.6 to access other socket options.
+//! * `tracing`: emit [tracing](https://docs.rs/tracing) spans and events for connections and messages.
+//!   Connecting and accepting is reported at the `debug` level, sending and receiving messages at the `trace` level.
+//! * `tokio-util`: add `*_or_cancelled()` variants of `accept`, `send` and `recv` that stop when a `CancellationToken` fires.
+//!
 //! # Example
 //! ```no_run
 //! # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
@@ -51,15 +64,68 @@ macro_rules! ready {
 	};
 }
 
+/// Emit a `tracing` event if the `tracing` feature is enabled.
+///
+/// Without the feature, the arguments are not evaluated.
+macro_rules! trace_event {
+	($level:ident, $($arg:tt)*) => {
+		#[cfg(feature = "tracing")]
+		tracing::$level!($($arg)*);
+	};
+}
+
+/// Report an event to the installed metrics recorder if the `metrics` feature is enabled.
+///
+/// Without the feature, the arguments are not evaluated.
+macro_rules! record_metric {
+	($function:ident($($arg:expr),*)) => {
+		#[cfg(feature = "metrics")]
+		crate::metrics::$function($($arg),*);
+	};
+}
+
 pub mod ancillary;
+pub mod blocking;
+mod builder;
+mod error;
+#[cfg(feature = "hyper")]
+pub mod hyper;
+mod limit;
 mod listener;
+mod listener_set;
+mod message_stream;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod peer_process;
+mod pool;
+#[cfg(feature = "process")]
+pub mod process;
+mod reconnect;
 mod socket;
+mod stats;
+#[cfg(target_os = "linux")]
+mod systemd;
 mod ucred;
 
-pub use listener::UnixSeqpacketListener;
-pub use socket::UnixSeqpacket;
+pub use builder::UnixSeqpacketBuilder;
+pub use error::{
+	check_socket_path, AncillaryTruncatedError, AncillaryTruncationReason, Error, ErrorReason, MessageTooLargeError,
+	Operation, PathTooLongError, SendAllError,
+};
+pub use listener::{Incoming, UnixSeqpacketListener};
+pub use listener_set::{ListenerId, ListenerSet};
+pub use message_stream::{AllocationStrategy, MessageStream};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use peer_process::PeerProcess;
+pub use pool::{BufferPool, PooledBuffer};
+pub use reconnect::ReconnectingSeqpacket;
+pub use socket::{RecvMeta, RecvSlot, UnixSeqpacket};
+pub use stats::SocketStats;
 
 pub use ucred::UCred;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use ucred::UCredWithGroups;
 
 #[doc(hidden)]
 #[deprecated(
@@ -80,6 +146,429 @@ fn socket_type() -> socket2::Type {
 	socket2::Type::seqpacket().cloexec().non_blocking()
 }
 
+/// Create a new close-on-exec non-blocking seqpacket socket.
+fn new_socket() -> std::io::Result<socket2::Socket> {
+	socket2::Socket::new(socket2::Domain::unix(), socket_type(), None)
+}
+
+/// Get the socket type for a close-on-exec blocking seqpacket socket.
+fn blocking_socket_type() -> socket2::Type {
+	socket2::Type::seqpacket().cloexec()
+}
+
+/// Get the value of an integer socket option.
+fn get_socket_option_int(
+	fd: std::os::unix::io::RawFd,
+	level: std::os::raw::c_int,
+	name: std::os::raw::c_int,
+) -> std::io::Result<std::os::raw::c_int> {
+	let mut value: std::os::raw::c_int = 0;
+	let mut len = std::mem::size_of_val(&value) as libc::socklen_t;
+	let ret = unsafe {
+		libc::getsockopt(
+			fd,
+			level,
+			name,
+			&mut value as *mut _ as *mut std::os::raw::c_void,
+			&mut len,
+		)
+	};
+	if ret == 0 {
+		Ok(value)
+	} else {
+		Err(std::io::Error::last_os_error())
+	}
+}
+
+/// The socket option to enable `SCM_SECURITY` messages.
+///
+/// Not all targets have this constant in `libc`.
+#[cfg(all(any(target_os = "android", target_os = "linux"), not(target_arch = "sparc64")))]
+pub(crate) const SO_PASSSEC: std::os::raw::c_int = 34;
+
+/// The socket option to enable `SCM_SECURITY` messages.
+///
+/// Not all targets have this constant in `libc`.
+#[cfg(all(any(target_os = "android", target_os = "linux"), target_arch = "sparc64"))]
+pub(crate) const SO_PASSSEC: std::os::raw::c_int = 0x1f;
+
+/// The socket option to get the security label of the peer.
+///
+/// Not all targets have this constant in `libc`.
+#[cfg(all(
+	any(target_os = "android", target_os = "linux"),
+	not(any(target_arch = "sparc64", target_arch = "mips", target_arch = "mips64"))
+))]
+pub(crate) const SO_PEERSEC: std::os::raw::c_int = 31;
+
+/// The socket option to get the security label of the peer.
+///
+/// Not all targets have this constant in `libc`.
+#[cfg(all(any(target_os = "android", target_os = "linux"), target_arch = "sparc64"))]
+pub(crate) const SO_PEERSEC: std::os::raw::c_int = 0x1e;
+
+/// The socket option to get the security label of the peer.
+///
+/// Not all targets have this constant in `libc`.
+#[cfg(all(any(target_os = "android", target_os = "linux"), any(target_arch = "mips", target_arch = "mips64")))]
+pub(crate) const SO_PEERSEC: std::os::raw::c_int = 30;
+
+/// The socket option to get the supplementary groups of the peer.
+///
+/// Not all targets have this constant in `libc`.
+#[cfg(all(any(target_os = "android", target_os = "linux"), not(target_arch = "sparc64")))]
+pub(crate) const SO_PEERGROUPS: std::os::raw::c_int = 59;
+
+/// The socket option to get the supplementary groups of the peer.
+///
+/// Not all targets have this constant in `libc`.
+#[cfg(all(any(target_os = "android", target_os = "linux"), target_arch = "sparc64"))]
+pub(crate) const SO_PEERGROUPS: std::os::raw::c_int = 0x3d;
+
+/// The socket option to get a pidfd for the peer.
+///
+/// Not all targets have this constant in `libc`.
+#[cfg(all(any(target_os = "android", target_os = "linux"), not(target_arch = "sparc64")))]
+pub(crate) const SO_PEERPIDFD: std::os::raw::c_int = 77;
+
+/// The socket option to get a pidfd for the peer.
+///
+/// Not all targets have this constant in `libc`.
+#[cfg(all(any(target_os = "android", target_os = "linux"), target_arch = "sparc64"))]
+pub(crate) const SO_PEERPIDFD: std::os::raw::c_int = 0x56;
+
+/// The socket option to get the unique cookie of a socket.
+///
+/// Not all targets have this constant in `libc`.
+#[cfg(all(any(target_os = "android", target_os = "linux"), not(target_arch = "sparc64")))]
+pub(crate) const SO_COOKIE: std::os::raw::c_int = 57;
+
+/// The socket option to get the unique cookie of a socket.
+///
+/// Not all targets have this constant in `libc`.
+#[cfg(all(any(target_os = "android", target_os = "linux"), target_arch = "sparc64"))]
+pub(crate) const SO_COOKIE: std::os::raw::c_int = 0x3b;
+
+/// Get the value of a socket option with a variable length.
+///
+/// If the kernel reports that the buffer is too small (`ERANGE`), the buffer is grown to the required size and the call is retried.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn get_socket_option_bytes(
+	fd: std::os::unix::io::RawFd,
+	level: std::os::raw::c_int,
+	name: std::os::raw::c_int,
+) -> std::io::Result<Vec<u8>> {
+	let mut buffer = vec![0u8; 256];
+	loop {
+		let mut len = buffer.len() as libc::socklen_t;
+		let ret = unsafe {
+			libc::getsockopt(
+				fd,
+				level,
+				name,
+				buffer.as_mut_ptr() as *mut std::os::raw::c_void,
+				&mut len,
+			)
+		};
+		if ret == 0 {
+			buffer.truncate(len as usize);
+			return Ok(buffer);
+		}
+
+		let error = std::io::Error::last_os_error();
+		if error.raw_os_error() == Some(libc::ERANGE) && len as usize > buffer.len() {
+			buffer.resize(len as usize, 0);
+		} else {
+			return Err(error);
+		}
+	}
+}
+
+/// Set the value of an integer socket option.
+#[cfg_attr(not(any(target_os = "android", target_os = "linux")), allow(dead_code))]
+fn set_socket_option_int(
+	fd: std::os::unix::io::RawFd,
+	level: std::os::raw::c_int,
+	name: std::os::raw::c_int,
+	value: std::os::raw::c_int,
+) -> std::io::Result<()> {
+	let ret = unsafe {
+		libc::setsockopt(
+			fd,
+			level,
+			name,
+			&value as *const _ as *const std::os::raw::c_void,
+			std::mem::size_of_val(&value) as libc::socklen_t,
+		)
+	};
+	if ret == 0 {
+		Ok(())
+	} else {
+		Err(std::io::Error::last_os_error())
+	}
+}
+
+/// Perform an `ioctl` that reports an integer value.
+fn ioctl_int(fd: std::os::unix::io::RawFd, request: std::os::raw::c_ulong) -> std::io::Result<std::os::raw::c_int> {
+	let mut value: std::os::raw::c_int = 0;
+	let ret = unsafe { libc::ioctl(fd, request as _, &mut value) };
+	if ret == 0 {
+		Ok(value)
+	} else {
+		Err(std::io::Error::last_os_error())
+	}
+}
+
+/// Get the halves of a socket that are shut down, without waiting.
+///
+/// A half is also shut down if the peer closed the connection.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn shutdown_state(fd: std::os::unix::io::RawFd) -> std::io::Result<Option<std::net::Shutdown>> {
+	let mut poll_fd = libc::pollfd {
+		fd,
+		events: libc::POLLRDHUP,
+		revents: 0,
+	};
+	let ret = unsafe { libc::poll(&mut poll_fd, 1, 0) };
+	if ret < 0 {
+		return Err(std::io::Error::last_os_error());
+	}
+	if poll_fd.revents & libc::POLLHUP != 0 {
+		Ok(Some(std::net::Shutdown::Both))
+	} else if poll_fd.revents & libc::POLLRDHUP != 0 {
+		Ok(Some(std::net::Shutdown::Read))
+	} else {
+		Ok(None)
+	}
+}
+
+/// Check that a file descriptor is a unix socket of the given type.
+fn check_socket_type(fd: std::os::unix::io::RawFd, socket_type: std::os::raw::c_int) -> std::io::Result<()> {
+	let actual_type = match get_socket_option_int(fd, libc::SOL_SOCKET, libc::SO_TYPE) {
+		Ok(x) => x,
+		Err(e) if e.raw_os_error() == Some(libc::ENOTSOCK) => {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::InvalidInput,
+				format!("file descriptor {} is not a socket", fd),
+			));
+		},
+		Err(e) => return Err(e),
+	};
+	if actual_type != socket_type {
+		return Err(std::io::Error::new(
+			std::io::ErrorKind::InvalidInput,
+			format!(
+				"file descriptor {} has the wrong socket type: expected {}, got {}",
+				fd,
+				socket_type_name(socket_type),
+				socket_type_name(actual_type),
+			),
+		));
+	}
+
+	#[cfg(any(target_os = "android", target_os = "linux"))]
+	{
+		let domain = get_socket_option_int(fd, libc::SOL_SOCKET, libc::SO_DOMAIN)?;
+		if domain != libc::AF_UNIX {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::InvalidInput,
+				format!("file descriptor {} is not a unix socket", fd),
+			));
+		}
+	}
+
+	Ok(())
+}
+
+/// Get a human readable name for a socket type.
+fn socket_type_name(socket_type: std::os::raw::c_int) -> String {
+	match socket_type {
+		libc::SOCK_STREAM => "SOCK_STREAM".into(),
+		libc::SOCK_DGRAM => "SOCK_DGRAM".into(),
+		libc::SOCK_SEQPACKET => "SOCK_SEQPACKET".into(),
+		libc::SOCK_RAW => "SOCK_RAW".into(),
+		other => format!("socket type {}", other),
+	}
+}
+
+/// Check if a socket is connected to a peer.
+fn is_connected(fd: std::os::unix::io::RawFd) -> std::io::Result<bool> {
+	unsafe {
+		let mut address: libc::sockaddr_un = std::mem::zeroed();
+		let mut len = std::mem::size_of::<libc::sockaddr_un>() as libc::socklen_t;
+		if libc::getpeername(fd, &mut address as *mut _ as *mut libc::sockaddr, &mut len) == 0 {
+			Ok(true)
+		} else {
+			let error = std::io::Error::last_os_error();
+			if error.raw_os_error() == Some(libc::ENOTCONN) {
+				Ok(false)
+			} else {
+				Err(error)
+			}
+		}
+	}
+}
+
+/// Check if a socket is listening for connections.
+fn is_listening(fd: std::os::unix::io::RawFd) -> std::io::Result<bool> {
+	Ok(get_socket_option_int(fd, libc::SOL_SOCKET, libc::SO_ACCEPTCONN)? != 0)
+}
+
+/// Set or clear the close-on-exec flag on a file descriptor.
+fn set_cloexec(fd: std::os::unix::io::RawFd, cloexec: bool) -> std::io::Result<()> {
+	unsafe {
+		let flags = libc::fcntl(fd, libc::F_GETFD);
+		if flags < 0 {
+			return Err(std::io::Error::last_os_error());
+		}
+		let new_flags = if cloexec {
+			flags | libc::FD_CLOEXEC
+		} else {
+			flags & !libc::FD_CLOEXEC
+		};
+		if new_flags != flags && libc::fcntl(fd, libc::F_SETFD, new_flags) < 0 {
+			return Err(std::io::Error::last_os_error());
+		}
+	}
+	Ok(())
+}
+
+/// Accept a connection on a listening socket.
+///
+/// The accepted socket is always in non-blocking mode.
+/// The close-on-exec flag is set or cleared according to the `cloexec` parameter.
+///
+/// Where possible, the flags are applied atomically by `accept4()`.
+fn accept(
+	fd: std::os::unix::io::RawFd,
+	cloexec: bool,
+) -> std::io::Result<(socket2::Socket, socket2::SockAddr)> {
+	use std::os::unix::io::FromRawFd;
+
+	let mut storage: libc::sockaddr_un = unsafe { std::mem::zeroed() };
+	let mut len = std::mem::size_of_val(&storage) as libc::socklen_t;
+	let storage_ptr = &mut storage as *mut libc::sockaddr_un as *mut libc::sockaddr;
+
+	let socket = loop {
+		#[cfg(any(
+			target_os = "android",
+			target_os = "dragonfly",
+			target_os = "freebsd",
+			target_os = "linux",
+			target_os = "netbsd",
+			target_os = "openbsd",
+		))]
+		let ret = unsafe {
+			let flags = if cloexec {
+				libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC
+			} else {
+				libc::SOCK_NONBLOCK
+			};
+			libc::accept4(fd, storage_ptr, &mut len, flags)
+		};
+
+		#[cfg(not(any(
+			target_os = "android",
+			target_os = "dragonfly",
+			target_os = "freebsd",
+			target_os = "linux",
+			target_os = "netbsd",
+			target_os = "openbsd",
+		)))]
+		let ret = unsafe { libc::accept(fd, storage_ptr, &mut len) };
+
+		if ret >= 0 {
+			break unsafe { socket2::Socket::from_raw_fd(ret) };
+		}
+		let error = std::io::Error::last_os_error();
+		if error.kind() != std::io::ErrorKind::Interrupted {
+			return Err(error);
+		}
+	};
+
+	#[cfg(not(any(
+		target_os = "android",
+		target_os = "dragonfly",
+		target_os = "freebsd",
+		target_os = "linux",
+		target_os = "netbsd",
+		target_os = "openbsd",
+	)))]
+	{
+		use std::os::unix::io::AsRawFd;
+		set_cloexec(socket.as_raw_fd(), cloexec)?;
+		socket.set_nonblocking(true)?;
+	}
+
+	let addr = unsafe { socket2::SockAddr::from_raw_parts(storage_ptr, len) };
+	Ok((socket, addr))
+}
+
+/// Create a [`socket2::SockAddr`] for a name in the abstract namespace.
+///
+/// The name should not include the leading null byte.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn abstract_sockaddr(name: &[u8]) -> std::io::Result<socket2::SockAddr> {
+	use std::os::unix::ffi::OsStrExt;
+
+	let mut path = Vec::with_capacity(name.len() + 1);
+	path.push(0);
+	path.extend_from_slice(name);
+	socket2::SockAddr::unix(std::ffi::OsStr::from_bytes(&path))
+}
+
+/// Create a [`socket2::SockAddr`] for a filesystem path.
+///
+/// Paths that are too long result in a [`PathTooLongError`].
+fn path_sockaddr<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<socket2::SockAddr> {
+	check_socket_path(&path)?;
+	socket2::SockAddr::unix(path)
+}
+
+/// Convert a [`std::os::unix::net::SocketAddr`] to a [`socket2::SockAddr`].
+///
+/// On Linux and Android, unnamed addresses are converted to an address that triggers autobind when used with `bind()`.
+/// On other platforms, unnamed addresses result in an error.
+fn unix_as_sockaddr(addr: &std::os::unix::net::SocketAddr) -> std::io::Result<socket2::SockAddr> {
+	#[cfg(target_os = "android")]
+	use std::os::android::net::SocketAddrExt;
+	#[cfg(target_os = "linux")]
+	use std::os::linux::net::SocketAddrExt;
+
+	if let Some(path) = addr.as_pathname() {
+		return path_sockaddr(path);
+	}
+
+	#[cfg(any(target_os = "android", target_os = "linux"))]
+	{
+		if let Some(name) = addr.as_abstract_name() {
+			return abstract_sockaddr(name);
+		}
+		Ok(autobind_sockaddr())
+	}
+
+	#[cfg(not(any(target_os = "android", target_os = "linux")))]
+	Err(std::io::Error::new(
+		std::io::ErrorKind::InvalidInput,
+		"unnamed socket addresses are not supported on this platform",
+	))
+}
+
+/// Create a [`socket2::SockAddr`] that triggers autobind when used with `bind()`.
+///
+/// The address consists of only the address family, without any path.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn autobind_sockaddr() -> socket2::SockAddr {
+	unsafe {
+		let mut addr: libc::sockaddr_un = std::mem::zeroed();
+		addr.sun_family = libc::AF_UNIX as libc::sa_family_t;
+		socket2::SockAddr::from_raw_parts(
+			&addr as *const libc::sockaddr_un as *const libc::sockaddr,
+			std::mem::size_of::<libc::sa_family_t>() as libc::socklen_t,
+		)
+	}
+}
+
 /// Convert a [`socket2::SockAddr`] to a [`std::os::unix::net::SocketAddr`].
 fn sockaddr_as_unix(addr: &socket2::SockAddr) -> Option<std::os::unix::net::SocketAddr> {
 	if addr.family() != libc::AF_LOCAL as libc::sa_family_t {
@@ -97,6 +586,6 @@ fn sockaddr_as_unix(addr: &socket2::SockAddr) -> Option<std::os::unix::net::Sock
 			addr: std::ptr::read(addr.as_ptr() as *const libc::sockaddr_un),
 			len: addr.len(),
 		};
-		Some(std::mem::transmute(internal))
+		Some(std::mem::transmute::<SocketAddrInternal, std::os::unix::net::SocketAddr>(internal))
 	}
 }
@@ -0,0 +1,89 @@
+//! Limit for the number of open connections accepted by a listener.
+
+use futures::task::AtomicWaker;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// Shared state for limiting the number of open connections.
+#[derive(Debug)]
+pub(crate) struct ConnectionLimit {
+	/// The maximum number of open connections.
+	max: usize,
+
+	/// The current number of open connections.
+	count: AtomicUsize,
+
+	/// The task waiting for a connection to be closed.
+	waker: AtomicWaker,
+}
+
+/// A permit for a single open connection.
+///
+/// When a permit is dropped, the task waiting for a free slot is woken up.
+#[derive(Debug)]
+pub(crate) struct ConnectionPermit {
+	limit: Arc<ConnectionLimit>,
+}
+
+impl ConnectionLimit {
+	/// Create a new connection limit.
+	pub(crate) fn new(max: usize) -> Arc<Self> {
+		Arc::new(Self {
+			max,
+			count: AtomicUsize::new(0),
+			waker: AtomicWaker::new(),
+		})
+	}
+
+	/// Get the maximum number of open connections.
+	pub(crate) fn max(&self) -> usize {
+		self.max
+	}
+
+	/// Get the current number of open connections.
+	pub(crate) fn count(&self) -> usize {
+		self.count.load(Ordering::Acquire)
+	}
+
+	/// Try to acquire a permit without waiting.
+	fn try_acquire(self: &Arc<Self>) -> Option<ConnectionPermit> {
+		self.count
+			.fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+				if count < self.max {
+					Some(count + 1)
+				} else {
+					None
+				}
+			})
+			.ok()?;
+		Some(ConnectionPermit { limit: self.clone() })
+	}
+
+	/// Acquire a permit, ignoring the limit.
+	pub(crate) fn acquire_unchecked(self: &Arc<Self>) -> ConnectionPermit {
+		self.count.fetch_add(1, Ordering::AcqRel);
+		ConnectionPermit { limit: self.clone() }
+	}
+
+	/// Acquire a permit, or schedule the current task to wake up when a connection is closed.
+	pub(crate) fn poll_acquire(self: &Arc<Self>, cx: &mut Context) -> Poll<ConnectionPermit> {
+		if let Some(permit) = self.try_acquire() {
+			return Poll::Ready(permit);
+		}
+		self.waker.register(cx.waker());
+		match self.try_acquire() {
+			Some(permit) => Poll::Ready(permit),
+			None => Poll::Pending,
+		}
+	}
+}
+
+impl Drop for ConnectionPermit {
+	fn drop(&mut self) {
+		self.limit.count.fetch_sub(1, Ordering::AcqRel);
+		// Also wake up for permits that were released without accepting a connection:
+		// another task may have registered the waker while this permit held the last free slot.
+		self.limit.waker.wake();
+	}
+}
@@ -1,36 +1,67 @@
 use futures::future::poll_fn;
-use std::os::unix::io::AsRawFd;
+use futures::stream::Stream;
+use std::convert::TryFrom;
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd};
 use std::os::unix::net::SocketAddr;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 use tokio::io::unix::AsyncFd;
 
-use crate::UnixSeqpacket;
+use crate::limit::{ConnectionLimit, ConnectionPermit};
+use crate::{UCred, UnixSeqpacket};
 
 /// Listener for Unix seqpacket sockets.
 pub struct UnixSeqpacketListener {
 	io: AsyncFd<socket2::Socket>,
+	backlog: Option<std::os::raw::c_int>,
+	socket_file: Option<SocketFile>,
+	limit: Option<Arc<ConnectionLimit>>,
+	accept_cloexec: bool,
 }
 
 impl std::fmt::Debug for UnixSeqpacketListener {
 	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-		f.debug_struct("UnixSeqpacketListener")
-			.field("fd", &self.io.get_ref().as_raw_fd())
-			.finish()
+		let mut debug = f.debug_struct("UnixSeqpacketListener");
+		debug
+			.field("fd", &self.io.as_raw_fd())
+			.field("local_addr", &self.local_addr().ok())
+			.field("backlog", &self.backlog)
+			.field("socket_file", &self.socket_file)
+			.field("limit", &self.limit)
+			.field("accept_cloexec", &self.accept_cloexec);
+		#[cfg(any(target_os = "android", target_os = "linux"))]
+		if let Ok(shutdown) = crate::shutdown_state(self.io.as_raw_fd()) {
+			debug.field("shutdown", &shutdown);
+		}
+		debug.finish()
 	}
 }
 
 impl UnixSeqpacketListener {
-	fn new(socket: socket2::Socket) -> std::io::Result<Self> {
+	/// The backlog used by [`Self::bind()`].
+	pub const DEFAULT_BACKLOG: std::os::raw::c_int = 128;
+
+	fn new(socket: socket2::Socket, backlog: Option<std::os::raw::c_int>) -> std::io::Result<Self> {
 		let io = AsyncFd::new(socket)?;
-		Ok(Self { io })
+		Ok(Self {
+			io,
+			backlog,
+			socket_file: None,
+			limit: None,
+			accept_cloexec: true,
+		})
 	}
 
 	/// Bind a new seqpacket listener to the given address.
 	///
 	/// The create listener will be ready to accept new connections.
+	///
+	/// The listener is created with a backlog of [`Self::DEFAULT_BACKLOG`].
+	/// Use [`Self::bind_with_backlog()`] to specify a different backlog.
 	pub fn bind<P: AsRef<Path>>(address: P) -> std::io::Result<Self> {
-		Self::bind_with_backlog(address, 128)
+		Self::bind_with_backlog(address, Self::DEFAULT_BACKLOG)
 	}
 
 	/// Bind a new seqpacket listener to the given address.
@@ -40,47 +71,604 @@ impl UnixSeqpacketListener {
 	/// The `backlog` parameter is used to determine the size of connection queue.
 	/// See `man 3 listen` for more information.
 	pub fn bind_with_backlog<P: AsRef<Path>>(address: P, backlog: std::os::raw::c_int) -> std::io::Result<Self> {
-		let address = socket2::SockAddr::unix(address)?;
-		let socket = socket2::Socket::new(socket2::Domain::unix(), crate::socket_type(), None)?;
+		Self::bind_path(address.as_ref(), backlog, None)
+	}
+
+	/// Bind a new seqpacket listener to the given address and set the permissions of the socket file.
+	///
+	/// The permissions are applied after the socket is bound but before it starts listening.
+	/// That means no other process can connect to the socket before the permissions are set.
+	///
+	/// The `mode` parameter contains the permission bits for the socket file, like `0o600`.
+	/// If setting the permissions fails, the socket file is removed again.
+	///
+	/// The create listener will be ready to accept new connections.
+	pub fn bind_with_mode<P: AsRef<Path>>(address: P, mode: u32) -> std::io::Result<Self> {
+		Self::bind_with_backlog_and_mode(address, Self::DEFAULT_BACKLOG, mode)
+	}
+
+	/// Bind a new seqpacket listener to the given address and set the permissions of the socket file.
+	///
+	/// See [`Self::bind_with_mode()`] and [`Self::bind_with_backlog()`] for more information.
+	pub fn bind_with_backlog_and_mode<P: AsRef<Path>>(
+		address: P,
+		backlog: std::os::raw::c_int,
+		mode: u32,
+	) -> std::io::Result<Self> {
+		Self::bind_path(address.as_ref(), backlog, Some(mode))
+	}
+
+	/// Bind a new seqpacket listener to the given address, replacing a stale socket file if needed.
+	///
+	/// If the address is already in use, this function checks if the existing file is a socket,
+	/// and if a process is still listening on it.
+	/// If the socket is stale (nobody is listening on it), the file is removed and binding is retried.
+	/// If another process is still listening on the socket, the original error is returned.
+	///
+	/// Files that are not sockets are never removed.
+	///
+	/// Note that there is an unavoidable race condition if multiple processes try to replace the same stale socket.
+	/// Use a lock file if that is a concern.
+	pub fn bind_replacing_stale<P: AsRef<Path>>(address: P) -> std::io::Result<Self> {
+		let path = address.as_ref();
+		match Self::bind_path(path, Self::DEFAULT_BACKLOG, None) {
+			Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => {
+				if !is_stale_socket(path) {
+					return Err(e);
+				}
+				std::fs::remove_file(path)?;
+				Self::bind_path(path, Self::DEFAULT_BACKLOG, None)
+			},
+			x => x,
+		}
+	}
+
+	/// Bind a new seqpacket listener to a filesystem path.
+	///
+	/// If `mode` is given, the permissions of the socket file are set before the socket starts listening.
+	fn bind_path(path: &Path, backlog: std::os::raw::c_int, mode: Option<u32>) -> std::io::Result<Self> {
+		Self::bind_socket_to_path(crate::new_socket()?, path, backlog, mode)
+	}
+
+	/// Bind a socket to a filesystem path and start listening.
+	///
+	/// If `mode` is given, the permissions of the socket file are set before the socket starts listening.
+	pub(crate) fn bind_socket_to_path(
+		socket: socket2::Socket,
+		path: &Path,
+		backlog: std::os::raw::c_int,
+		mode: Option<u32>,
+	) -> std::io::Result<Self> {
+		use std::os::unix::fs::PermissionsExt;
+
+		let address = crate::path_sockaddr(path)?;
 		socket.bind(&address)?;
+		if let Some(mode) = mode {
+			if let Err(e) = std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode)) {
+				let _ = std::fs::remove_file(path);
+				return Err(e);
+			}
+		}
+		socket.listen(backlog)?;
+		let mut listener = Self::new(socket, Some(backlog))?;
+		listener.socket_file = SocketFile::new(path);
+		Ok(listener)
+	}
+
+	/// Bind a new seqpacket listener to the given socket address.
+	///
+	/// The address can be a filesystem path or (on Linux and Android) a name in the abstract namespace.
+	/// This can be used with addresses obtained from [`Self::local_addr()`] or [`UnixSeqpacket::peer_addr()`].
+	///
+	/// On Linux and Android, binding to an unnamed address makes the kernel choose a unique name in the abstract namespace.
+	/// On other platforms, unnamed addresses result in an error.
+	///
+	/// The create listener will be ready to accept new connections.
+	pub fn bind_addr(address: &SocketAddr) -> std::io::Result<Self> {
+		Self::bind_addr_with_backlog(address, Self::DEFAULT_BACKLOG)
+	}
+
+	/// Bind a new seqpacket listener to the given socket address.
+	///
+	/// See [`Self::bind_addr()`] for the supported addresses,
+	/// and [`Self::bind_with_backlog()`] for the meaning of the `backlog` parameter.
+	pub fn bind_addr_with_backlog(address: &SocketAddr, backlog: std::os::raw::c_int) -> std::io::Result<Self> {
+		if let Some(path) = address.as_pathname() {
+			return Self::bind_path(path, backlog, None);
+		}
+		let address = crate::unix_as_sockaddr(address)?;
+		Self::bind_sockaddr(&address, backlog)
+	}
+
+	/// Bind a new seqpacket listener to the given name in the abstract namespace.
+	///
+	/// The name should not include the leading null byte,
+	/// it is added automatically.
+	///
+	/// If the name is empty, the kernel picks a unique name (autobind).
+	/// Use [`Self::local_addr()`] to retrieve the chosen name.
+	///
+	/// The create listener will be ready to accept new connections.
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub fn bind_abstract(name: &[u8]) -> std::io::Result<Self> {
+		Self::bind_abstract_with_backlog(name, Self::DEFAULT_BACKLOG)
+	}
+
+	/// Bind a new seqpacket listener to the given name in the abstract namespace.
+	///
+	/// The name should not include the leading null byte,
+	/// it is added automatically.
+	///
+	/// If the name is empty, the kernel picks a unique name (autobind).
+	/// Use [`Self::local_addr()`] to retrieve the chosen name.
+	///
+	/// The create listener will be ready to accept new connections.
+	///
+	/// The `backlog` parameter is used to determine the size of connection queue.
+	/// See `man 3 listen` for more information.
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub fn bind_abstract_with_backlog(name: &[u8], backlog: std::os::raw::c_int) -> std::io::Result<Self> {
+		let address = if name.is_empty() {
+			crate::autobind_sockaddr()
+		} else {
+			crate::abstract_sockaddr(name)?
+		};
+		Self::bind_sockaddr(&address, backlog)
+	}
+
+	/// Bind a new seqpacket listener to the given socket address.
+	fn bind_sockaddr(address: &socket2::SockAddr, backlog: std::os::raw::c_int) -> std::io::Result<Self> {
+		Self::bind_socket(crate::new_socket()?, address, backlog)
+	}
+
+	/// Bind a socket to the given socket address and start listening.
+	pub(crate) fn bind_socket(
+		socket: socket2::Socket,
+		address: &socket2::SockAddr,
+		backlog: std::os::raw::c_int,
+	) -> std::io::Result<Self> {
+		socket.bind(address)?;
 		socket.listen(backlog)?;
-		Self::new(socket)
+		Self::new(socket, Some(backlog))
+	}
+
+	/// Create a listener from a socket passed by systemd socket activation.
+	///
+	/// The `name` must match the `FileDescriptorName=` option of the socket unit.
+	/// If the socket unit does not specify a name, systemd uses the name of the socket unit.
+	///
+	/// This function reads the `LISTEN_PID`, `LISTEN_FDS` and `LISTEN_FDNAMES` environment variables.
+	/// It verifies that the file descriptor is a listening unix seqpacket socket before taking ownership of it.
+	/// Each socket can only be taken once: later calls with the same name return an error.
+	#[cfg(any(doc, target_os = "linux"))]
+	pub fn from_systemd(name: &str) -> std::io::Result<Self> {
+		let socket = crate::systemd::take_listen_fd(name)?;
+		Self::new(socket, None)
+	}
+
+	/// Remove the socket file when the listener is dropped.
+	///
+	/// This only has an effect if the listener was bound to a filesystem path by this crate.
+	/// The listener remembers the exact file it created,
+	/// and it will not remove the path if it has been replaced by a different file in the meantime.
+	///
+	/// By default, the socket file is not removed.
+	pub fn set_unlink_on_drop(&mut self, unlink: bool) {
+		if let Some(socket_file) = &mut self.socket_file {
+			socket_file.unlink = unlink;
+		}
+	}
+
+	/// Check if the socket file will be removed when the listener is dropped.
+	///
+	/// See [`Self::set_unlink_on_drop()`] for more information.
+	pub fn unlink_on_drop(&self) -> bool {
+		self.socket_file.as_ref().is_some_and(|x| x.unlink)
+	}
+
+	/// Set or clear the close-on-exec flag on accepted sockets.
+	///
+	/// By default, accepted sockets have the close-on-exec flag set,
+	/// so they are not inherited by child processes created with `exec`.
+	/// Clearing the flag can be useful to pass accepted connections to an exec'd worker process.
+	///
+	/// Where supported, the flag is applied atomically when the connection is accepted by using `accept4()`.
+	/// Accepted sockets are always in non-blocking mode, since that is required by the tokio runtime.
+	pub fn set_accept_cloexec(&mut self, cloexec: bool) {
+		self.accept_cloexec = cloexec;
+	}
+
+	/// Check if accepted sockets get the close-on-exec flag.
+	///
+	/// See [`Self::set_accept_cloexec()`] for more information.
+	pub fn accept_cloexec(&self) -> bool {
+		self.accept_cloexec
+	}
+
+	/// Limit the number of open connections accepted by this listener.
+	///
+	/// When the limit is reached, [`Self::accept()`] and related functions wait until an accepted connection is closed.
+	/// A connection counts as open until the [`UnixSeqpacket`] is dropped or converted into a file descriptor.
+	/// Pending connections accepted with [`Self::accept_pending()`] are counted, but are not limited.
+	///
+	/// Connections accepted before calling this function are not counted towards the new limit.
+	/// Pass `None` to remove the limit.
+	pub fn set_max_connections(&mut self, max: Option<usize>) {
+		self.limit = max.map(ConnectionLimit::new);
+	}
+
+	/// Get the maximum number of open connections accepted by this listener.
+	///
+	/// See [`Self::set_max_connections()`] for more information.
+	pub fn max_connections(&self) -> Option<usize> {
+		self.limit.as_ref().map(|limit| limit.max())
+	}
+
+	/// Get the number of open connections counted towards the connection limit.
+	///
+	/// Returns `None` if no connection limit is set.
+	pub fn connection_count(&self) -> Option<usize> {
+		self.limit.as_ref().map(|limit| limit.count())
+	}
+
+	/// Get the backlog that was requested when the listener was created.
+	///
+	/// Note that the kernel may silently limit the backlog to a lower value.
+	/// On Linux, the backlog is capped to the value of the `net.core.somaxconn` sysctl.
+	///
+	/// Returns `None` if the backlog is not known,
+	/// for example when the listener was created from an existing file descriptor.
+	pub fn backlog(&self) -> Option<std::os::raw::c_int> {
+		self.backlog
+	}
+
+	/// Create a new listener from a blocking listener.
+	///
+	/// The listener is put in non-blocking mode and registered with the tokio runtime.
+	/// This function must be called from within the context of a tokio runtime.
+	pub fn from_std(listener: crate::blocking::UnixSeqpacketListener) -> std::io::Result<Self> {
+		let (socket, backlog) = listener.into_parts();
+		socket.set_nonblocking(true)?;
+		Self::new(socket, backlog)
+	}
+
+	/// Deregister the listener from the tokio runtime and convert it into a blocking listener.
+	///
+	/// The listener is put in blocking mode.
+	/// The socket file will not be removed, even if [`Self::set_unlink_on_drop()`] was used.
+	pub fn into_std(self) -> std::io::Result<crate::blocking::UnixSeqpacketListener> {
+		let backlog = self.backlog;
+		let socket = self.into_socket();
+		socket.set_nonblocking(false)?;
+		Ok(crate::blocking::UnixSeqpacketListener::new(socket, backlog))
 	}
 
-	/// Get the socket address of the local half of this connection.
+	/// Deregister the listener from the tokio runtime and return the inner socket.
+	///
+	/// The socket file will not be removed, even if [`Self::set_unlink_on_drop()`] was used.
+	fn into_socket(mut self) -> socket2::Socket {
+		if let Some(socket_file) = &mut self.socket_file {
+			socket_file.unlink = false;
+		}
+		self.io.into_inner()
+	}
+
+	/// Get the socket address the listener is bound to.
+	///
+	/// This can be used to discover the address when it was chosen by the kernel,
+	/// or to advertise the address to clients.
 	pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
 		let addr = self.io.get_ref().local_addr()?;
 		Ok(crate::sockaddr_as_unix(&addr).unwrap())
 	}
 
+	/// Get the size of the send buffer (`SO_SNDBUF`).
+	///
+	/// On Linux, accepted sockets inherit the buffer sizes of the listener.
+	pub fn send_buffer_size(&self) -> std::io::Result<usize> {
+		self.io.get_ref().send_buffer_size()
+	}
+
+	/// Set the size of the send buffer (`SO_SNDBUF`).
+	///
+	/// The kernel may adjust the value, use [`Self::send_buffer_size()`] to get the actual size.
+	pub fn set_send_buffer_size(&self, size: usize) -> std::io::Result<()> {
+		self.io.get_ref().set_send_buffer_size(size)
+	}
+
+	/// Get the size of the receive buffer (`SO_RCVBUF`).
+	///
+	/// On Linux, accepted sockets inherit the buffer sizes of the listener.
+	pub fn recv_buffer_size(&self) -> std::io::Result<usize> {
+		self.io.get_ref().recv_buffer_size()
+	}
+
+	/// Set the size of the receive buffer (`SO_RCVBUF`).
+	///
+	/// The kernel may adjust the value, use [`Self::recv_buffer_size()`] to get the actual size.
+	pub fn set_recv_buffer_size(&self, size: usize) -> std::io::Result<()> {
+		self.io.get_ref().set_recv_buffer_size(size)
+	}
+
+	/// Borrow the listener as a [`socket2::SockRef`](socket2_06::SockRef) to access socket options that are not wrapped by this crate.
+	///
+	/// This function is only available when the `socket2-06` feature is enabled.
+	#[cfg(feature = "socket2-06")]
+	pub fn socket_ref(&self) -> socket2_06::SockRef<'_> {
+		socket2_06::SockRef::from(self)
+	}
+
 	/// Get the value of the `SO_ERROR` option.
+	///
+	/// This returns and clears the pending error on the listener, if any.
+	/// It can be used in an accept loop to check if the listener itself is in an error state
+	/// after [`Self::accept()`] returned an error.
 	pub fn take_error(&self) -> std::io::Result<Option<std::io::Error>> {
 		self.io.get_ref().take_error()
 	}
 
 	/// Check if there is a connection ready to accept.
-	pub fn poll_accept(&mut self, cx: &mut Context) -> Poll<std::io::Result<(UnixSeqpacket, SocketAddr)>> {
+	///
+	/// On success, this returns the connected socket and the address of the peer.
+	///
+	/// If there is no connection ready yet, the current task is scheduled to wake up when a new connection arrives.
+	/// Only the task from the most recent call is woken up.
+	pub fn poll_accept(&self, cx: &mut Context) -> Poll<std::io::Result<(UnixSeqpacket, SocketAddr)>> {
+		// Only take a permit once a connection is ready, so idle accepting tasks do not hold free slots.
 		let mut ready_guard = ready!(self.io.poll_read_ready(cx)?);
 
-		let (socket, addr) = match self.io.get_ref().accept() {
-			Ok(x) => x,
-			Err(e) => {
-				if e.kind() == std::io::ErrorKind::WouldBlock {
-					ready_guard.clear_ready();
-					return Poll::Pending;
-				} else {
-					return Poll::Ready(Err(e));
-				}
-			},
+		let permit = match &self.limit {
+			Some(limit) => Some(ready!(limit.poll_acquire(cx))),
+			None => None,
 		};
 
-		socket.set_nonblocking(true)?;
+		match self.try_accept(permit) {
+			Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+				ready_guard.clear_ready();
+				record_metric!(record_would_block(self.io.as_raw_fd()));
+				Poll::Pending
+			},
+			x => Poll::Ready(x),
+		}
+	}
+
+	/// Accept a connection without waiting for the listener to become ready.
+	///
+	/// The connection permit, if any, is attached to the accepted socket.
+	fn try_accept(&self, permit: Option<ConnectionPermit>) -> std::io::Result<(UnixSeqpacket, SocketAddr)> {
+		let (socket, addr) = crate::accept(self.io.as_raw_fd(), self.accept_cloexec)?;
 		let addr = crate::sockaddr_as_unix(&addr).unwrap();
-		Poll::Ready(Ok((UnixSeqpacket::new(socket)?, addr)))
+		trace_event!(debug, listener = self.io.as_raw_fd(), fd = socket.as_raw_fd(), peer = ?addr, "accepted connection");
+		Ok((UnixSeqpacket::new(socket)?.with_permit(permit), addr))
 	}
 
 	/// Accept a new incoming connection on the listener.
-	pub async fn accept(&mut self) -> std::io::Result<(UnixSeqpacket, SocketAddr)> {
+	///
+	/// On success, this returns the connected socket and the address of the peer.
+	/// The address is taken directly from the `accept()` call,
+	/// so there is no need to call [`UnixSeqpacket::peer_addr()`] separately.
+	///
+	/// Note that the peer address is usually unnamed,
+	/// unless the connecting socket was explicitly bound to an address before connecting.
+	pub async fn accept(&self) -> std::io::Result<(UnixSeqpacket, SocketAddr)> {
 		poll_fn(|cx| self.poll_accept(cx)).await
 	}
+
+	/// Accept a new incoming connection on the listener, unless the cancellation token fires first.
+	///
+	/// Returns `Ok(None)` if the token was cancelled before a connection was accepted.
+	/// Pending connections remain queued in that case.
+	///
+	/// This function is only available when the `tokio-util` feature is enabled.
+	#[cfg(feature = "tokio-util")]
+	pub async fn accept_or_cancelled(
+		&self,
+		token: &tokio_util::sync::CancellationToken,
+	) -> std::io::Result<Option<(UnixSeqpacket, SocketAddr)>> {
+		token.run_until_cancelled(self.accept()).await.transpose()
+	}
+
+	/// Accept a new incoming connection and get the credentials of the peer.
+	///
+	/// The credentials are retrieved with [`UnixSeqpacket::peer_cred()`] before the connection is returned.
+	/// If the credentials can not be retrieved, the connection is closed and an error is returned.
+	///
+	/// Note that the credentials are those of the process that called `connect`,
+	/// which is not necessarily the process that currently has the file descriptor of the other side of the connection.
+	pub async fn accept_with_credentials(&self) -> std::io::Result<(UnixSeqpacket, UCred)> {
+		let (socket, _addr) = self.accept().await?;
+		let cred = socket.peer_cred()?;
+		Ok((socket, cred))
+	}
+
+	/// Stop accepting new connections, without closing the listener.
+	///
+	/// After this function returns, new connection attempts are refused by the kernel.
+	/// Connections that were already queued can still be accepted with [`Self::accept_pending()`],
+	/// or rejected with [`Self::reject_pending()`].
+	///
+	/// Note that [`Self::accept()`] never completes once all pending connections have been accepted.
+	///
+	/// This can be used to drain the listener during a graceful shutdown or a rolling restart.
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub fn shutdown(&self) -> std::io::Result<()> {
+		self.io.get_ref().shutdown(std::net::Shutdown::Read)
+	}
+
+	/// Accept a connection that was queued before the listener was shut down.
+	///
+	/// This function does not wait for new connections.
+	/// It returns `Ok(None)` when all pending connections have been accepted.
+	///
+	/// This function is meant to be used after calling [`Self::shutdown()`].
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub fn accept_pending(&self) -> std::io::Result<Option<(UnixSeqpacket, SocketAddr)>> {
+		let permit = self.limit.as_ref().map(|limit| limit.acquire_unchecked());
+		match self.try_accept(permit) {
+			Ok(x) => Ok(Some(x)),
+			Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => Ok(None),
+			Err(e) => Err(e),
+		}
+	}
+
+	/// Reject all connections that were queued before the listener was shut down.
+	///
+	/// Each pending connection is accepted and closed immediately.
+	/// Returns the number of rejected connections.
+	///
+	/// This function is meant to be used after calling [`Self::shutdown()`].
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub fn reject_pending(&self) -> std::io::Result<usize> {
+		let mut count = 0;
+		while self.accept_pending()?.is_some() {
+			count += 1;
+		}
+		Ok(count)
+	}
+
+	/// Get a stream of incoming connections.
+	///
+	/// The stream never ends: it yields a new item for every accepted connection or error.
+	/// Dropping the stream before an item is ready does not lose any connections.
+	pub fn incoming(&self) -> Incoming<'_> {
+		Incoming { listener: self }
+	}
+}
+
+impl AsRawFd for UnixSeqpacketListener {
+	fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+		self.io.as_raw_fd()
+	}
+}
+
+impl AsFd for UnixSeqpacketListener {
+	fn as_fd(&self) -> BorrowedFd<'_> {
+		// The file descriptor remains open for as long as `self` is borrowed.
+		unsafe { BorrowedFd::borrow_raw(self.as_raw_fd()) }
+	}
+}
+
+/// Take ownership of a file descriptor and wrap it in a [`UnixSeqpacketListener`].
+///
+/// The file descriptor must be a listening unix seqpacket socket, or an error is returned.
+/// The file descriptor is put in non-blocking mode and registered with the tokio runtime.
+/// If anything fails, the file descriptor is closed.
+impl TryFrom<OwnedFd> for UnixSeqpacketListener {
+	type Error = std::io::Error;
+
+	fn try_from(fd: OwnedFd) -> std::io::Result<Self> {
+		crate::check_socket_type(fd.as_raw_fd(), libc::SOCK_SEQPACKET)?;
+		if !crate::is_listening(fd.as_raw_fd())? {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::InvalidInput,
+				format!("file descriptor {} is not a listening socket", fd.as_raw_fd()),
+			));
+		}
+		let socket = unsafe { socket2::Socket::from_raw_fd(fd.into_raw_fd()) };
+		socket.set_nonblocking(true)?;
+		Self::new(socket, None)
+	}
+}
+
+/// Deregister the listener from the tokio runtime and return the inner file descriptor.
+impl From<UnixSeqpacketListener> for OwnedFd {
+	fn from(listener: UnixSeqpacketListener) -> Self {
+		unsafe { OwnedFd::from_raw_fd(listener.into_socket().into_raw_fd()) }
+	}
+}
+
+/// Check if a path refers to a socket file that nobody is listening on.
+fn is_stale_socket(path: &Path) -> bool {
+	use std::os::unix::fs::FileTypeExt;
+
+	match std::fs::symlink_metadata(path) {
+		Ok(metadata) if metadata.file_type().is_socket() => (),
+		_ => return false,
+	}
+
+	let address = match crate::path_sockaddr(path) {
+		Ok(x) => x,
+		Err(_) => return false,
+	};
+	let socket = match crate::new_socket() {
+		Ok(x) => x,
+		Err(_) => return false,
+	};
+
+	// Only a refused connection means nobody is listening.
+	// Any other error (like a full backlog or a different socket type) could come from a live socket.
+	match socket.connect(&address) {
+		Err(e) => e.kind() == std::io::ErrorKind::ConnectionRefused,
+		Ok(()) => false,
+	}
+}
+
+/// A socket file created by a listener.
+#[derive(Debug)]
+struct SocketFile {
+	/// The absolute path of the socket file.
+	path: PathBuf,
+
+	/// The device ID of the socket file.
+	dev: u64,
+
+	/// The inode number of the socket file.
+	ino: u64,
+
+	/// If true, remove the socket file when dropped.
+	unlink: bool,
+}
+
+impl SocketFile {
+	/// Remember the identity of a newly created socket file.
+	///
+	/// Returns `None` if the socket file could not be inspected.
+	fn new(path: &Path) -> Option<Self> {
+		use std::os::unix::fs::MetadataExt;
+
+		let path = if path.is_relative() {
+			std::env::current_dir().ok()?.join(path)
+		} else {
+			path.to_path_buf()
+		};
+		let metadata = std::fs::symlink_metadata(&path).ok()?;
+		Some(Self {
+			path,
+			dev: metadata.dev(),
+			ino: metadata.ino(),
+			unlink: false,
+		})
+	}
+}
+
+impl Drop for SocketFile {
+	fn drop(&mut self) {
+		use std::os::unix::fs::MetadataExt;
+
+		if !self.unlink {
+			return;
+		}
+		if let Ok(metadata) = std::fs::symlink_metadata(&self.path) {
+			if metadata.dev() == self.dev && metadata.ino() == self.ino {
+				let _ = std::fs::remove_file(&self.path);
+			}
+		}
+	}
+}
+
+/// Stream of incoming connections for a [`UnixSeqpacketListener`].
+///
+/// Created by [`UnixSeqpacketListener::incoming()`].
+#[derive(Debug)]
+pub struct Incoming<'a> {
+	listener: &'a UnixSeqpacketListener,
+}
+
+impl Stream for Incoming<'_> {
+	type Item = std::io::Result<UnixSeqpacket>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let (socket, _addr) = ready!(self.listener.poll_accept(cx)?);
+		Poll::Ready(Some(Ok(socket)))
+	}
 }
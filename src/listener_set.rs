@@ -0,0 +1,99 @@
+use futures::future::poll_fn;
+use std::os::unix::net::SocketAddr;
+use std::task::{Context, Poll};
+
+use crate::{UnixSeqpacket, UnixSeqpacketListener};
+
+/// Identifier for a listener in a [`ListenerSet`].
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ListenerId(u64);
+
+/// A set of listeners that can accept connections from all listeners in a single task.
+///
+/// Connections are accepted fairly: after a listener produced a connection,
+/// the other listeners are polled first on the next call.
+///
+/// Listeners can be added and removed at any time.
+#[derive(Debug, Default)]
+pub struct ListenerSet {
+	listeners: Vec<(ListenerId, UnixSeqpacketListener)>,
+	next_id: u64,
+	next_index: usize,
+}
+
+impl ListenerSet {
+	/// Create a new empty listener set.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Add a listener to the set.
+	///
+	/// Returns an identifier that can be used to remove the listener again.
+	pub fn insert(&mut self, listener: UnixSeqpacketListener) -> ListenerId {
+		let id = ListenerId(self.next_id);
+		self.next_id += 1;
+		self.listeners.push((id, listener));
+		id
+	}
+
+	/// Remove a listener from the set.
+	///
+	/// Returns `None` if the set does not contain a listener with the given identifier.
+	pub fn remove(&mut self, id: ListenerId) -> Option<UnixSeqpacketListener> {
+		let index = self.listeners.iter().position(|(x, _)| *x == id)?;
+		let (_, listener) = self.listeners.remove(index);
+		if self.next_index > index {
+			self.next_index -= 1;
+		}
+		Some(listener)
+	}
+
+	/// Get a reference to a listener in the set.
+	pub fn get(&self, id: ListenerId) -> Option<&UnixSeqpacketListener> {
+		self.listeners.iter().find(|(x, _)| *x == id).map(|(_, listener)| listener)
+	}
+
+	/// Get the number of listeners in the set.
+	pub fn len(&self) -> usize {
+		self.listeners.len()
+	}
+
+	/// Check if the set is empty.
+	pub fn is_empty(&self) -> bool {
+		self.listeners.is_empty()
+	}
+
+	/// Iterate over the listeners in the set.
+	pub fn iter(&self) -> impl Iterator<Item = (ListenerId, &UnixSeqpacketListener)> {
+		self.listeners.iter().map(|(id, listener)| (*id, listener))
+	}
+
+	/// Check if there is a connection ready to accept on any of the listeners.
+	///
+	/// On success, this returns the identifier of the listener that accepted the connection,
+	/// the connected socket and the address of the peer.
+	///
+	/// If there is no connection ready yet, the current task is scheduled to wake up when a new connection arrives.
+	/// If the set is empty, this function always returns [`Poll::Pending`].
+	/// In that case, the task is not woken up when a listener is added.
+	pub fn poll_accept(&mut self, cx: &mut Context) -> Poll<std::io::Result<(ListenerId, UnixSeqpacket, SocketAddr)>> {
+		let count = self.listeners.len();
+		for i in 0..count {
+			let index = (self.next_index + i) % count;
+			let (id, listener) = &self.listeners[index];
+			if let Poll::Ready(result) = listener.poll_accept(cx) {
+				self.next_index = (index + 1) % count;
+				return Poll::Ready(result.map(|(socket, addr)| (*id, socket, addr)));
+			}
+		}
+		Poll::Pending
+	}
+
+	/// Accept a new incoming connection on any of the listeners.
+	///
+	/// See [`Self::poll_accept()`] for more information.
+	pub async fn accept(&mut self) -> std::io::Result<(ListenerId, UnixSeqpacket, SocketAddr)> {
+		poll_fn(|cx| self.poll_accept(cx)).await
+	}
+}
@@ -0,0 +1,132 @@
+//! Stream of received messages.
+
+use futures::stream::Stream;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::UnixSeqpacket;
+
+/// How a [`MessageStream`] allocates the buffer for each message.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AllocationStrategy {
+	/// Determine the size of the next message first, and allocate a buffer of exactly that size.
+	///
+	/// This needs an extra system call per message, but never allocates more memory than needed.
+	Exact,
+
+	/// Allocate a buffer of the maximum message size, and shrink it to the size of the received message.
+	///
+	/// This needs only one system call per message, but allocates and zeroes the maximum size for every message.
+	MaxSize,
+}
+
+/// Stream of messages received on a [`UnixSeqpacket`].
+///
+/// Created by [`UnixSeqpacket::messages()`].
+///
+/// Messages larger than the maximum size are discarded,
+/// and reported as an error wrapping a [`MessageTooLargeError`][crate::MessageTooLargeError].
+/// The stream continues with the next message after an error.
+///
+/// An empty message and a closed connection can not be distinguished,
+/// so the stream ends after receiving an empty message.
+///
+/// If other tasks are receiving on the same socket concurrently with [`AllocationStrategy::Exact`],
+/// they may take a message between determining the size and receiving it.
+/// In that case, the next message may be discarded even if it fits in the maximum size.
+///
+/// # Example
+/// ```no_run
+/// # async fn foo(socket: tokio_seqpacket::UnixSeqpacket) -> std::io::Result<()> {
+/// use futures::StreamExt;
+///
+/// let mut messages = socket.messages().max_size(4096);
+/// while let Some(message) = messages.next().await {
+///     println!("received {} bytes", message?.len());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MessageStream<'a> {
+	socket: &'a UnixSeqpacket,
+	max_size: usize,
+	allocation: AllocationStrategy,
+	done: bool,
+}
+
+impl<'a> MessageStream<'a> {
+	/// The maximum message size used by default.
+	pub const DEFAULT_MAX_SIZE: usize = 64 * 1024;
+
+	/// Set the maximum size of a message in bytes.
+	///
+	/// Larger messages are discarded and reported as an error.
+	pub fn max_size(mut self, max_size: usize) -> Self {
+		self.max_size = max_size;
+		self
+	}
+
+	/// Set how the buffer for each message is allocated.
+	///
+	/// The default is [`AllocationStrategy::Exact`].
+	pub fn allocation(mut self, allocation: AllocationStrategy) -> Self {
+		self.allocation = allocation;
+		self
+	}
+
+	/// Get the socket the messages are received from.
+	pub fn socket(&self) -> &'a UnixSeqpacket {
+		self.socket
+	}
+
+	/// Receive the next message without blocking.
+	fn poll_recv_message(&self, cx: &mut Context) -> Poll<std::io::Result<Vec<u8>>> {
+		let capacity = match self.allocation {
+			// Oversized messages do not fit in the buffer, so they are discarded when receiving them.
+			AllocationStrategy::Exact => ready!(self.socket.poll_next_message_size(cx)?).min(self.max_size),
+			AllocationStrategy::MaxSize => self.max_size,
+		};
+		let mut buffer = vec![0; capacity];
+		let len = ready!(self.socket.poll_recv_or_discard(cx, &mut buffer)?);
+		buffer.truncate(len);
+		if self.allocation == AllocationStrategy::MaxSize {
+			buffer.shrink_to_fit();
+		}
+		Poll::Ready(Ok(buffer))
+	}
+}
+
+impl Stream for MessageStream<'_> {
+	type Item = std::io::Result<Vec<u8>>;
+
+	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		if self.done {
+			return Poll::Ready(None);
+		}
+		match ready!(self.poll_recv_message(cx)) {
+			Ok(message) if message.is_empty() => {
+				self.done = true;
+				Poll::Ready(None)
+			},
+			x => Poll::Ready(Some(x)),
+		}
+	}
+}
+
+impl UnixSeqpacket {
+	/// Get a stream of the messages received on this socket.
+	///
+	/// The stream uses a maximum message size of [`MessageStream::DEFAULT_MAX_SIZE`] and [`AllocationStrategy::Exact`].
+	/// Use [`MessageStream::max_size()`] and [`MessageStream::allocation()`] to change them.
+	///
+	/// Dropping the stream before an item is ready does not lose any messages.
+	pub fn messages(&self) -> MessageStream<'_> {
+		MessageStream {
+			socket: self,
+			max_size: MessageStream::DEFAULT_MAX_SIZE,
+			allocation: AllocationStrategy::Exact,
+			done: false,
+		}
+	}
+}
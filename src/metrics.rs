@@ -0,0 +1,220 @@
+//! Counters for messages and I/O events on sockets.
+//!
+//! Install a [`Recorder`] with [`set_recorder()`] to be notified of every message that is sent or received by any socket.
+//! Every notification includes the file descriptor of the socket,
+//! so a recorder can keep counters per socket as well as global counters.
+//!
+//! The [`Counters`] type is a ready-to-use recorder that keeps global counters.
+//!
+//! # Example
+//! ```
+//! use tokio_seqpacket::metrics::{self, Counters};
+//!
+//! static COUNTERS: Counters = Counters::new();
+//!
+//! metrics::set_recorder(&COUNTERS).unwrap();
+//! // ...
+//! let snapshot = COUNTERS.snapshot();
+//! println!("sent {} messages with {} bytes", snapshot.messages_sent, snapshot.bytes_sent);
+//! ```
+
+use std::os::unix::io::RawFd;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+
+/// Receiver of metric events.
+///
+/// All methods have a default implementation that does nothing,
+/// so implementations only need to override the events they are interested in.
+///
+/// The methods are called directly from the send and receive paths, so they should be cheap and must not block.
+pub trait Recorder: Send + Sync {
+	/// A message of `size` bytes carrying `fds` file descriptors was sent on socket `fd`.
+	fn message_sent(&self, fd: RawFd, size: usize, fds: usize) {
+		let _ = (fd, size, fds);
+	}
+
+	/// A message carrying `fds` file descriptors was received on socket `fd`, and `size` bytes of it were written to the buffer.
+	///
+	/// If the message was truncated, the discarded part is not included in `size`.
+	fn message_received(&self, fd: RawFd, size: usize, fds: usize) {
+		let _ = (fd, size, fds);
+	}
+
+	/// A message received on socket `fd` did not fit in the buffer and was truncated.
+	fn message_truncated(&self, fd: RawFd) {
+		let _ = fd;
+	}
+
+	/// The ancillary data of a message received on socket `fd` was truncated.
+	fn ancillary_truncated(&self, fd: RawFd) {
+		let _ = fd;
+	}
+
+	/// An operation on socket `fd` would block and the task will be woken up when the socket is ready again.
+	fn would_block(&self, fd: RawFd) {
+		let _ = fd;
+	}
+}
+
+/// The globally installed recorder.
+static RECORDER: OnceLock<&'static dyn Recorder> = OnceLock::new();
+
+/// Install the global recorder.
+///
+/// The recorder can only be installed once.
+/// If a recorder was already installed, this returns an error.
+pub fn set_recorder(recorder: &'static dyn Recorder) -> Result<(), SetRecorderError> {
+	RECORDER.set(recorder).map_err(|_| SetRecorderError { _private: () })
+}
+
+/// Report a sent message to the installed recorder.
+pub(crate) fn record_sent(fd: RawFd, size: usize, fds: usize) {
+	if let Some(recorder) = RECORDER.get() {
+		recorder.message_sent(fd, size, fds);
+	}
+}
+
+/// Report a received message to the installed recorder.
+pub(crate) fn record_received(fd: RawFd, size: usize, fds: usize, truncated: bool, ancillary_truncated: bool) {
+	if let Some(recorder) = RECORDER.get() {
+		recorder.message_received(fd, size, fds);
+		if truncated {
+			recorder.message_truncated(fd);
+		}
+		if ancillary_truncated {
+			recorder.ancillary_truncated(fd);
+		}
+	}
+}
+
+/// Report an operation that would block to the installed recorder.
+pub(crate) fn record_would_block(fd: RawFd) {
+	if let Some(recorder) = RECORDER.get() {
+		recorder.would_block(fd);
+	}
+}
+
+/// Error returned by [`set_recorder()`] if a recorder was already installed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SetRecorderError {
+	_private: (),
+}
+
+impl std::fmt::Display for SetRecorderError {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(f, "a metrics recorder was already installed")
+	}
+}
+
+impl std::error::Error for SetRecorderError {}
+
+/// Recorder that keeps global counters for all sockets.
+///
+/// Use [`Self::snapshot()`] to read the current values.
+#[derive(Debug, Default)]
+pub struct Counters {
+	messages_sent: AtomicU64,
+	bytes_sent: AtomicU64,
+	fds_sent: AtomicU64,
+	messages_received: AtomicU64,
+	bytes_received: AtomicU64,
+	fds_received: AtomicU64,
+	messages_truncated: AtomicU64,
+	ancillary_truncated: AtomicU64,
+	would_block: AtomicU64,
+}
+
+impl Counters {
+	/// Create a new set of counters, all starting at zero.
+	pub const fn new() -> Self {
+		Self {
+			messages_sent: AtomicU64::new(0),
+			bytes_sent: AtomicU64::new(0),
+			fds_sent: AtomicU64::new(0),
+			messages_received: AtomicU64::new(0),
+			bytes_received: AtomicU64::new(0),
+			fds_received: AtomicU64::new(0),
+			messages_truncated: AtomicU64::new(0),
+			ancillary_truncated: AtomicU64::new(0),
+			would_block: AtomicU64::new(0),
+		}
+	}
+
+	/// Get the current values of the counters.
+	///
+	/// The counters are read one by one, so the snapshot may be slightly inconsistent if other threads are using sockets.
+	pub fn snapshot(&self) -> CounterSnapshot {
+		CounterSnapshot {
+			messages_sent: self.messages_sent.load(Ordering::Relaxed),
+			bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+			fds_sent: self.fds_sent.load(Ordering::Relaxed),
+			messages_received: self.messages_received.load(Ordering::Relaxed),
+			bytes_received: self.bytes_received.load(Ordering::Relaxed),
+			fds_received: self.fds_received.load(Ordering::Relaxed),
+			messages_truncated: self.messages_truncated.load(Ordering::Relaxed),
+			ancillary_truncated: self.ancillary_truncated.load(Ordering::Relaxed),
+			would_block: self.would_block.load(Ordering::Relaxed),
+		}
+	}
+}
+
+impl Recorder for Counters {
+	fn message_sent(&self, _fd: RawFd, size: usize, fds: usize) {
+		self.messages_sent.fetch_add(1, Ordering::Relaxed);
+		self.bytes_sent.fetch_add(size as u64, Ordering::Relaxed);
+		self.fds_sent.fetch_add(fds as u64, Ordering::Relaxed);
+	}
+
+	fn message_received(&self, _fd: RawFd, size: usize, fds: usize) {
+		self.messages_received.fetch_add(1, Ordering::Relaxed);
+		self.bytes_received.fetch_add(size as u64, Ordering::Relaxed);
+		self.fds_received.fetch_add(fds as u64, Ordering::Relaxed);
+	}
+
+	fn message_truncated(&self, _fd: RawFd) {
+		self.messages_truncated.fetch_add(1, Ordering::Relaxed);
+	}
+
+	fn ancillary_truncated(&self, _fd: RawFd) {
+		self.ancillary_truncated.fetch_add(1, Ordering::Relaxed);
+	}
+
+	fn would_block(&self, _fd: RawFd) {
+		self.would_block.fetch_add(1, Ordering::Relaxed);
+	}
+}
+
+/// The values of [`Counters`] at a point in time.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct CounterSnapshot {
+	/// The number of messages sent.
+	pub messages_sent: u64,
+
+	/// The number of bytes sent.
+	pub bytes_sent: u64,
+
+	/// The number of file descriptors sent.
+	pub fds_sent: u64,
+
+	/// The number of messages received.
+	pub messages_received: u64,
+
+	/// The number of bytes received into buffers.
+	///
+	/// The discarded part of truncated messages is not included.
+	pub bytes_received: u64,
+
+	/// The number of file descriptors received.
+	pub fds_received: u64,
+
+	/// The number of received messages that were truncated.
+	pub messages_truncated: u64,
+
+	/// The number of received messages with truncated ancillary data.
+	pub ancillary_truncated: u64,
+
+	/// The number of times an operation would block and had to wait for the socket to become ready again.
+	pub would_block: u64,
+}
@@ -0,0 +1,125 @@
+use libc::pid_t;
+use std::ffi::OsString;
+use std::os::unix::ffi::OsStringExt;
+use std::os::unix::io::{AsRawFd, OwnedFd};
+use std::path::{Path, PathBuf};
+
+/// Information about the process on the other side of a connection.
+///
+/// Retrieved with [`UnixSeqpacket::peer_process()`](crate::UnixSeqpacket::peer_process).
+/// The information is read from `/proc` and reflects the state of the process at that moment,
+/// not at the moment the connection was established.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct PeerProcess {
+	/// PID (process ID) of the process.
+	pid: pid_t,
+	/// Path of the executable, if it could be read.
+	exe: Option<PathBuf>,
+	/// Command line arguments of the process.
+	cmdline: Vec<OsString>,
+	/// Start time of the process in clock ticks after system boot.
+	start_time: u64,
+}
+
+impl PeerProcess {
+	/// Read the information of a process from `/proc`.
+	///
+	/// If a pidfd for the process is given, it is used to verify that the process ID was not reused while reading.
+	pub(crate) fn read(pid: pid_t, pidfd: Option<&OwnedFd>) -> std::io::Result<Self> {
+		let proc_dir = PathBuf::from(format!("/proc/{}", pid));
+		let stat = std::fs::read(proc_dir.join("stat"))?;
+		let start_time = parse_start_time(&stat).ok_or_else(|| {
+			std::io::Error::new(
+				std::io::ErrorKind::InvalidData,
+				format!("failed to parse start time from {}/stat", proc_dir.display()),
+			)
+		})?;
+		let cmdline = parse_cmdline(std::fs::read(proc_dir.join("cmdline"))?);
+		// Reading the executable of a process owned by a different user requires privileges.
+		let exe = std::fs::read_link(proc_dir.join("exe")).ok();
+
+		if let Some(pidfd) = pidfd {
+			if pidfd_pid(pidfd)? != Some(pid) {
+				return Err(std::io::Error::new(
+					std::io::ErrorKind::NotFound,
+					format!("peer process {} exited while reading its information", pid),
+				));
+			}
+		}
+
+		Ok(Self {
+			pid,
+			exe,
+			cmdline,
+			start_time,
+		})
+	}
+
+	/// Get the PID (process ID) of the process.
+	pub fn pid(&self) -> pid_t {
+		self.pid
+	}
+
+	/// Get the path of the executable of the process.
+	///
+	/// This is `None` if the path could not be read,
+	/// which usually means the process belongs to a different user.
+	pub fn exe(&self) -> Option<&Path> {
+		self.exe.as_deref()
+	}
+
+	/// Get the command line arguments of the process.
+	///
+	/// Note that a process can modify its own command line.
+	pub fn cmdline(&self) -> &[OsString] {
+		&self.cmdline
+	}
+
+	/// Get the start time of the process in clock ticks after system boot.
+	///
+	/// Together with the process ID, this uniquely identifies a process.
+	/// Divide by `sysconf(_SC_CLK_TCK)` to get the time in seconds.
+	pub fn start_time(&self) -> u64 {
+		self.start_time
+	}
+}
+
+/// Parse the start time from the contents of `/proc/<pid>/stat`.
+fn parse_start_time(stat: &[u8]) -> Option<u64> {
+	// The command name is in parentheses and may contain spaces or parentheses itself,
+	// so start after the last closing parenthesis.
+	let end_of_comm = stat.iter().rposition(|&byte| byte == b')')?;
+	let fields = std::str::from_utf8(&stat[end_of_comm + 1..]).ok()?;
+	// The start time is field 22, and the fields after the command name start at field 3.
+	fields.split_ascii_whitespace().nth(22 - 3)?.parse().ok()
+}
+
+/// Split the contents of `/proc/<pid>/cmdline` into separate arguments.
+fn parse_cmdline(mut cmdline: Vec<u8>) -> Vec<OsString> {
+	// Every argument is terminated by a null byte.
+	if cmdline.last() == Some(&0) {
+		cmdline.pop();
+	}
+	if cmdline.is_empty() {
+		return Vec::new();
+	}
+	cmdline
+		.split(|&byte| byte == 0)
+		.map(|arg| OsString::from_vec(arg.to_vec()))
+		.collect()
+}
+
+/// Get the process ID of a pidfd, or `None` if the process has exited.
+fn pidfd_pid(pidfd: &OwnedFd) -> std::io::Result<Option<pid_t>> {
+	let info = std::fs::read_to_string(format!("/proc/self/fdinfo/{}", pidfd.as_raw_fd()))?;
+	let pid = info
+		.lines()
+		.find_map(|line| line.strip_prefix("Pid:"))
+		.and_then(|pid| pid.trim().parse::<pid_t>().ok())
+		.ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "failed to parse pid from pidfd info"))?;
+	if pid > 0 {
+		Ok(Some(pid))
+	} else {
+		Ok(None)
+	}
+}
@@ -0,0 +1,166 @@
+//! Pool of reusable receive buffers.
+
+use std::sync::{Arc, Mutex};
+
+use crate::UnixSeqpacket;
+
+/// A pool of reusable buffers for receiving messages.
+///
+/// Buffers are taken from the pool by [`UnixSeqpacket::recv_pooled()`],
+/// and are returned to the pool automatically when the [`PooledBuffer`] is dropped.
+/// This avoids allocating and zeroing a new buffer for every received message.
+///
+/// The pool can be cloned cheaply to share it between tasks.
+/// All clones refer to the same buffers.
+///
+/// # Example
+/// ```no_run
+/// # async fn foo(socket: tokio_seqpacket::UnixSeqpacket) -> std::io::Result<()> {
+/// use tokio_seqpacket::BufferPool;
+///
+/// let pool = BufferPool::new(4096, 64);
+/// loop {
+///     let message = socket.recv_pooled(&pool).await?;
+///     println!("received {} bytes", message.len());
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BufferPool {
+	inner: Arc<PoolInner>,
+}
+
+#[derive(Debug)]
+struct PoolInner {
+	/// The size of each buffer.
+	buffer_size: usize,
+
+	/// The maximum number of idle buffers kept in the pool.
+	max_idle: usize,
+
+	/// The idle buffers.
+	buffers: Mutex<Vec<Vec<u8>>>,
+}
+
+/// A buffer taken from a [`BufferPool`] holding a received message.
+///
+/// The buffer dereferences to the received data.
+/// It is returned to the pool when dropped.
+#[derive(Debug)]
+pub struct PooledBuffer {
+	buffer: Vec<u8>,
+	len: usize,
+	pool: Arc<PoolInner>,
+}
+
+impl BufferPool {
+	/// Create a new pool of buffers of `buffer_size` bytes.
+	///
+	/// At most `max_idle` buffers are kept in the pool when they are not in use.
+	/// Buffers are allocated on demand, so the pool never limits the number of buffers in use.
+	pub fn new(buffer_size: usize, max_idle: usize) -> Self {
+		Self {
+			inner: Arc::new(PoolInner {
+				buffer_size,
+				max_idle,
+				buffers: Mutex::new(Vec::with_capacity(max_idle)),
+			}),
+		}
+	}
+
+	/// Get the size of the buffers in the pool.
+	///
+	/// Messages larger than this are truncated when received into a pooled buffer.
+	pub fn buffer_size(&self) -> usize {
+		self.inner.buffer_size
+	}
+
+	/// Get the number of idle buffers currently kept in the pool.
+	pub fn idle(&self) -> usize {
+		self.inner.lock().len()
+	}
+
+	/// Take a buffer from the pool, or allocate a new one if the pool is empty.
+	///
+	/// The returned buffer is empty until data is received into it.
+	pub(crate) fn take(&self) -> PooledBuffer {
+		let buffer = self.inner.lock().pop();
+		let buffer = buffer.unwrap_or_else(|| vec![0; self.inner.buffer_size]);
+		PooledBuffer {
+			buffer,
+			len: 0,
+			pool: self.inner.clone(),
+		}
+	}
+}
+
+impl PoolInner {
+	fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Vec<u8>>> {
+		// The list of buffers can not be left in an inconsistent state, so ignore poisoning.
+		self.buffers.lock().unwrap_or_else(|e| e.into_inner())
+	}
+}
+
+impl PooledBuffer {
+	/// Get the received data.
+	pub fn data(&self) -> &[u8] {
+		&self.buffer[..self.len]
+	}
+
+	/// Get the received data as a mutable slice.
+	pub fn data_mut(&mut self) -> &mut [u8] {
+		&mut self.buffer[..self.len]
+	}
+
+	/// Get the full buffer to receive data into.
+	pub(crate) fn full_buffer_mut(&mut self) -> &mut [u8] {
+		&mut self.buffer
+	}
+
+	/// Set the length of the received data.
+	pub(crate) fn set_len(&mut self, len: usize) {
+		self.len = len.min(self.buffer.len());
+	}
+}
+
+impl std::ops::Deref for PooledBuffer {
+	type Target = [u8];
+
+	fn deref(&self) -> &[u8] {
+		self.data()
+	}
+}
+
+impl std::ops::DerefMut for PooledBuffer {
+	fn deref_mut(&mut self) -> &mut [u8] {
+		self.data_mut()
+	}
+}
+
+impl AsRef<[u8]> for PooledBuffer {
+	fn as_ref(&self) -> &[u8] {
+		self.data()
+	}
+}
+
+impl Drop for PooledBuffer {
+	fn drop(&mut self) {
+		let mut buffers = self.pool.lock();
+		if buffers.len() < self.pool.max_idle {
+			buffers.push(std::mem::take(&mut self.buffer));
+		}
+	}
+}
+
+impl UnixSeqpacket {
+	/// Receive a message from the connected peer into a buffer from a [`BufferPool`].
+	///
+	/// The buffer is returned to the pool when the returned [`PooledBuffer`] is dropped.
+	/// Messages larger than [`BufferPool::buffer_size()`] are truncated.
+	pub async fn recv_pooled(&self, pool: &BufferPool) -> std::io::Result<PooledBuffer> {
+		let mut buffer = pool.take();
+		let len = self.recv(buffer.full_buffer_mut()).await?;
+		buffer.set_len(len);
+		Ok(buffer)
+	}
+}
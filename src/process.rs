@@ -0,0 +1,72 @@
+//! Spawn child processes that are connected to the parent with a seqpacket socket.
+//!
+//! This module is only available when the `process` feature is enabled.
+
+use std::os::unix::io::{AsRawFd, RawFd};
+use tokio::process::{Child, Command};
+
+use crate::UnixSeqpacket;
+
+/// Spawn a child process with one end of a connected socket pair at a known file descriptor.
+///
+/// A new pair of connected seqpacket sockets is created.
+/// One end is made available to the child process as file descriptor `child_fd`,
+/// and the other end is returned to the caller together with the child process.
+/// The socket in the child process is in blocking mode and does not have the close-on-exec flag set.
+///
+/// Any file descriptor that was already open as `child_fd` in the child process is replaced.
+/// Use a file descriptor that does not conflict with stdin, stdout or stderr,
+/// unless you explicitly want to replace one of them.
+///
+/// The child process can adopt the socket with [`UnixSeqpacket::try_from_fd()`].
+///
+/// The socket is passed to the child using a [`Command::pre_exec()`] hook.
+/// The command should not be spawned again after this function returns, since the hook refers to a closed socket.
+///
+/// # Example
+/// ```no_run
+/// # async fn foo() -> std::io::Result<()> {
+/// use tokio::process::Command;
+///
+/// let mut command = Command::new("worker");
+/// command.env("WORKER_FD", "3");
+/// let (mut child, socket) = tokio_seqpacket::process::spawn_with_seqpacket(&mut command, 3)?;
+/// socket.send(b"Hello worker!").await?;
+/// child.wait().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn spawn_with_seqpacket(command: &mut Command, child_fd: RawFd) -> std::io::Result<(Child, UnixSeqpacket)> {
+	if child_fd < 0 {
+		return Err(std::io::Error::new(
+			std::io::ErrorKind::InvalidInput,
+			format!("invalid file descriptor number for child process: {}", child_fd),
+		));
+	}
+
+	let (parent, child) = socket2::Socket::pair(socket2::Domain::unix(), crate::blocking_socket_type(), None)?;
+	parent.set_nonblocking(true)?;
+	let parent = UnixSeqpacket::new(parent)?;
+
+	let socket_fd = child.as_raw_fd();
+	unsafe {
+		// Only async-signal-safe functions may be used between fork() and exec().
+		command.pre_exec(move || {
+			if socket_fd == child_fd {
+				// The socket already has the right number, it only needs to survive exec().
+				crate::set_cloexec(socket_fd, false)
+			} else if libc::dup2(socket_fd, child_fd) == -1 {
+				// The descriptor created by dup2() does not have the close-on-exec flag set.
+				Err(std::io::Error::last_os_error())
+			} else {
+				Ok(())
+			}
+		});
+	}
+
+	let process = command.spawn()?;
+
+	// The parent has no use for the child end of the pair, it is kept open by the child process.
+	drop(child);
+	Ok((process, parent))
+}
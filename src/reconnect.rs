@@ -0,0 +1,209 @@
+//! Client socket that transparently reconnects when the connection is lost.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::UnixSeqpacket;
+
+/// Seqpacket client that reconnects to the server when the connection is lost.
+///
+/// When sending or receiving fails because the connection was reset or closed by the peer
+/// (`ECONNRESET`, `EPIPE` or `ENOTCONN`), the socket is reconnected and the operation is retried once on the new connection.
+/// On Linux and Android, receiving end-of-file from a peer that closed the connection also counts as a lost connection,
+/// see [`Self::recv()`].
+/// Connection attempts are retried with an exponential backoff, optionally randomized with jitter.
+///
+/// Note that messages that were queued on the old connection but not yet read by the peer are lost.
+/// Received messages are only ever read from the current connection.
+#[derive(Debug)]
+pub struct ReconnectingSeqpacket {
+	address: PathBuf,
+	socket: Option<UnixSeqpacket>,
+	initial_backoff: Duration,
+	max_backoff: Duration,
+	jitter: f64,
+	max_attempts: Option<usize>,
+	rng: u64,
+}
+
+impl ReconnectingSeqpacket {
+	/// The default delay before the first retry of a failed connection attempt.
+	pub const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+
+	/// The default maximum delay between connection attempts.
+	pub const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+	/// Create a new reconnecting socket for the given address without connecting yet.
+	///
+	/// The connection is established on first use, or by calling [`Self::reconnect()`].
+	pub fn new<P: AsRef<Path>>(address: P) -> Self {
+		let address = address.as_ref().to_path_buf();
+		let mut hasher = RandomState::new().build_hasher();
+		hasher.write(address.as_os_str().as_bytes());
+		Self {
+			address,
+			socket: None,
+			initial_backoff: Self::DEFAULT_INITIAL_BACKOFF,
+			max_backoff: Self::DEFAULT_MAX_BACKOFF,
+			jitter: 0.0,
+			max_attempts: None,
+			// Xorshift must not be seeded with zero.
+			rng: hasher.finish() | 1,
+		}
+	}
+
+	/// Create a new reconnecting socket and connect it to the given address.
+	///
+	/// The initial connection is attempted only once, so that configuration errors are reported immediately.
+	pub async fn connect<P: AsRef<Path>>(address: P) -> std::io::Result<Self> {
+		let mut socket = Self::new(address);
+		socket.socket = Some(UnixSeqpacket::connect(&socket.address).await?);
+		Ok(socket)
+	}
+
+	/// Set the delay before the first retry of a failed connection attempt.
+	///
+	/// The delay doubles after each failed attempt, up to the maximum set with [`Self::set_max_backoff()`].
+	pub fn set_initial_backoff(&mut self, backoff: Duration) {
+		self.initial_backoff = backoff;
+	}
+
+	/// Set the maximum delay between connection attempts.
+	pub fn set_max_backoff(&mut self, backoff: Duration) {
+		self.max_backoff = backoff;
+	}
+
+	/// Set the amount of random jitter applied to the backoff delay.
+	///
+	/// The value is clamped to the range `0.0..=1.0`.
+	/// A jitter of `0.25` randomly shortens each delay by up to 25%.
+	/// This prevents many clients from reconnecting at the same time when a server restarts.
+	pub fn set_jitter(&mut self, jitter: f64) {
+		self.jitter = jitter.clamp(0.0, 1.0);
+	}
+
+	/// Set the maximum number of connection attempts for a single reconnect.
+	///
+	/// If `None` (the default), connecting is retried forever.
+	pub fn set_max_attempts(&mut self, max_attempts: Option<usize>) {
+		self.max_attempts = max_attempts;
+	}
+
+	/// Get the address the socket connects to.
+	pub fn address(&self) -> &Path {
+		&self.address
+	}
+
+	/// Get the current connection, if the socket is connected.
+	pub fn get_ref(&self) -> Option<&UnixSeqpacket> {
+		self.socket.as_ref()
+	}
+
+	/// Check if the socket currently has a connection.
+	///
+	/// This does not detect if the peer closed the connection since the last operation.
+	pub fn is_connected(&self) -> bool {
+		self.socket.is_some()
+	}
+
+	/// Close the current connection, if any.
+	///
+	/// The next operation will establish a new connection.
+	pub fn disconnect(&mut self) {
+		self.socket = None;
+	}
+
+	/// Close the current connection and establish a new one.
+	///
+	/// Failed connection attempts are retried with exponential backoff,
+	/// until the maximum number of attempts is reached.
+	pub async fn reconnect(&mut self) -> std::io::Result<&UnixSeqpacket> {
+		self.socket = None;
+		let mut backoff = self.initial_backoff;
+		let mut attempts = 0;
+		let socket = loop {
+			attempts += 1;
+			match UnixSeqpacket::connect(&self.address).await {
+				Ok(socket) => break socket,
+				Err(e) => {
+					if self.max_attempts.is_some_and(|max| attempts >= max) {
+						return Err(e);
+					}
+				},
+			}
+			tokio::time::sleep(self.apply_jitter(backoff)).await;
+			backoff = backoff.saturating_mul(2).min(self.max_backoff);
+		};
+		Ok(self.socket.insert(socket))
+	}
+
+	/// Get the current connection, or establish a new one if the socket is not connected.
+	pub async fn socket(&mut self) -> std::io::Result<&UnixSeqpacket> {
+		match self.socket {
+			Some(ref socket) => Ok(socket),
+			None => self.reconnect().await,
+		}
+	}
+
+	/// Send data to the peer, reconnecting if the connection was lost.
+	pub async fn send(&mut self, buffer: &[u8]) -> std::io::Result<usize> {
+		match self.socket().await?.send(buffer).await {
+			Err(e) if is_disconnected(&e) => self.reconnect().await?.send(buffer).await,
+			x => x,
+		}
+	}
+
+	/// Receive data from the peer, reconnecting if the connection was lost.
+	///
+	/// A closed connection is reported by the socket as a message of zero bytes, just like an empty message.
+	/// On Linux and Android, receiving zero bytes triggers a reconnect if the peer shut down the connection.
+	/// Otherwise, the zero bytes are returned as an empty message.
+	///
+	/// Note that an empty message sent by the peer right before closing the connection can not be distinguished from the end-of-file.
+	pub async fn recv(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+		let socket = self.socket().await?;
+		match socket.recv(buffer).await {
+			Err(e) if is_disconnected(&e) => self.reconnect().await?.recv(buffer).await,
+			Ok(0) if !buffer.is_empty() && is_closed_by_peer(socket) => self.reconnect().await?.recv(buffer).await,
+			x => x,
+		}
+	}
+
+	/// Randomly shorten a delay according to the configured jitter.
+	fn apply_jitter(&mut self, delay: Duration) -> Duration {
+		if self.jitter == 0.0 {
+			return delay;
+		}
+		// Xorshift64: good enough to spread out reconnecting clients.
+		self.rng ^= self.rng << 13;
+		self.rng ^= self.rng >> 7;
+		self.rng ^= self.rng << 17;
+		let random = (self.rng >> 11) as f64 / (1u64 << 53) as f64;
+		delay.mul_f64(1.0 - self.jitter * random)
+	}
+}
+
+/// Check if the peer closed the connection, after receiving zero bytes from it.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn is_closed_by_peer(socket: &UnixSeqpacket) -> bool {
+	matches!(crate::shutdown_state(socket.as_raw_fd()), Ok(Some(_)))
+}
+
+/// Check if the peer closed the connection, after receiving zero bytes from it.
+///
+/// The shutdown state can not be queried on this platform, so zero bytes are treated as an empty message.
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+fn is_closed_by_peer(_socket: &UnixSeqpacket) -> bool {
+	false
+}
+
+/// Check if an error indicates that the connection was lost.
+fn is_disconnected(error: &std::io::Error) -> bool {
+	matches!(
+		error.raw_os_error(),
+		Some(libc::ECONNRESET) | Some(libc::EPIPE) | Some(libc::ENOTCONN)
+	)
+}
@@ -1,52 +1,335 @@
 use futures::future::poll_fn;
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
 use std::io::{IoSlice, IoSliceMut};
-use std::os::unix::io::{AsRawFd, IntoRawFd};
+use std::mem::MaybeUninit;
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd};
 use std::path::Path;
 use std::task::{Context, Poll};
 use tokio::io::unix::AsyncFd;
+use tokio::io::{Interest, Ready};
 
 use crate::ancillary::SocketAncillary;
-use crate::UCred;
+use crate::limit::ConnectionPermit;
+use crate::stats::StatsTracker;
+use crate::{MessageTooLargeError, SendAllError, SocketStats, UCred, UnixSeqpacketBuilder};
 
 /// Unix seqpacket socket.
 pub struct UnixSeqpacket {
 	io: AsyncFd<socket2::Socket>,
+	permit: Option<ConnectionPermit>,
+	stats: Option<Box<StatsTracker>>,
+}
+
+/// Information about a received message.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RecvMeta {
+	size: usize,
+	received: usize,
+	flags: std::os::raw::c_int,
+}
+
+impl RecvMeta {
+	/// Create the metadata from the full message size, the buffer capacity and the `msg_flags` from `recvmsg`.
+	fn new(size: usize, capacity: usize, flags: std::os::raw::c_int) -> Self {
+		Self {
+			size,
+			received: size.min(capacity),
+			flags,
+		}
+	}
+
+	/// Get the original size of the message in bytes.
+	///
+	/// If the message was truncated, this is larger than the number of received bytes.
+	pub fn size(&self) -> usize {
+		self.size
+	}
+
+	/// Get the number of bytes written to the buffer.
+	pub fn received(&self) -> usize {
+		self.received
+	}
+
+	/// Check if the message was truncated because the buffer was too small (`MSG_TRUNC`).
+	pub fn truncated(&self) -> bool {
+		self.flags & libc::MSG_TRUNC != 0
+	}
+
+	/// Check if ancillary data was discarded because the ancillary buffer was too small (`MSG_CTRUNC`).
+	///
+	/// Use [`SocketAncillary::truncation()`] to find out why the ancillary data was truncated.
+	pub fn ancillary_truncated(&self) -> bool {
+		self.flags & libc::MSG_CTRUNC != 0
+	}
+
+	/// Check if the kernel reported the end of a record (`MSG_EOR`).
+	///
+	/// Every seqpacket message is a complete record, and Linux does not report `MSG_EOR` for unix sockets.
+	/// This is mainly useful for portable code that inspects record marks on other platforms.
+	pub fn end_of_record(&self) -> bool {
+		self.flags & libc::MSG_EOR != 0
+	}
+
+	/// Get the raw `msg_flags` reported by the kernel.
+	pub fn flags(&self) -> std::os::raw::c_int {
+		self.flags
+	}
+}
+
+/// A buffer for receiving a single message with [`UnixSeqpacket::recv_batch()`].
+#[derive(Debug)]
+pub struct RecvSlot<'a> {
+	buffer: &'a mut [u8],
+	received: usize,
+	truncated: bool,
+}
+
+impl<'a> RecvSlot<'a> {
+	/// Create a new slot that receives a message into the given buffer.
+	pub fn new(buffer: &'a mut [u8]) -> Self {
+		Self {
+			buffer,
+			received: 0,
+			truncated: false,
+		}
+	}
+
+	/// Get the number of bytes received into the buffer.
+	pub fn received(&self) -> usize {
+		self.received
+	}
+
+	/// Check if the message was truncated because the buffer was too small.
+	pub fn truncated(&self) -> bool {
+		self.truncated
+	}
+
+	/// Get the received message.
+	pub fn data(&self) -> &[u8] {
+		&self.buffer[..self.received]
+	}
 }
 
 impl std::fmt::Debug for UnixSeqpacket {
 	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-		f.debug_struct("UnixSeqpacket")
+		let peer_addr = self.peer_addr().ok();
+		let mut debug = f.debug_struct("UnixSeqpacket");
+		debug
 			.field("fd", &self.io.get_ref().as_raw_fd())
-			.finish()
+			.field("local_addr", &self.local_addr().ok())
+			.field("connected", &peer_addr.is_some())
+			.field("peer_addr", &peer_addr);
+		#[cfg(any(target_os = "android", target_os = "linux"))]
+		if let Ok(shutdown) = crate::shutdown_state(self.as_raw_fd()) {
+			debug.field("shutdown", &shutdown);
+		}
+		debug.finish()
 	}
 }
 
 impl UnixSeqpacket {
+	/// The maximum number of chunks in a buffer passed to [`Self::send_buf()`].
+	#[cfg(feature = "bytes")]
+	pub const MAX_SEND_BUF_CHUNKS: usize = 64;
+
 	pub(crate) fn new(socket: socket2::Socket) -> std::io::Result<Self> {
 		let io = AsyncFd::new(socket)?;
-		Ok(Self { io })
+		Ok(Self {
+			io,
+			permit: None,
+			stats: None,
+		})
+	}
+
+	/// Attach a connection permit to the socket.
+	///
+	/// The permit is released when the socket is dropped.
+	pub(crate) fn with_permit(mut self, permit: Option<ConnectionPermit>) -> Self {
+		self.permit = permit;
+		self
+	}
+
+	/// Update the statistics after sending a message of `size` bytes.
+	fn record_sent(&self, size: usize) {
+		if let Some(stats) = &self.stats {
+			stats.record_sent(size);
+		}
+	}
+
+	/// Update the statistics after receiving a message of which `size` bytes were written to the buffer.
+	fn record_received(&self, size: usize, msg_flags: std::os::raw::c_int) {
+		if let Some(stats) = &self.stats {
+			stats.record_received(size, msg_flags & libc::MSG_TRUNC != 0);
+		}
 	}
 
 	/// Connect a new seqpacket socket to the given address.
 	pub async fn connect<P: AsRef<Path>>(address: P) -> std::io::Result<Self> {
-		let address = socket2::SockAddr::unix(address)?;
-		let socket = socket2::Socket::new(socket2::Domain::unix(), crate::socket_type(), None)?;
-		#[allow(clippy::single_match)]
-		match socket.connect(&address) {
-			Err(e) => {
-				if e.kind() != std::io::ErrorKind::WouldBlock {
-					return Err(e);
-				}
-			},
-			_ => (),
-		};
+		let address = crate::path_sockaddr(address)?;
+		Self::connect_sockaddr(&address).await
+	}
+
+	/// Connect a new seqpacket socket to the given socket address.
+	///
+	/// The address can be a filesystem path or (on Linux and Android) a name in the abstract namespace.
+	/// This can be used with addresses obtained from [`UnixSeqpacketListener::local_addr()`](crate::UnixSeqpacketListener::local_addr).
+	///
+	/// It is not possible to connect to an unnamed address.
+	pub async fn connect_addr(address: &std::os::unix::net::SocketAddr) -> std::io::Result<Self> {
+		if address.is_unnamed() {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::InvalidInput,
+				"can not connect to an unnamed socket address",
+			));
+		}
+		let address = crate::unix_as_sockaddr(address)?;
+		Self::connect_sockaddr(&address).await
+	}
+
+	/// Connect a new seqpacket socket to the given name in the abstract namespace.
+	///
+	/// The name should not include the leading null byte,
+	/// it is added automatically.
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub async fn connect_abstract(name: &[u8]) -> std::io::Result<Self> {
+		let address = crate::abstract_sockaddr(name)?;
+		Self::connect_sockaddr(&address).await
+	}
+
+	/// Connect a new seqpacket socket to the given address, with a timeout.
+	///
+	/// If the connection is not established before the timeout expires,
+	/// the socket is closed and an error of kind [`std::io::ErrorKind::TimedOut`] is returned.
+	///
+	/// If the connection backlog of the listener is full, the connection attempt is retried until the timeout expires.
+	pub async fn connect_timeout<P: AsRef<Path>>(address: P, timeout: std::time::Duration) -> std::io::Result<Self> {
+		let address = crate::path_sockaddr(address)?;
+		Self::connect_sockaddr_timeout(&address, Some(timeout)).await
+	}
+
+	/// Connect a new seqpacket socket to the given address and verify the credentials of the peer.
+	///
+	/// After the connection is established, the credentials of the listening process are passed to `verify`.
+	/// If it returns `false`, the socket is closed and an error of kind [`std::io::ErrorKind::PermissionDenied`] is returned.
+	///
+	/// This can be used to make sure that a socket was created by the expected user,
+	/// and not by an impostor that created a socket at the same path.
+	///
+	/// # Example
+	/// ```no_run
+	/// # async fn foo() -> std::io::Result<()> {
+	/// use tokio_seqpacket::UnixSeqpacket;
+	///
+	/// // Only talk to a server running as root.
+	/// let socket = UnixSeqpacket::connect_verified("/run/foo.sock", |cred| cred.uid() == 0).await?;
+	/// # Ok(())
+	/// # }
+	/// ```
+	pub async fn connect_verified<P, F>(address: P, verify: F) -> std::io::Result<Self>
+	where
+		P: AsRef<Path>,
+		F: FnOnce(&UCred) -> bool,
+	{
+		let socket = Self::connect(address).await?;
+		let cred = socket.peer_cred()?;
+		if !verify(&cred) {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::PermissionDenied,
+				format!("peer credentials rejected: {:?}", cred),
+			));
+		}
+		Ok(socket)
+	}
+
+	/// Connect a new seqpacket socket to the given socket address.
+	async fn connect_sockaddr(address: &socket2::SockAddr) -> std::io::Result<Self> {
+		Self::connect_sockaddr_timeout(address, None).await
+	}
+
+	/// Connect a new seqpacket socket to the given socket address, with an optional timeout.
+	async fn connect_sockaddr_timeout(
+		address: &socket2::SockAddr,
+		timeout: Option<std::time::Duration>,
+	) -> std::io::Result<Self> {
+		Self::connect_socket(crate::new_socket()?, address, timeout).await
+	}
+
+	/// Connect a socket to the given socket address, with an optional timeout.
+	///
+	/// Without a timeout, a full connection backlog is reported as an error of kind [`std::io::ErrorKind::WouldBlock`].
+	/// With a timeout, the connection attempt is retried until the timeout expires.
+	#[cfg_attr(
+		feature = "tracing",
+		tracing::instrument(level = "debug", name = "connect", skip_all, fields(address = ?crate::sockaddr_as_unix(address)))
+	)]
+	pub(crate) async fn connect_socket(
+		socket: socket2::Socket,
+		address: &socket2::SockAddr,
+		timeout: Option<std::time::Duration>,
+	) -> std::io::Result<Self> {
+		/// Delay between connection attempts when the backlog of the listener is full.
+		const RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(10);
+
+		let deadline = timeout.map(|timeout| tokio::time::Instant::now() + timeout);
+		let timed_out = || std::io::Error::new(std::io::ErrorKind::TimedOut, "connection timed out");
+
+		loop {
+			match start_connect(&socket, address) {
+				Ok(()) => break,
+				Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => match deadline {
+					None => return Err(e),
+					Some(deadline) if tokio::time::Instant::now() + RETRY_DELAY > deadline => return Err(timed_out()),
+					Some(_) => tokio::time::sleep(RETRY_DELAY).await,
+				},
+				Err(e) => return Err(e),
+			}
+		}
 
 		let socket = Self::new(socket)?;
-		socket.io.writable().await?.retain_ready();
+		match deadline {
+			None => poll_fn(|cx| socket.poll_connect(cx)).await?,
+			Some(deadline) => match tokio::time::timeout_at(deadline, poll_fn(|cx| socket.poll_connect(cx))).await {
+				Ok(result) => result?,
+				Err(_) => return Err(timed_out()),
+			},
+		}
+		trace_event!(debug, fd = socket.as_raw_fd(), "connected");
 		Ok(socket)
 	}
 
+	/// Start connecting a new seqpacket socket to the given address, without waiting for the connection to complete.
+	///
+	/// The returned socket may still be connecting.
+	/// Use [`Self::poll_connect()`] to wait for the connection to be established and to check for errors.
+	///
+	/// If the connection backlog of the listener is full,
+	/// an error of kind [`std::io::ErrorKind::WouldBlock`] is returned.
+	pub fn start_connect<P: AsRef<Path>>(address: P) -> std::io::Result<Self> {
+		let address = crate::path_sockaddr(address)?;
+		Self::start_connect_sockaddr(&address)
+	}
+
+	/// Start connecting a new seqpacket socket to the given socket address.
+	fn start_connect_sockaddr(address: &socket2::SockAddr) -> std::io::Result<Self> {
+		let socket = crate::new_socket()?;
+		start_connect(&socket, address)?;
+		Self::new(socket)
+	}
+
+	/// Check if a connection started with [`Self::start_connect()`] has been established.
+	///
+	/// This waits for the socket to become writable and then checks the `SO_ERROR` option.
+	/// If the connection failed, the error is returned.
+	///
+	/// If the connection is not established yet, the current task is scheduled to wake up when the socket becomes writeable.
+	pub fn poll_connect(&self, cx: &mut Context) -> Poll<std::io::Result<()>> {
+		ready!(self.io.poll_write_ready(cx)?).retain_ready();
+		match self.take_error()? {
+			Some(e) => Poll::Ready(Err(e)),
+			None => Poll::Ready(Ok(())),
+		}
+	}
+
 	/// Create a pair of connected seqpacket sockets.
 	pub fn pair() -> std::io::Result<(Self, Self)> {
 		let (a, b) = socket2::Socket::pair(socket2::Domain::unix(), crate::socket_type(), None)?;
@@ -55,138 +338,1222 @@ impl UnixSeqpacket {
 		Ok((a, b))
 	}
 
+	/// Create a pair of connected seqpacket sockets with the options from a builder.
+	///
+	/// The options are applied to both sockets before they are returned.
+	/// Options that only apply to listeners are ignored.
+	pub fn pair_with_options(options: &UnixSeqpacketBuilder) -> std::io::Result<(Self, Self)> {
+		options.pair()
+	}
+
 	/// Wrap a raw file descriptor as [`UnixSeqpacket`].
 	///
 	/// Registration of the file descriptor with the tokio runtime may fail.
 	/// For that reason, this function returns a [`std::io::Result`].
 	///
+	/// The file descriptor is not checked and must already be in non-blocking mode.
+	/// Prefer converting from an [`OwnedFd`] with [`TryFrom`], which validates the socket type.
+	///
 	/// # Safety
 	/// This function is unsafe because the socket assumes it is the sole owner of the file descriptor.
 	/// Usage of this function could accidentally allow violating this contract
 	/// which can cause memory unsafety in code that relies on it being true.
 	pub unsafe fn from_raw_fd(fd: std::os::unix::io::RawFd) -> std::io::Result<Self> {
-		use std::os::unix::io::FromRawFd;
 		Self::new(socket2::Socket::from_raw_fd(fd))
 	}
 
+	/// Register a blocking seqpacket socket with the tokio runtime.
+	///
+	/// The socket is put in non-blocking mode.
+	pub fn from_std(socket: crate::blocking::UnixSeqpacket) -> std::io::Result<Self> {
+		let socket = socket.into_socket();
+		socket.set_nonblocking(true)?;
+		Self::new(socket)
+	}
+
+	/// Deregister the socket from the tokio runtime and convert it into a blocking socket.
+	///
+	/// The socket is put in blocking mode.
+	/// If the socket was accepted from a listener with a connection limit,
+	/// it no longer counts against the limit after the conversion.
+	pub fn into_std(self) -> std::io::Result<crate::blocking::UnixSeqpacket> {
+		let socket = self.io.into_inner();
+		socket.set_nonblocking(false)?;
+		Ok(crate::blocking::UnixSeqpacket::new(socket))
+	}
+
+	/// Take ownership of a connected seqpacket socket and register it with the tokio runtime.
+	///
+	/// This is meant for adopting file descriptors from elsewhere,
+	/// such as descriptors received over a socket or inherited from a parent process.
+	/// It verifies that the file descriptor is a unix seqpacket socket that is connected to a peer,
+	/// and returns an error describing the problem otherwise.
+	///
+	/// The file descriptor is put in non-blocking mode.
+	/// If anything fails, the file descriptor is closed.
+	pub fn try_from_fd(fd: OwnedFd) -> std::io::Result<Self> {
+		crate::check_socket_type(fd.as_raw_fd(), libc::SOCK_SEQPACKET)?;
+		if !crate::is_connected(fd.as_raw_fd())? {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::NotConnected,
+				format!("file descriptor {} is not a connected socket", fd.as_raw_fd()),
+			));
+		}
+		Self::try_from(fd)
+	}
+
+	/// Take ownership of a connected seqpacket socket received from a peer and register it with the tokio runtime.
+	///
+	/// This accepts a [`ReceivedFd`](crate::ancillary::ReceivedFd) as returned by [`Self::recv_fds()`] or [`Self::recv_with_fd()`],
+	/// or any other [`OwnedFd`].
+	/// It performs the same checks as [`Self::try_from_fd()`].
+	pub fn from_received_fd(fd: impl Into<OwnedFd>) -> std::io::Result<Self> {
+		Self::try_from_fd(fd.into())
+	}
+
+	/// Create a new independently owned handle to the same socket.
+	///
+	/// The file descriptor is duplicated and the new descriptor is registered with the tokio runtime.
+	/// Both handles refer to the same connection, so messages can be sent and received through either of them.
+	/// The connection is only closed when all handles have been dropped or shut down.
+	///
+	/// If the socket was accepted from a listener with a connection limit,
+	/// the connection counts against the limit until the original handle is dropped.
+	pub fn try_clone(&self) -> std::io::Result<Self> {
+		Self::new(self.io.get_ref().try_clone()?)
+	}
+
 	/// Get the raw file descriptor of the socket.
 	pub fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
 		self.io.as_raw_fd()
 	}
 
-	/// Deregister the socket from the tokio runtime and return the inner file descriptor.
-	pub fn into_raw_fd(self) -> std::os::unix::io::RawFd {
-		self.io.into_inner().into_raw_fd()
+	/// Deregister the socket from the tokio runtime and return the inner file descriptor.
+	pub fn into_raw_fd(self) -> std::os::unix::io::RawFd {
+		self.io.into_inner().into_raw_fd()
+	}
+
+	#[doc(hidden)]
+	#[deprecated(
+		since = "0.4.0",
+		note = "all I/O functions now take a shared reference to self, so splitting is no longer necessary"
+	)]
+	pub fn split(&self) -> (&Self, &Self) {
+		(self, self)
+	}
+
+	/// Get the socket address of the local half of this connection.
+	pub fn local_addr(&self) -> std::io::Result<std::os::unix::net::SocketAddr> {
+		let addr = self.io.get_ref().local_addr()?;
+		Ok(crate::sockaddr_as_unix(&addr).unwrap())
+	}
+
+	/// Get the socket address of the remote half of this connection.
+	pub fn peer_addr(&self) -> std::io::Result<std::os::unix::net::SocketAddr> {
+		let addr = self.io.get_ref().peer_addr()?;
+		Ok(crate::sockaddr_as_unix(&addr).unwrap())
+	}
+
+	/// Get the effective credentials of the process which called `connect` or `pair`.
+	///
+	/// Note that this is not necessarily the process that currently has the file descriptor
+	/// of the other side of the connection.
+	pub fn peer_cred(&self) -> std::io::Result<UCred> {
+		UCred::from_socket_peer(&self.io)
+	}
+
+	/// Get the security label of the process which called `connect` or `pair` (`SO_PEERSEC`).
+	///
+	/// This is the label assigned by the active Linux Security Module, such as an SELinux context or an AppArmor profile.
+	/// Like [`Self::peer_cred()`], it is recorded when the connection is established.
+	/// Use [`Self::set_passsec()`] to receive the label of the sender with every message instead.
+	///
+	/// If no security module provides a label, an error with `ENOPROTOOPT` is returned.
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub fn peer_security_context(&self) -> std::io::Result<std::ffi::OsString> {
+		use std::os::unix::ffi::OsStringExt;
+
+		let mut label = crate::get_socket_option_bytes(self.as_raw_fd(), libc::SOL_SOCKET, crate::SO_PEERSEC)?;
+		while label.last() == Some(&0) {
+			label.pop();
+		}
+		Ok(std::ffi::OsString::from_vec(label))
+	}
+
+	/// Get the supplementary groups of the process which called `connect` or `pair` (`SO_PEERGROUPS`).
+	///
+	/// Like [`Self::peer_cred()`], the groups are recorded when the connection is established.
+	/// This requires Linux 4.13 or newer, older kernels report `ENOPROTOOPT`.
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub fn peer_groups(&self) -> std::io::Result<Vec<libc::gid_t>> {
+		let groups = crate::get_socket_option_bytes(self.as_raw_fd(), libc::SOL_SOCKET, crate::SO_PEERGROUPS)?;
+		let groups = groups
+			.chunks_exact(std::mem::size_of::<libc::gid_t>())
+			.map(|gid| libc::gid_t::from_ne_bytes(gid.try_into().unwrap()))
+			.collect();
+		Ok(groups)
+	}
+
+	/// Get a pidfd for the process which called `connect` or `pair` (`SO_PEERPIDFD`).
+	///
+	/// Unlike the process ID from [`Self::peer_cred()`], a pidfd keeps referring to the same process,
+	/// even if it exits and the process ID is reused.
+	/// It can be used to send signals to the peer or to wait for it to exit.
+	///
+	/// This requires Linux 6.5 or newer, older kernels report `ENOPROTOOPT`.
+	/// The returned file descriptor has the close-on-exec flag set.
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub fn peer_pidfd(&self) -> std::io::Result<OwnedFd> {
+		let fd = crate::get_socket_option_int(self.as_raw_fd(), libc::SOL_SOCKET, crate::SO_PEERPIDFD)?;
+		Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+	}
+
+	/// Get the unique cookie of the socket (`SO_COOKIE`).
+	///
+	/// The kernel assigns every socket a 64-bit cookie that is never reused while the system is running.
+	/// It identifies the socket across processes,
+	/// and can be used to correlate connections with eBPF programs, `ss` output or audit logs.
+	///
+	/// This requires Linux 4.13 or newer, older kernels report `ENOPROTOOPT`.
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub fn cookie(&self) -> std::io::Result<u64> {
+		let value = crate::get_socket_option_bytes(self.as_raw_fd(), libc::SOL_SOCKET, crate::SO_COOKIE)?;
+		let value = value.try_into().map_err(|_| {
+			std::io::Error::new(std::io::ErrorKind::InvalidData, "unexpected size of socket cookie")
+		})?;
+		Ok(u64::from_ne_bytes(value))
+	}
+
+	/// Get information about the process which called `connect` or `pair` from `/proc`.
+	///
+	/// The process ID is taken from [`Self::peer_cred()`].
+	/// If the kernel supports [`Self::peer_pidfd()`], the pidfd is used to verify that the process did not exit
+	/// and that the process ID was not reused by another process while the information was read.
+	/// On older kernels, this can not be detected.
+	///
+	/// If the peer process has exited, an error of kind [`std::io::ErrorKind::NotFound`] is returned.
+	#[cfg(any(target_os = "android", target_os = "linux"))]
+	pub fn peer_process(&self) -> std::io::Result<crate::PeerProcess> {
+		let pidfd = match self.peer_pidfd() {
+			Ok(pidfd) => Some(pidfd),
+			Err(e) if e.raw_os_error() == Some(libc::ENOPROTOOPT) => None,
+			Err(e) => return Err(e),
+		};
+		let pid = self.peer_cred()?.pid().ok_or_else(|| {
+			std::io::Error::new(std::io::ErrorKind::NotFound, "the process ID of the peer is not known")
+		})?;
+		crate::PeerProcess::read(pid, pidfd.as_ref())
+	}
+
+	/// Get the effective credentials and the supplementary groups of the process which called `connect` or `pair`.
+	///
+	/// The groups are retrieved with [`Self::peer_groups()`].
+	/// On kernels older than Linux 4.13, the groups are read from `/proc` instead.
+	/// In that case they reflect the current groups of the process with the process ID from [`Self::peer_cred()`],
+	/// rather than the groups at the time the connection was established.
+	#[cfg(any(target_os = "android", target_os = "linux"))]
+	pub fn peer_cred_full(&self) -> std::io::Result<crate::UCredWithGroups> {
+		let cred = self.peer_cred()?;
+		let groups = match self.peer_groups() {
+			Ok(groups) => groups,
+			Err(e) if e.raw_os_error() == Some(libc::ENOPROTOOPT) => match cred.pid() {
+				Some(pid) => crate::ucred::read_proc_groups(pid)?,
+				None => return Err(e),
+			},
+			Err(e) => return Err(e),
+		};
+		Ok(crate::UCredWithGroups::new(cred, groups))
+	}
+
+	/// Get the size of the send buffer (`SO_SNDBUF`).
+	///
+	/// A message larger than the send buffer can not be sent and results in an `EMSGSIZE` error.
+	pub fn send_buffer_size(&self) -> std::io::Result<usize> {
+		self.io.get_ref().send_buffer_size()
+	}
+
+	/// Set the size of the send buffer (`SO_SNDBUF`).
+	///
+	/// The kernel may adjust the value: Linux doubles it to leave room for bookkeeping and enforces a minimum and maximum.
+	/// Use [`Self::send_buffer_size()`] to get the actual size.
+	pub fn set_send_buffer_size(&self, size: usize) -> std::io::Result<()> {
+		self.io.get_ref().set_send_buffer_size(size)
+	}
+
+	/// Get the size of the receive buffer (`SO_RCVBUF`).
+	pub fn recv_buffer_size(&self) -> std::io::Result<usize> {
+		self.io.get_ref().recv_buffer_size()
+	}
+
+	/// Set the size of the receive buffer (`SO_RCVBUF`).
+	///
+	/// The kernel may adjust the value: Linux doubles it to leave room for bookkeeping and enforces a minimum and maximum.
+	/// Use [`Self::recv_buffer_size()`] to get the actual size.
+	pub fn set_recv_buffer_size(&self, size: usize) -> std::io::Result<()> {
+		self.io.get_ref().set_recv_buffer_size(size)
+	}
+
+	/// Get the number of bytes in the receive queue that have not been received yet (`FIONREAD`).
+	///
+	/// This is the total size of all queued messages, not just the next one.
+	/// Use [`Self::next_message_size()`] to get the size of the next message.
+	pub fn pending_recv_bytes(&self) -> std::io::Result<usize> {
+		Ok(crate::ioctl_int(self.as_raw_fd(), libc::FIONREAD as _)? as usize)
+	}
+
+	/// Get the number of bytes in the send queue that have not been received by the peer yet (`SIOCOUTQ`).
+	///
+	/// The value includes the bookkeeping overhead of the kernel for each queued message,
+	/// so it is larger than the total size of the queued data.
+	/// It drops to zero once the peer has received all messages.
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub fn pending_send_bytes(&self) -> std::io::Result<usize> {
+		Ok(crate::ioctl_int(self.as_raw_fd(), libc::TIOCOUTQ as _)? as usize)
+	}
+
+	/// Get the mark of the socket (`SO_MARK`).
+	#[cfg(any(doc, target_os = "linux"))]
+	pub fn mark(&self) -> std::io::Result<u32> {
+		self.io.get_ref().mark()
+	}
+
+	/// Set the mark of the socket (`SO_MARK`).
+	///
+	/// Setting the mark requires the `CAP_NET_ADMIN` capability.
+	/// Use [`UnixSeqpacketBuilder::mark()`] to set the mark before the socket is connected.
+	#[cfg(any(doc, target_os = "linux"))]
+	pub fn set_mark(&self, mark: u32) -> std::io::Result<()> {
+		self.io.get_ref().set_mark(mark)
+	}
+
+	/// Get the priority of the socket (`SO_PRIORITY`).
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub fn priority(&self) -> std::io::Result<u32> {
+		Ok(crate::get_socket_option_int(self.as_raw_fd(), libc::SOL_SOCKET, libc::SO_PRIORITY)? as u32)
+	}
+
+	/// Set the priority of the socket (`SO_PRIORITY`).
+	///
+	/// Priorities from 0 to 6 can be set by any process,
+	/// higher priorities require the `CAP_NET_ADMIN` capability.
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub fn set_priority(&self, priority: u32) -> std::io::Result<()> {
+		crate::set_socket_option_int(self.as_raw_fd(), libc::SOL_SOCKET, libc::SO_PRIORITY, priority as _)
+	}
+
+	/// Get the busy poll timeout of the socket in microseconds (`SO_BUSY_POLL`).
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub fn busy_poll(&self) -> std::io::Result<u32> {
+		Ok(crate::get_socket_option_int(self.as_raw_fd(), libc::SOL_SOCKET, libc::SO_BUSY_POLL)? as u32)
+	}
+
+	/// Set the busy poll timeout of the socket in microseconds (`SO_BUSY_POLL`).
+	///
+	/// Increasing the timeout requires the `CAP_NET_ADMIN` capability.
+	/// Kernels built without busy polling support report `ENOPROTOOPT`.
+	/// Use [`UnixSeqpacketBuilder::busy_poll()`] to set the timeout before the socket is connected.
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub fn set_busy_poll(&self, microseconds: u32) -> std::io::Result<()> {
+		crate::set_socket_option_int(self.as_raw_fd(), libc::SOL_SOCKET, libc::SO_BUSY_POLL, microseconds as _)
+	}
+
+	/// Enable or disable receiving of `SCM_CREDENTIALS` messages (`SO_PASSCRED`).
+	///
+	/// When enabled, the kernel attaches the credentials of the sender to every received message.
+	/// Messages that were already queued before the option was enabled do not carry credentials.
+	/// Use [`UnixSeqpacketBuilder::passcred()`] to enable the option before the socket is connected.
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub fn set_passcred(&self, passcred: bool) -> std::io::Result<()> {
+		crate::set_socket_option_int(self.as_raw_fd(), libc::SOL_SOCKET, libc::SO_PASSCRED, passcred as _)
+	}
+
+	/// Check if receiving of `SCM_CREDENTIALS` messages is enabled (`SO_PASSCRED`).
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub fn passcred(&self) -> std::io::Result<bool> {
+		Ok(crate::get_socket_option_int(self.as_raw_fd(), libc::SOL_SOCKET, libc::SO_PASSCRED)? != 0)
+	}
+
+	/// Enable or disable receiving of `SCM_SECURITY` messages (`SO_PASSSEC`).
+	///
+	/// When enabled, the kernel attaches the security label of the sender to every received message,
+	/// if a Linux Security Module like SELinux or AppArmor is active.
+	/// The label can be read with [`AncillaryData::ScmSecurity`](crate::ancillary::AncillaryData::ScmSecurity).
+	/// Use [`UnixSeqpacketBuilder::passsec()`] to enable the option before the socket is connected.
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub fn set_passsec(&self, passsec: bool) -> std::io::Result<()> {
+		crate::set_socket_option_int(self.as_raw_fd(), libc::SOL_SOCKET, crate::SO_PASSSEC, passsec as _)
+	}
+
+	/// Check if receiving of `SCM_SECURITY` messages is enabled (`SO_PASSSEC`).
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub fn passsec(&self) -> std::io::Result<bool> {
+		Ok(crate::get_socket_option_int(self.as_raw_fd(), libc::SOL_SOCKET, crate::SO_PASSSEC)? != 0)
+	}
+
+	/// Borrow the socket as a [`socket2::SockRef`](socket2_06::SockRef) to access socket options that are not wrapped by this crate.
+	///
+	/// This function is only available when the `socket2-06` feature is enabled.
+	#[cfg(feature = "socket2-06")]
+	pub fn socket_ref(&self) -> socket2_06::SockRef<'_> {
+		socket2_06::SockRef::from(self)
+	}
+
+	/// Get the value of the `SO_ERROR` option.
+	pub fn take_error(&self) -> std::io::Result<Option<std::io::Error>> {
+		self.io.get_ref().take_error()
+	}
+
+	/// Try to send data on the socket to the connected peer without blocking.
+	///
+	/// If the socket is not ready yet, the current task is scheduled to wake up when the socket becomes writeable.
+	pub fn poll_send(&self, cx: &mut Context, buffer: &[u8]) -> Poll<std::io::Result<usize>> {
+		poll_send(self, cx, buffer)
+	}
+
+	/// Try to send data on the socket to the connected peer without blocking.
+	///
+	/// If the socket is not ready yet, the current task is scheduled to wake up when the socket becomes writeable.
+	pub fn poll_send_vectored(&self, cx: &mut Context, buffer: &[IoSlice]) -> Poll<std::io::Result<usize>> {
+		poll_send_vectored(self, cx, buffer)
+	}
+
+	/// Try to send data with ancillary data on the socket to the connected peer without blocking.
+	///
+	/// If the socket is not ready yet, the current task is scheduled to wake up when the socket becomes writeable.
+	pub fn poll_send_vectored_with_ancillary(
+		&self,
+		cx: &mut Context,
+		buffer: &[IoSlice],
+		ancillary: &mut SocketAncillary,
+	) -> Poll<std::io::Result<usize>> {
+		poll_send_vectored_with_ancillary(self, cx, buffer, ancillary)
+	}
+
+	/// Wait for any of the requested readiness states.
+	///
+	/// The returned [`Ready`] set reports which of the requested states are ready.
+	/// This allows a single task to handle both sending and receiving with one await.
+	///
+	/// The readiness event may be a false positive, so a subsequent [`Self::try_recv()`] or [`Self::try_send()`]
+	/// can still fail with [`std::io::ErrorKind::WouldBlock`].
+	///
+	/// # Example
+	/// ```no_run
+	/// # async fn foo(socket: tokio_seqpacket::UnixSeqpacket) -> std::io::Result<()> {
+	/// use tokio::io::Interest;
+	///
+	/// let ready = socket.ready(Interest::READABLE | Interest::WRITABLE).await?;
+	/// if ready.is_readable() {
+	///     let mut buffer = [0u8; 128];
+	///     match socket.try_recv(&mut buffer) {
+	///         Ok(len) => println!("received {} bytes", len),
+	///         Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => (),
+	///         Err(e) => return Err(e),
+	///     }
+	/// }
+	/// if ready.is_writable() {
+	///     match socket.try_send(b"Hello!") {
+	///         Ok(_) => println!("sent message"),
+	///         Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => (),
+	///         Err(e) => return Err(e),
+	///     }
+	/// }
+	/// # Ok(())
+	/// # }
+	/// ```
+	pub async fn ready(&self, interest: Interest) -> std::io::Result<Ready> {
+		let mut guard = self.io.ready(interest).await?;
+		let ready = guard.ready();
+		guard.retain_ready();
+		Ok(ready)
+	}
+
+	/// Wait for the socket to become readable.
+	///
+	/// This can be used together with [`Self::try_recv()`] to receive multiple messages after a single wake-up.
+	/// The readiness event may be a false positive, so a subsequent [`Self::try_recv()`] can still fail with [`std::io::ErrorKind::WouldBlock`].
+	pub async fn readable(&self) -> std::io::Result<()> {
+		self.io.readable().await?.retain_ready();
+		Ok(())
+	}
+
+	/// Wait for the socket to become writable.
+	///
+	/// This can be used together with [`Self::try_send()`] to send multiple messages after a single wake-up.
+	/// The readiness event may be a false positive, so a subsequent [`Self::try_send()`] can still fail with [`std::io::ErrorKind::WouldBlock`].
+	pub async fn writable(&self) -> std::io::Result<()> {
+		self.io.writable().await?.retain_ready();
+		Ok(())
+	}
+
+	/// Try to send data on the socket to the connected peer without waiting.
+	///
+	/// If the message can not be queued immediately, an error of kind [`std::io::ErrorKind::WouldBlock`] is returned.
+	/// Unlike [`Self::poll_send()`], this does not schedule the current task to be woken up.
+	pub fn try_send(&self, buffer: &[u8]) -> std::io::Result<usize> {
+		let size = self.try_nonblocking(Interest::WRITABLE, |socket| socket.send(buffer))?;
+		self.record_sent(size);
+		record_metric!(record_sent(self.as_raw_fd(), size, 0));
+		Ok(size)
+	}
+
+	/// Try to send data on the socket to the connected peer without blocking, with extra `MSG_*` flags.
+	///
+	/// The flags are combined with the flags that are always used (`MSG_NOSIGNAL`).
+	///
+	/// If the socket is not ready yet, the current task is scheduled to wake up when the socket becomes writeable.
+	pub fn poll_send_with_flags(
+		&self,
+		cx: &mut Context,
+		buffer: &[u8],
+		flags: std::os::raw::c_int,
+	) -> Poll<std::io::Result<usize>> {
+		poll_send_with_flags(self, cx, buffer, flags)
+	}
+
+	/// Send data on the socket to the connected peer, with extra `MSG_*` flags.
+	///
+	/// The flags are combined with the flags that are always used (`MSG_NOSIGNAL`).
+	///
+	/// For example, `MSG_EOR` can be used to mark the end of a record for peers that rely on record marks.
+	///
+	/// Note that Linux does not support zero-copy sends for unix sockets:
+	/// enabling `SO_ZEROCOPY` fails with `EOPNOTSUPP` and `MSG_ZEROCOPY` has no effect.
+	pub async fn send_with_flags(&self, buffer: &[u8], flags: std::os::raw::c_int) -> std::io::Result<usize> {
+		poll_fn(|cx| self.poll_send_with_flags(cx, buffer, flags)).await
+	}
+
+	/// Send the contents of a [`bytes::Buf`] as a single message.
+	///
+	/// The chunks of the buffer are sent with a single vectored send, without copying them into one contiguous buffer.
+	/// On success, the buffer is advanced by the number of bytes sent.
+	///
+	/// The buffer may consist of at most [`Self::MAX_SEND_BUF_CHUNKS`] chunks.
+	/// If it has more chunks, an error of kind [`std::io::ErrorKind::InvalidInput`] is returned and nothing is sent.
+	///
+	/// This function is only available when the `bytes` feature is enabled.
+	#[cfg(feature = "bytes")]
+	pub async fn send_buf<B: bytes::Buf>(&self, buffer: &mut B) -> std::io::Result<usize> {
+		let mut slices = [IoSlice::new(&[]); Self::MAX_SEND_BUF_CHUNKS];
+		let count = buffer.chunks_vectored(&mut slices);
+		let slices = &slices[..count];
+		let total: usize = slices.iter().map(|x| x.len()).sum();
+		if total != buffer.remaining() {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::InvalidInput,
+				format!("buffer consists of more than {} chunks", Self::MAX_SEND_BUF_CHUNKS),
+			));
+		}
+		let sent = self.send_vectored(slices).await?;
+		buffer.advance(sent);
+		Ok(sent)
+	}
+
+	/// Try to send multiple messages to the connected peer with a single system call, without blocking.
+	///
+	/// Each slice is sent as a separate message.
+	/// Returns the number of messages that were sent, which may be less than the number of slices.
+	/// The remaining messages should be sent with another call.
+	///
+	/// If the socket is not ready yet, the current task is scheduled to wake up when the socket becomes writeable.
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub fn poll_send_batch(&self, cx: &mut Context, messages: &[&[u8]]) -> Poll<std::io::Result<usize>> {
+		if messages.is_empty() {
+			return Poll::Ready(Ok(0));
+		}
+
+		let mut ready_guard = ready!(self.io.poll_write_ready(cx)?);
+		match send_mmsg(self.io.get_ref(), messages) {
+			Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+				ready_guard.clear_ready();
+				record_metric!(record_would_block(self.as_raw_fd()));
+				Poll::Pending
+			},
+			Ok(sent) => {
+				for message in &messages[..sent] {
+					self.record_sent(message.len());
+				}
+				Poll::Ready(Ok(sent))
+			},
+			x => Poll::Ready(x),
+		}
+	}
+
+	/// Send multiple messages to the connected peer with a single system call (`sendmmsg`).
+	///
+	/// Each slice is sent as a separate message.
+	/// Returns the number of messages that were sent, which may be less than the number of slices.
+	/// The remaining messages should be sent with another call.
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub async fn send_batch(&self, messages: &[&[u8]]) -> std::io::Result<usize> {
+		poll_fn(|cx| self.poll_send_batch(cx, messages)).await
+	}
+
+	/// Send all messages from an iterator to the connected peer.
+	///
+	/// Each item is sent as a separate message, waiting for the socket to become writable as needed.
+	/// Returns the number of messages that were sent.
+	///
+	/// If sending a message fails, the remaining messages are not sent.
+	/// The returned [`SendAllError`] holds the error and the number of messages that were sent before it.
+	///
+	/// # Example
+	/// ```no_run
+	/// # async fn foo(socket: tokio_seqpacket::UnixSeqpacket, queue: Vec<Vec<u8>>) -> std::io::Result<()> {
+	/// if let Err(e) = socket.send_all(&queue).await {
+	///     eprintln!("only {} of {} messages were sent: {}", e.sent(), queue.len(), e.error());
+	/// }
+	/// # Ok(())
+	/// # }
+	/// ```
+	pub async fn send_all<I>(&self, messages: I) -> Result<usize, SendAllError>
+	where
+		I: IntoIterator,
+		I::Item: AsRef<[u8]>,
+	{
+		let mut sent = 0;
+		for message in messages {
+			match self.send(message.as_ref()).await {
+				Ok(_) => sent += 1,
+				Err(e) => return Err(SendAllError::new(sent, e)),
+			}
+		}
+		Ok(sent)
+	}
+
+	/// Send data on the socket to the connected peer.
+	pub async fn send(&self, buffer: &[u8]) -> std::io::Result<usize> {
+		poll_fn(|cx| self.poll_send(cx, buffer)).await
+	}
+
+	/// Send data on the socket to the connected peer, with a timeout.
+	///
+	/// If the message can not be queued before the timeout expires,
+	/// an error of kind [`std::io::ErrorKind::TimedOut`] is returned and nothing is sent.
+	pub async fn send_timeout(&self, buffer: &[u8], timeout: std::time::Duration) -> std::io::Result<usize> {
+		self.send_deadline(buffer, tokio::time::Instant::now() + timeout).await
+	}
+
+	/// Send data on the socket to the connected peer, with a deadline.
+	///
+	/// If the message can not be queued before the deadline,
+	/// an error of kind [`std::io::ErrorKind::TimedOut`] is returned and nothing is sent.
+	///
+	/// This is useful when multiple operations share a total time budget.
+	pub async fn send_deadline(&self, buffer: &[u8], deadline: tokio::time::Instant) -> std::io::Result<usize> {
+		match tokio::time::timeout_at(deadline, self.send(buffer)).await {
+			Ok(result) => result,
+			Err(_) => Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "send timed out")),
+		}
+	}
+
+	/// Send data on the socket to the connected peer, unless the cancellation token fires first.
+	///
+	/// Returns `Ok(None)` if the token was cancelled before the message was queued.
+	/// In that case, nothing was sent.
+	///
+	/// This function is only available when the `tokio-util` feature is enabled.
+	#[cfg(feature = "tokio-util")]
+	pub async fn send_or_cancelled(
+		&self,
+		token: &tokio_util::sync::CancellationToken,
+		buffer: &[u8],
+	) -> std::io::Result<Option<usize>> {
+		token.run_until_cancelled(self.send(buffer)).await.transpose()
+	}
+
+	/// Send data with file descriptors on the socket to the connected peer.
+	///
+	/// The file descriptors are sent as a `SCM_RIGHTS` control message.
+	/// The ancillary buffer is allocated internally with the right size for the number of file descriptors.
+	///
+	/// The file descriptors remain open in the current process,
+	/// the peer receives new file descriptors that refer to the same open files.
+	pub async fn send_fds(&self, buffer: &[u8], fds: &[BorrowedFd<'_>]) -> std::io::Result<usize> {
+		let mut ancillary = crate::ancillary::AncillaryBuilder::new().with_fds(fds).build()?;
+		self.send_vectored_with_ancillary(&[IoSlice::new(buffer)], &mut ancillary).await
+	}
+
+	/// Send data with any number of file descriptors, split over multiple messages if needed.
+	///
+	/// The kernel limits the number of file descriptors in a single message,
+	/// so the file descriptors are sent in chunks of at most 253 per message.
+	/// Every message starts with a small header that tells the peer how many messages still follow.
+	/// The data is sent with the last message.
+	///
+	/// The peer must receive the messages with [`Self::recv_fds_chunked()`].
+	/// Do not send other messages on the same socket concurrently, or the chunks may be interleaved with them.
+	///
+	/// Returns the number of bytes of `buffer` that were sent.
+	pub async fn send_fds_chunked(&self, buffer: &[u8], fds: &[BorrowedFd<'_>]) -> std::io::Result<usize> {
+		let chunks = fds.len().max(1).div_ceil(crate::ancillary::SCM_MAX_FD);
+		for (i, chunk) in fds.chunks(crate::ancillary::SCM_MAX_FD).take(chunks - 1).enumerate() {
+			let header = ((chunks - 1 - i) as u32).to_ne_bytes();
+			let mut ancillary = crate::ancillary::AncillaryBuilder::new().with_fds(chunk).build()?;
+			self.send_vectored_with_ancillary(&[IoSlice::new(&header)], &mut ancillary).await?;
+		}
+
+		let last = &fds[(chunks - 1) * crate::ancillary::SCM_MAX_FD..];
+		let header = 0u32.to_ne_bytes();
+		let mut ancillary = crate::ancillary::AncillaryBuilder::new().with_fds(last).build()?;
+		let size = self
+			.send_vectored_with_ancillary(&[IoSlice::new(&header), IoSlice::new(buffer)], &mut ancillary)
+			.await?;
+		Ok(size.saturating_sub(header.len()))
+	}
+
+	/// Send data with a single file descriptor on the socket to the connected peer.
+	///
+	/// This is a shorthand for [`Self::send_fds()`] with one file descriptor.
+	pub async fn send_with_fd(&self, buffer: &[u8], fd: BorrowedFd<'_>) -> std::io::Result<usize> {
+		self.send_fds(buffer, &[fd]).await
+	}
+
+	/// Send data with explicit credentials on the socket to the connected peer.
+	///
+	/// The credentials are sent as a `SCM_CREDENTIALS` control message.
+	/// Use [`SocketCred::from_process()`](crate::ancillary::SocketCred::from_process) to send the credentials of the current process.
+	/// The kernel verifies the credentials:
+	/// sending the PID of another process requires `CAP_SYS_ADMIN`,
+	/// and sending a different UID or GID requires `CAP_SETUID` or `CAP_SETGID`.
+	///
+	/// The peer only receives the credentials if it enabled `SO_PASSCRED`,
+	/// for example with [`UnixSeqpacketBuilder::passcred()`].
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub async fn send_with_creds(
+		&self,
+		buffer: &[u8],
+		creds: &crate::ancillary::SocketCred,
+	) -> std::io::Result<usize> {
+		let mut ancillary = crate::ancillary::AncillaryBuilder::new().with_creds(creds.clone()).build()?;
+		self.send_vectored_with_ancillary(&[IoSlice::new(buffer)], &mut ancillary).await
+	}
+
+	/// Send data on the socket to the connected peer.
+	pub async fn send_vectored(&self, buffer: &[IoSlice<'_>]) -> std::io::Result<usize> {
+		poll_fn(|cx| self.poll_send_vectored(cx, buffer)).await
+	}
+
+	/// Send data with ancillary data on the socket to the connected peer.
+	pub async fn send_vectored_with_ancillary(
+		&self,
+		buffer: &[IoSlice<'_>],
+		ancillary: &mut SocketAncillary<'_>,
+	) -> std::io::Result<usize> {
+		poll_fn(|cx| self.poll_send_vectored_with_ancillary(cx, buffer, ancillary)).await
+	}
+
+	/// Try to receive data on the socket from the connected peer without blocking.
+	///
+	/// If there is no data ready yet, the current task is scheduled to wake up when the socket becomes readable.
+	pub fn poll_recv(&self, cx: &mut Context, buffer: &mut [u8]) -> Poll<std::io::Result<usize>> {
+		poll_recv(self, cx, buffer)
+	}
+
+	/// Try to receive data on the socket from the connected peer without blocking.
+	///
+	/// If there is no data ready yet, the current task is scheduled to wake up when the socket becomes readable.
+	pub fn poll_recv_vectored(&self, cx: &mut Context, buffer: &mut [IoSliceMut]) -> Poll<std::io::Result<usize>> {
+		poll_recv_vectored(self, cx, buffer)
+	}
+
+	/// Try to receive data with ancillary data on the socket from the connected peer without blocking.
+	///
+	/// If there is no data ready yet, the current task is scheduled to wake up when the socket becomes readable.
+	pub fn poll_recv_vectored_with_ancillary(
+		&self,
+		cx: &mut Context,
+		buffer: &mut [IoSliceMut],
+		ancillary: &mut SocketAncillary,
+	) -> Poll<std::io::Result<usize>> {
+		poll_recv_vectored_with_ancillary(self, cx, buffer, ancillary)
+	}
+
+	/// Try to receive data with ancillary data on the socket from the connected peer without blocking.
+	///
+	/// Unlike [`Self::poll_recv_vectored_with_ancillary()`], this reports the full size of the message
+	/// and the flags set by the kernel, such as `MSG_TRUNC` and `MSG_CTRUNC`.
+	///
+	/// If there is no data ready yet, the current task is scheduled to wake up when the socket becomes readable.
+	pub fn poll_recv_vectored_with_ancillary_meta(
+		&self,
+		cx: &mut Context,
+		buffer: &mut [IoSliceMut],
+		ancillary: &mut SocketAncillary,
+	) -> Poll<std::io::Result<RecvMeta>> {
+		poll_recv_vectored_with_ancillary_meta(self, cx, buffer, ancillary)
+	}
+
+	/// Try to receive data on the socket from the connected peer without waiting.
+	///
+	/// If no message is queued, an error of kind [`std::io::ErrorKind::WouldBlock`] is returned.
+	/// Unlike [`Self::poll_recv()`], this does not schedule the current task to be woken up.
+	pub fn try_recv(&self, buffer: &mut [u8]) -> std::io::Result<usize> {
+		self.try_recv_vectored(&mut [IoSliceMut::new(buffer)])
+	}
+
+	/// Try to receive data on the socket from the connected peer without waiting.
+	///
+	/// If no message is queued, an error of kind [`std::io::ErrorKind::WouldBlock`] is returned.
+	/// Unlike [`Self::poll_recv_vectored()`], this does not schedule the current task to be woken up.
+	pub fn try_recv_vectored(&self, buffer: &mut [IoSliceMut]) -> std::io::Result<usize> {
+		self.try_recv_vectored_with_ancillary(buffer, &mut SocketAncillary::new(&mut []))
+	}
+
+	/// Try to receive data with ancillary data on the socket from the connected peer without waiting.
+	///
+	/// If no message is queued, an error of kind [`std::io::ErrorKind::WouldBlock`] is returned.
+	/// Unlike [`Self::poll_recv_vectored_with_ancillary()`], this does not schedule the current task to be woken up.
+	pub fn try_recv_vectored_with_ancillary(
+		&self,
+		buffer: &mut [IoSliceMut],
+		ancillary: &mut SocketAncillary,
+	) -> std::io::Result<usize> {
+		let (size, msg_flags) =
+			self.try_nonblocking(Interest::READABLE, |socket| recv_msg_with_flags(socket, buffer, ancillary, 0))?;
+		self.record_received(size, msg_flags);
+		Ok(size)
+	}
+
+	/// Try to receive data on the socket from the connected peer without blocking, with extra `MSG_*` flags.
+	///
+	/// The flags are combined with the flags that are always used (`MSG_NOSIGNAL` and `MSG_CMSG_CLOEXEC`).
+	/// Note that with `MSG_TRUNC`, the returned size is the full size of the message, which can be larger than the buffer.
+	///
+	/// If there is no data ready yet, the current task is scheduled to wake up when the socket becomes readable.
+	pub fn poll_recv_with_flags(
+		&self,
+		cx: &mut Context,
+		buffer: &mut [u8],
+		flags: std::os::raw::c_int,
+	) -> Poll<std::io::Result<usize>> {
+		poll_recv_with_flags(self, cx, buffer, flags)
+	}
+
+	/// Receive data on the socket from the connected peer, with extra `MSG_*` flags.
+	///
+	/// The flags are combined with the flags that are always used (`MSG_NOSIGNAL` and `MSG_CMSG_CLOEXEC`).
+	/// Note that with `MSG_TRUNC`, the returned size is the full size of the message, which can be larger than the buffer.
+	pub async fn recv_with_flags(&self, buffer: &mut [u8], flags: std::os::raw::c_int) -> std::io::Result<usize> {
+		poll_fn(|cx| self.poll_recv_with_flags(cx, buffer, flags)).await
+	}
+
+	/// Try to receive data on the socket from the connected peer into uninitialized memory without blocking.
+	///
+	/// On success, the number of received bytes is returned.
+	/// That many bytes at the start of the buffer have been initialized.
+	/// This avoids the cost of zeroing a large buffer before every receive.
+	///
+	/// If there is no data ready yet, the current task is scheduled to wake up when the socket becomes readable.
+	pub fn poll_recv_uninit(&self, cx: &mut Context, buffer: &mut [MaybeUninit<u8>]) -> Poll<std::io::Result<usize>> {
+		poll_recv_uninit(self, cx, buffer)
+	}
+
+	/// Receive data on the socket from the connected peer into uninitialized memory.
+	///
+	/// On success, the number of received bytes is returned.
+	/// That many bytes at the start of the buffer have been initialized.
+	/// This avoids the cost of zeroing a large buffer before every receive.
+	pub async fn recv_uninit(&self, buffer: &mut [MaybeUninit<u8>]) -> std::io::Result<usize> {
+		poll_fn(|cx| self.poll_recv_uninit(cx, buffer)).await
+	}
+
+	/// Receive the next message from the connected peer and append it to a [`bytes::BytesMut`].
+	///
+	/// The size of the message is determined first, and enough capacity is reserved in the buffer to hold it.
+	/// The message is received directly into the spare capacity of the buffer, without zeroing it first.
+	///
+	/// Returns the number of bytes appended to the buffer.
+	///
+	/// This function is only available when the `bytes` feature is enabled.
+	#[cfg(feature = "bytes")]
+	pub async fn recv_buf(&self, buffer: &mut bytes::BytesMut) -> std::io::Result<usize> {
+		let size = self.next_message_size().await?;
+		buffer.reserve(size);
+		let received = self.recv_uninit(buffer.spare_capacity_mut()).await?;
+		// Safety: the kernel initialized this many bytes of the spare capacity.
+		unsafe {
+			buffer.set_len(buffer.len() + received);
+		}
+		Ok(received)
+	}
+
+	/// Try to receive multiple messages from the connected peer with a single system call, without blocking.
+	///
+	/// Each slot receives one message.
+	/// Returns the number of messages that were received, which may be less than the number of slots.
+	/// Only the first slots up to that number are filled, the others are left untouched.
+	///
+	/// If there is no data ready yet, the current task is scheduled to wake up when the socket becomes readable.
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub fn poll_recv_batch(&self, cx: &mut Context, slots: &mut [RecvSlot]) -> Poll<std::io::Result<usize>> {
+		if slots.is_empty() {
+			return Poll::Ready(Ok(0));
+		}
+
+		let mut ready_guard = ready!(self.io.poll_read_ready(cx)?);
+		match recv_mmsg(self.io.get_ref(), slots) {
+			Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+				ready_guard.clear_ready();
+				record_metric!(record_would_block(self.as_raw_fd()));
+				Poll::Pending
+			},
+			Ok(received) => {
+				if let Some(stats) = &self.stats {
+					for slot in &slots[..received] {
+						stats.record_received(slot.received, slot.truncated);
+					}
+				}
+				Poll::Ready(Ok(received))
+			},
+			x => Poll::Ready(x),
+		}
+	}
+
+	/// Receive multiple messages from the connected peer with a single system call (`recvmmsg`).
+	///
+	/// This waits until at least one message is available, and then receives all queued messages that fit in the slots.
+	/// Returns the number of messages that were received.
+	/// Only the first slots up to that number are filled, the others are left untouched.
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub async fn recv_batch(&self, slots: &mut [RecvSlot<'_>]) -> std::io::Result<usize> {
+		poll_fn(|cx| self.poll_recv_batch(cx, slots)).await
+	}
+
+	/// Try to read the next message from the connected peer without removing it from the queue.
+	///
+	/// The message remains queued, and the socket stays readable,
+	/// so a later call to [`Self::recv()`] receives the same message again.
+	///
+	/// If there is no data ready yet, the current task is scheduled to wake up when the socket becomes readable.
+	pub fn poll_peek(&self, cx: &mut Context, buffer: &mut [u8]) -> Poll<std::io::Result<usize>> {
+		poll_recv_with_flags(self, cx, buffer, libc::MSG_PEEK)
+	}
+
+	/// Read the next message from the connected peer without removing it from the queue.
+	///
+	/// The message remains queued, so a later call to [`Self::recv()`] receives the same message again.
+	/// This can be used to inspect a message header before deciding how to handle the message.
+	pub async fn peek(&self, buffer: &mut [u8]) -> std::io::Result<usize> {
+		poll_fn(|cx| self.poll_peek(cx, buffer)).await
+	}
+
+	/// Try to receive data on the socket from the connected peer without blocking, and report truncation.
+	///
+	/// If the message does not fit in the buffer, the remainder of the message is discarded,
+	/// and the returned [`RecvMeta`] reports the original size of the message.
+	///
+	/// If there is no data ready yet, the current task is scheduled to wake up when the socket becomes readable.
+	pub fn poll_recv_with_meta(&self, cx: &mut Context, buffer: &mut [u8]) -> Poll<std::io::Result<RecvMeta>> {
+		poll_recv_with_meta(self, cx, buffer)
 	}
 
-	#[doc(hidden)]
-	#[deprecated(
-		since = "0.4.0",
-		note = "all I/O functions now take a shared reference to self, so splitting is no longer necessary"
-	)]
-	pub fn split(&self) -> (&Self, &Self) {
-		(self, self)
+	/// Receive data on the socket from the connected peer, and report truncation.
+	///
+	/// If the message does not fit in the buffer, the remainder of the message is discarded,
+	/// and the returned [`RecvMeta`] reports the original size of the message.
+	pub async fn recv_with_meta(&self, buffer: &mut [u8]) -> std::io::Result<RecvMeta> {
+		poll_fn(|cx| self.poll_recv_with_meta(cx, buffer)).await
 	}
 
-	/// Get the socket address of the local half of this connection.
-	pub fn local_addr(&self) -> std::io::Result<std::os::unix::net::SocketAddr> {
-		let addr = self.io.get_ref().local_addr()?;
-		Ok(crate::sockaddr_as_unix(&addr).unwrap())
+	/// Try to receive a message that fits in the buffer, discarding messages that are too large.
+	///
+	/// If the next message is larger than the buffer, it is removed from the socket and
+	/// an error of kind [`std::io::ErrorKind::InvalidData`] wrapping a [`MessageTooLargeError`] is returned.
+	/// The buffer contents are unspecified in that case.
+	///
+	/// If there is no data ready yet, the current task is scheduled to wake up when the socket becomes readable.
+	pub fn poll_recv_or_discard(&self, cx: &mut Context, buffer: &mut [u8]) -> Poll<std::io::Result<usize>> {
+		let capacity = buffer.len();
+		let meta = ready!(poll_recv_with_meta(self, cx, buffer)?);
+		if meta.truncated() {
+			Poll::Ready(Err(MessageTooLargeError::new(meta.size(), capacity).into()))
+		} else {
+			Poll::Ready(Ok(meta.received()))
+		}
 	}
 
-	/// Get the socket address of the remote half of this connection.
-	pub fn peer_addr(&self) -> std::io::Result<std::os::unix::net::SocketAddr> {
-		let addr = self.io.get_ref().peer_addr()?;
-		Ok(crate::sockaddr_as_unix(&addr).unwrap())
+	/// Receive a message that fits in the buffer, discarding messages that are too large.
+	///
+	/// Unlike [`Self::recv()`], a message that is larger than the buffer is never silently truncated.
+	/// Instead, the message is removed from the socket and
+	/// an error of kind [`std::io::ErrorKind::InvalidData`] wrapping a [`MessageTooLargeError`] is returned.
+	/// The next call receives the next message, so oversized messages can be skipped without losing sync.
+	///
+	/// # Example
+	/// ```no_run
+	/// # async fn foo(socket: tokio_seqpacket::UnixSeqpacket) -> std::io::Result<()> {
+	/// use tokio_seqpacket::MessageTooLargeError;
+	///
+	/// let mut buffer = [0u8; 1024];
+	/// loop {
+	///     match socket.recv_or_discard(&mut buffer).await {
+	///         Ok(len) => println!("received {} bytes", len),
+	///         Err(e) => match e.get_ref().and_then(|e| e.downcast_ref::<MessageTooLargeError>()) {
+	///             Some(e) => eprintln!("skipped message of {} bytes", e.size()),
+	///             None => return Err(e),
+	///         },
+	///     }
+	/// }
+	/// # }
+	/// ```
+	pub async fn recv_or_discard(&self, buffer: &mut [u8]) -> std::io::Result<usize> {
+		poll_fn(|cx| self.poll_recv_or_discard(cx, buffer)).await
 	}
 
-	/// Get the effective credentials of the process which called `connect` or `pair`.
+	/// Try to get the size of the next queued message without blocking.
 	///
-	/// Note that this is not necessarily the process that currently has the file descriptor
-	/// of the other side of the connection.
-	pub fn peer_cred(&self) -> std::io::Result<UCred> {
-		UCred::from_socket_peer(&self.io)
+	/// The message is not removed from the queue.
+	/// Note that an empty message and a closed connection both report a size of 0.
+	///
+	/// If there is no data ready yet, the current task is scheduled to wake up when the socket becomes readable.
+	pub fn poll_next_message_size(&self, cx: &mut Context) -> Poll<std::io::Result<usize>> {
+		poll_recv_with_flags(self, cx, &mut [], libc::MSG_PEEK | libc::MSG_TRUNC)
 	}
 
-	/// Get the value of the `SO_ERROR` option.
-	pub fn take_error(&self) -> std::io::Result<Option<std::io::Error>> {
-		self.io.get_ref().take_error()
+	/// Get the size of the next queued message in bytes, waiting for a message if needed.
+	///
+	/// The message is not removed from the queue.
+	/// This can be used to pick a large enough buffer before calling [`Self::recv()`].
+	/// Note that an empty message and a closed connection both report a size of 0.
+	pub async fn next_message_size(&self) -> std::io::Result<usize> {
+		poll_fn(|cx| self.poll_next_message_size(cx)).await
 	}
 
-	/// Try to send data on the socket to the connected peer without blocking.
+	/// Receive the next message from the connected peer into a newly allocated [`Vec`].
 	///
-	/// If the socket is not ready yet, the current task is scheduled to wake up when the socket becomes writeable.
-	pub fn poll_send(&self, cx: &mut Context, buffer: &[u8]) -> Poll<std::io::Result<usize>> {
-		poll_send(self, cx, buffer)
+	/// The size of the message is determined first, so the vector is allocated with exactly the right size.
+	///
+	/// If other tasks are receiving on the same socket concurrently,
+	/// they may take the message between determining the size and receiving it.
+	/// In that case, a different message is received, which may be truncated.
+	/// Use [`Self::recv_with_meta()`] if you need to detect that.
+	pub async fn recv_to_vec(&self) -> std::io::Result<Vec<u8>> {
+		let size = self.next_message_size().await?;
+		let mut buffer = vec![0; size];
+		let received = self.recv(&mut buffer).await?;
+		buffer.truncate(received);
+		Ok(buffer)
 	}
 
-	/// Try to send data on the socket to the connected peer without blocking.
+	/// Receive multiple messages from the connected peer into newly allocated vectors.
 	///
-	/// If the socket is not ready yet, the current task is scheduled to wake up when the socket becomes writeable.
-	pub fn poll_send_vectored(&self, cx: &mut Context, buffer: &[IoSlice]) -> Poll<std::io::Result<usize>> {
-		poll_send_vectored(self, cx, buffer)
+	/// This waits until at least one message is available.
+	/// After that, all messages that are already queued are received without waiting,
+	/// up to a total of `limit` messages.
+	/// The messages are appended to `messages`, and the number of received messages is returned.
+	///
+	/// If `limit` is zero, this returns immediately with `Ok(0)`.
+	///
+	/// An empty message and a closed connection can not be distinguished.
+	/// To avoid filling the vector with empty messages when the connection is closed,
+	/// no more messages are received after an empty message.
+	///
+	/// # Example
+	/// ```no_run
+	/// # async fn foo(socket: tokio_seqpacket::UnixSeqpacket) -> std::io::Result<()> {
+	/// let mut messages = Vec::with_capacity(32);
+	/// loop {
+	///     socket.recv_many(&mut messages, 32).await?;
+	///     for message in messages.drain(..) {
+	///         println!("received {} bytes", message.len());
+	///     }
+	/// }
+	/// # }
+	/// ```
+	pub async fn recv_many(&self, messages: &mut Vec<Vec<u8>>, limit: usize) -> std::io::Result<usize> {
+		if limit == 0 {
+			return Ok(0);
+		}
+
+		let message = self.recv_to_vec().await?;
+		let mut done = message.is_empty();
+		messages.push(message);
+		let mut count = 1;
+
+		while count < limit && !done {
+			let size = match self.try_nonblocking(Interest::READABLE, |socket| {
+				recv_msg(socket, &mut [], &mut SocketAncillary::new(&mut []), libc::MSG_PEEK | libc::MSG_TRUNC)
+			}) {
+				Ok(size) => size,
+				Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+				Err(e) => return Err(e),
+			};
+			let mut message = vec![0; size];
+			let received = self.try_recv(&mut message)?;
+			message.truncate(received);
+			done = message.is_empty();
+			messages.push(message);
+			count += 1;
+		}
+
+		Ok(count)
 	}
 
-	/// Try to send data with ancillary data on the socket to the connected peer without blocking.
-	///
-	/// If the socket is not ready yet, the current task is scheduled to wake up when the socket becomes writeable.
-	pub fn poll_send_vectored_with_ancillary(
-		&self,
-		cx: &mut Context,
-		buffer: &[IoSlice],
-		ancillary: &mut SocketAncillary,
-	) -> Poll<std::io::Result<usize>> {
-		poll_send_vectored_with_ancillary(self, cx, buffer, ancillary)
+	/// Receive data on the socket from the connected peer.
+	pub async fn recv(&self, buffer: &mut [u8]) -> std::io::Result<usize> {
+		poll_fn(|cx| self.poll_recv(cx, buffer)).await
 	}
 
-	/// Send data on the socket to the connected peer.
-	pub async fn send(&self, buffer: &[u8]) -> std::io::Result<usize> {
-		poll_fn(|cx| self.poll_send(cx, buffer)).await
+	/// Receive data on the socket from the connected peer, with a timeout.
+	///
+	/// If no message arrives before the timeout expires, an error of kind [`std::io::ErrorKind::TimedOut`] is returned.
+	///
+	/// A message is always received as a whole, so a timeout never loses part of a message.
+	/// If the timeout expires, any message that arrives later stays queued for the next receive.
+	pub async fn recv_timeout(&self, buffer: &mut [u8], timeout: std::time::Duration) -> std::io::Result<usize> {
+		self.recv_deadline(buffer, tokio::time::Instant::now() + timeout).await
 	}
 
-	/// Send data on the socket to the connected peer.
-	pub async fn send_vectored(&self, buffer: &[IoSlice<'_>]) -> std::io::Result<usize> {
-		poll_fn(|cx| self.poll_send_vectored(cx, buffer)).await
+	/// Receive data on the socket from the connected peer, with a deadline.
+	///
+	/// If no message arrives before the deadline, an error of kind [`std::io::ErrorKind::TimedOut`] is returned.
+	/// Like [`Self::recv_timeout()`], this never loses part of a message.
+	///
+	/// This is useful when multiple operations share a total time budget.
+	pub async fn recv_deadline(&self, buffer: &mut [u8], deadline: tokio::time::Instant) -> std::io::Result<usize> {
+		match tokio::time::timeout_at(deadline, self.recv(buffer)).await {
+			Ok(result) => result,
+			Err(_) => Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "receive timed out")),
+		}
 	}
 
-	/// Send data with ancillary data on the socket to the connected peer.
-	pub async fn send_vectored_with_ancillary(
+	/// Receive data on the socket from the connected peer, unless the cancellation token fires first.
+	///
+	/// Returns `Ok(None)` if the token was cancelled before a message was received.
+	/// In that case, no message was removed from the queue.
+	///
+	/// This function is only available when the `tokio-util` feature is enabled.
+	///
+	/// # Example
+	/// ```no_run
+	/// # async fn foo(socket: tokio_seqpacket::UnixSeqpacket) -> std::io::Result<()> {
+	/// use tokio_util::sync::CancellationToken;
+	///
+	/// let shutdown = CancellationToken::new();
+	/// let mut buffer = [0u8; 1024];
+	/// while let Some(len) = socket.recv_or_cancelled(&shutdown, &mut buffer).await? {
+	///     println!("received {} bytes", len);
+	/// }
+	/// # Ok(())
+	/// # }
+	/// ```
+	#[cfg(feature = "tokio-util")]
+	pub async fn recv_or_cancelled(
 		&self,
-		buffer: &[IoSlice<'_>],
-		ancillary: &mut SocketAncillary<'_>,
-	) -> std::io::Result<usize> {
-		poll_fn(|cx| self.poll_send_vectored_with_ancillary(cx, buffer, ancillary)).await
+		token: &tokio_util::sync::CancellationToken,
+		buffer: &mut [u8],
+	) -> std::io::Result<Option<usize>> {
+		token.run_until_cancelled(self.recv(buffer)).await.transpose()
 	}
 
-	/// Try to receive data on the socket from the connected peer without blocking.
+	/// Receive data and file descriptors on the socket from the connected peer.
 	///
-	/// If there is no data ready yet, the current task is scheduled to wake up when the socket becomes readable.
-	pub fn poll_recv(&self, cx: &mut Context, buffer: &mut [u8]) -> Poll<std::io::Result<usize>> {
-		poll_recv(self, cx, buffer)
+	/// All file descriptors received in `SCM_RIGHTS` control messages are returned as [`ReceivedFd`](crate::ancillary::ReceivedFd),
+	/// so they are closed automatically when dropped.
+	/// Other control messages are ignored.
+	/// The ancillary buffer is allocated internally and is large enough for the maximum number of file descriptors in a message.
+	pub async fn recv_fds(&self, buffer: &mut [u8]) -> std::io::Result<(usize, Vec<crate::ancillary::ReceivedFd>)> {
+		use crate::ancillary::OwnedAncillaryData;
+
+		let space = crate::ancillary::ancillary_space_for_fds(crate::ancillary::SCM_MAX_FD);
+		let mut ancillary = SocketAncillary::with_capacity(space);
+		let size = self
+			.recv_vectored_with_ancillary(&mut [IoSliceMut::new(buffer)], &mut ancillary)
+			.await?;
+
+		let mut fds = Vec::new();
+		for message in ancillary.into_messages() {
+			if let Ok(OwnedAncillaryData::ScmRights(rights)) = message {
+				fds.extend(rights);
+			}
+		}
+		Ok((size, fds))
 	}
 
-	/// Try to receive data on the socket from the connected peer without blocking.
+	/// Receive data and any number of file descriptors sent with [`Self::send_fds_chunked()`].
 	///
-	/// If there is no data ready yet, the current task is scheduled to wake up when the socket becomes readable.
-	pub fn poll_recv_vectored(&self, cx: &mut Context, buffer: &mut [IoSliceMut]) -> Poll<std::io::Result<usize>> {
-		poll_recv_vectored(self, cx, buffer)
+	/// This receives messages until the last chunk arrives,
+	/// and returns the data of the last message with the file descriptors of all chunks in their original order.
+	///
+	/// If a message does not carry a valid chunk header, an error of kind [`std::io::ErrorKind::InvalidData`] is returned.
+	/// If file descriptors were discarded because the control data was truncated,
+	/// an [`AncillaryTruncatedError`](crate::AncillaryTruncatedError) is returned wrapped in an error of the same kind.
+	/// File descriptors received before the error are closed.
+	pub async fn recv_fds_chunked(&self, buffer: &mut [u8]) -> std::io::Result<(usize, Vec<crate::ancillary::ReceivedFd>)> {
+		let space = crate::ancillary::ancillary_space_for_fds(crate::ancillary::SCM_MAX_FD);
+		let mut ancillary = SocketAncillary::with_capacity(space);
+		let mut fds = Vec::new();
+		let mut expected = None;
+
+		loop {
+			let mut header = [0u8; 4];
+			let size = self
+				.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut header), IoSliceMut::new(buffer)], &mut ancillary)
+				.await?;
+			fds.extend(ancillary.take_fds());
+
+			if let Some(truncation) = ancillary.truncation() {
+				return Err(truncation.into());
+			}
+			if size < header.len() {
+				return Err(std::io::Error::new(
+					std::io::ErrorKind::InvalidData,
+					"message is too short to contain a chunk header",
+				));
+			}
+
+			let remaining = u32::from_ne_bytes(header);
+			if expected.is_some_and(|expected| remaining != expected) {
+				return Err(std::io::Error::new(
+					std::io::ErrorKind::InvalidData,
+					"received chunks out of order",
+				));
+			}
+			if remaining == 0 {
+				return Ok((size - header.len(), fds));
+			}
+			expected = Some(remaining - 1);
+		}
 	}
 
-	/// Try to receive data with ancillary data on the socket from the connected peer without blocking.
+	/// Receive data and at most one file descriptor on the socket from the connected peer.
 	///
-	/// If there is no data ready yet, the current task is scheduled to wake up when the socket becomes readable.
-	pub fn poll_recv_vectored_with_ancillary(
-		&self,
-		cx: &mut Context,
-		buffer: &mut [IoSliceMut],
-		ancillary: &mut SocketAncillary,
-	) -> Poll<std::io::Result<usize>> {
-		poll_recv_vectored_with_ancillary(self, cx, buffer, ancillary)
+	/// This is meant to be used with messages sent by [`Self::send_with_fd()`].
+	/// If the message carries more than one file descriptor, the additional file descriptors are closed.
+	pub async fn recv_with_fd(&self, buffer: &mut [u8]) -> std::io::Result<(usize, Option<crate::ancillary::ReceivedFd>)> {
+		let (size, fds) = self.recv_fds(buffer).await?;
+		Ok((size, fds.into_iter().next()))
 	}
 
-	/// Receive data on the socket from the connected peer.
-	pub async fn recv(&self, buffer: &mut [u8]) -> std::io::Result<usize> {
-		poll_fn(|cx| self.poll_recv(cx, buffer)).await
+	/// Receive data and the credentials of the sender on the socket from the connected peer.
+	///
+	/// The credentials are parsed from the `SCM_CREDENTIALS` control message.
+	/// Unlike [`Self::peer_cred()`], they reflect the process that sent this particular message.
+	///
+	/// The kernel only attaches credentials to messages if `SO_PASSCRED` was enabled on the receiving socket
+	/// before the message was sent, for example with [`UnixSeqpacketBuilder::passcred()`].
+	/// If the message has no credentials, `None` is returned.
+	/// Any file descriptors sent with the message are closed.
+	#[cfg(any(doc, target_os = "android", target_os = "linux"))]
+	pub async fn recv_with_creds(&self, buffer: &mut [u8]) -> std::io::Result<(usize, Option<UCred>)> {
+		use crate::ancillary::OwnedAncillaryData;
+
+		let space = crate::ancillary::ancillary_space_for_fds(crate::ancillary::SCM_MAX_FD) + crate::ancillary::ancillary_space_for_creds();
+		let mut ancillary = SocketAncillary::with_capacity(space);
+		let size = self
+			.recv_vectored_with_ancillary(&mut [IoSliceMut::new(buffer)], &mut ancillary)
+			.await?;
+
+		let mut creds = None;
+		for message in ancillary.into_messages() {
+			if let Ok(OwnedAncillaryData::ScmCredentials(received)) = message {
+				if let Some(received) = received.first() {
+					creds = Some(received.to_ucred());
+				}
+			}
+		}
+		Ok((size, creds))
 	}
 
 	/// Receive data on the socket from the connected peer.
@@ -203,6 +1570,107 @@ impl UnixSeqpacket {
 		poll_fn(|cx| self.poll_recv_vectored_with_ancillary(cx, buffer, ancillary)).await
 	}
 
+	/// Receive data with ancillary data on the socket from the connected peer, reporting the message flags.
+	///
+	/// Unlike [`Self::recv_vectored_with_ancillary()`], this reports the full size of the message
+	/// and the flags set by the kernel, such as `MSG_TRUNC` and `MSG_CTRUNC`.
+	///
+	/// # Example
+	/// ```no_run
+	/// # async fn foo(socket: tokio_seqpacket::UnixSeqpacket) -> std::io::Result<()> {
+	/// use std::io::IoSliceMut;
+	/// use tokio_seqpacket::ancillary::SocketAncillary;
+	///
+	/// let mut buffer = [0u8; 1024];
+	/// let mut ancillary_buffer = [0u8; 128];
+	/// let mut ancillary = SocketAncillary::new(&mut ancillary_buffer);
+	/// let meta = socket
+	///     .recv_vectored_with_ancillary_meta(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary)
+	///     .await?;
+	/// if meta.truncated() || meta.ancillary_truncated() {
+	///     eprintln!("received a truncated message of {} bytes", meta.size());
+	/// }
+	/// # Ok(())
+	/// # }
+	/// ```
+	pub async fn recv_vectored_with_ancillary_meta(
+		&self,
+		buffer: &mut [IoSliceMut<'_>],
+		ancillary: &mut SocketAncillary<'_>,
+	) -> std::io::Result<RecvMeta> {
+		poll_fn(|cx| self.poll_recv_vectored_with_ancillary_meta(cx, buffer, ancillary)).await
+	}
+
+	/// Try to perform a custom I/O operation on the socket without waiting.
+	///
+	/// If the runtime has seen the socket become ready for `interest`, the closure is called.
+	/// Otherwise, an error of kind [`std::io::ErrorKind::WouldBlock`] is returned without calling the closure.
+	///
+	/// The closure should perform a single non-blocking operation on the file descriptor, for example with `libc`.
+	/// If it returns [`std::io::ErrorKind::WouldBlock`], the readiness for `interest` is cleared.
+	/// The closure must only return that error if the operation really failed because the socket was not ready,
+	/// or the readiness tracking of the socket may get confused.
+	///
+	/// # Example
+	/// ```no_run
+	/// # async fn foo(socket: tokio_seqpacket::UnixSeqpacket) -> std::io::Result<()> {
+	/// use tokio::io::Interest;
+	///
+	/// let mut buffer = [0u8; 128];
+	/// socket.readable().await?;
+	/// let len = socket.try_io(Interest::READABLE, || {
+	///     let ret = unsafe {
+	///         libc::recv(socket.as_raw_fd(), buffer.as_mut_ptr().cast(), buffer.len(), libc::MSG_PEEK)
+	///     };
+	///     if ret < 0 {
+	///         Err(std::io::Error::last_os_error())
+	///     } else {
+	///         Ok(ret as usize)
+	///     }
+	/// })?;
+	/// # Ok(())
+	/// # }
+	/// ```
+	pub fn try_io<R>(&self, interest: Interest, f: impl FnOnce() -> std::io::Result<R>) -> std::io::Result<R> {
+		self.io.try_io(interest, |_| f())
+	}
+
+	/// Perform a custom I/O operation on the socket, waiting for readiness as needed.
+	///
+	/// The closure is called when the socket is ready for `interest`.
+	/// If it returns [`std::io::ErrorKind::WouldBlock`], the readiness is cleared and the closure is called again
+	/// when the socket becomes ready again.
+	///
+	/// See [`Self::try_io()`] for the requirements on the closure.
+	pub async fn async_io<R>(
+		&self,
+		interest: Interest,
+		mut f: impl FnMut() -> std::io::Result<R>,
+	) -> std::io::Result<R> {
+		self.io.async_io(interest, |_| f()).await
+	}
+
+	/// Perform a non-blocking I/O operation on the socket.
+	///
+	/// If the operation fails with [`std::io::ErrorKind::WouldBlock`], the readiness for `interest` is cleared,
+	/// so that [`Self::readable()`] and [`Self::writable()`] wait for a new readiness event.
+	/// If the runtime has not seen the socket become ready yet, the operation is still attempted.
+	fn try_nonblocking<R>(
+		&self,
+		interest: Interest,
+		mut operation: impl FnMut(&socket2::Socket) -> std::io::Result<R>,
+	) -> std::io::Result<R> {
+		let mut attempted = false;
+		let result = self.io.try_io(interest, |socket| {
+			attempted = true;
+			operation(socket)
+		});
+		match result {
+			Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock && !attempted => operation(self.io.get_ref()),
+			x => x,
+		}
+	}
+
 	/// Shuts down the read, write, or both halves of this connection.
 	///
 	/// This function will cause all pending and future I/O calls on the
@@ -211,6 +1679,26 @@ impl UnixSeqpacket {
 	pub fn shutdown(&self, how: std::net::Shutdown) -> std::io::Result<()> {
 		self.io.get_ref().shutdown(how)
 	}
+
+	/// Start tracking statistics for this socket.
+	///
+	/// The counters start at zero and are updated by all functions that send or receive messages.
+	/// If statistics are already enabled, this does nothing.
+	///
+	/// Statistics are disabled by default, to avoid the overhead when they are not used.
+	pub fn enable_stats(&mut self) {
+		if self.stats.is_none() {
+			self.stats = Some(Box::new(StatsTracker::new()));
+		}
+	}
+
+	/// Get the cumulative statistics of this socket.
+	///
+	/// This can be used to report the health of connections or to close idle connections.
+	/// Returns `None` if statistics were not enabled with [`Self::enable_stats()`].
+	pub fn stats(&self) -> Option<SocketStats> {
+		self.stats.as_ref().map(|stats| stats.snapshot())
+	}
 }
 
 impl AsRawFd for UnixSeqpacket {
@@ -225,12 +1713,62 @@ impl IntoRawFd for UnixSeqpacket {
 	}
 }
 
+impl AsFd for UnixSeqpacket {
+	fn as_fd(&self) -> BorrowedFd<'_> {
+		// The file descriptor remains open for as long as `self` is borrowed.
+		unsafe { BorrowedFd::borrow_raw(self.as_raw_fd()) }
+	}
+}
+
+/// Take ownership of a file descriptor and wrap it in a [`UnixSeqpacket`].
+///
+/// The file descriptor must be a unix seqpacket socket that is not listening, or an error is returned.
+/// The file descriptor is put in non-blocking mode and registered with the tokio runtime.
+/// If anything fails, the file descriptor is closed.
+impl TryFrom<OwnedFd> for UnixSeqpacket {
+	type Error = std::io::Error;
+
+	fn try_from(fd: OwnedFd) -> std::io::Result<Self> {
+		crate::check_socket_type(fd.as_raw_fd(), libc::SOCK_SEQPACKET)?;
+		if crate::is_listening(fd.as_raw_fd())? {
+			return Err(std::io::Error::new(
+				std::io::ErrorKind::InvalidInput,
+				format!("file descriptor {} is a listening socket", fd.as_raw_fd()),
+			));
+		}
+		let socket = unsafe { socket2::Socket::from_raw_fd(fd.into_raw_fd()) };
+		socket.set_nonblocking(true)?;
+		Self::new(socket)
+	}
+}
+
+/// Deregister the socket from the tokio runtime and return the inner file descriptor.
+impl From<UnixSeqpacket> for OwnedFd {
+	fn from(socket: UnixSeqpacket) -> Self {
+		unsafe { OwnedFd::from_raw_fd(socket.into_raw_fd()) }
+	}
+}
+
+/// Start connecting a socket to the given address.
+///
+/// Succeeds if the connection was established or is in progress.
+fn start_connect(socket: &socket2::Socket, address: &socket2::SockAddr) -> std::io::Result<()> {
+	match socket.connect(address) {
+		Ok(()) => Ok(()),
+		Err(e) if e.raw_os_error() == Some(libc::EINPROGRESS) => Ok(()),
+		Err(e) => Err(e),
+	}
+}
+
 const SEND_MSG_DEFAULT_FLAGS: std::os::raw::c_int = libc::MSG_NOSIGNAL;
 const RECV_MSG_DEFAULT_FLAGS: std::os::raw::c_int = libc::MSG_NOSIGNAL | libc::MSG_CMSG_CLOEXEC;
 
-fn send_msg(socket: &socket2::Socket, buffer: &[IoSlice], ancillary: &mut SocketAncillary) -> std::io::Result<usize> {
-	ancillary.truncated = false;
-
+fn send_msg(
+	socket: &socket2::Socket,
+	buffer: &[IoSlice],
+	ancillary: &mut SocketAncillary,
+	flags: std::os::raw::c_int,
+) -> std::io::Result<usize> {
 	let control_data = match ancillary.len() {
 		0 => std::ptr::null_mut(),
 		_ => ancillary.buffer.as_mut_ptr() as *mut std::os::raw::c_void,
@@ -256,14 +1794,33 @@ fn send_msg(socket: &socket2::Socket, buffer: &[IoSlice], ancillary: &mut Socket
 			.map_err(|_| std::io::ErrorKind::InvalidInput)?;
 	}
 
-	unsafe { check_returned_size(libc::sendmsg(fd, &header as *const _, SEND_MSG_DEFAULT_FLAGS)) }
+	let size = unsafe { check_returned_size(libc::sendmsg(fd, &header as *const _, SEND_MSG_DEFAULT_FLAGS | flags))? };
+	trace_event!(trace, fd, size, fds = ancillary.fd_count(), "sent message");
+	record_metric!(record_sent(fd, size, ancillary.fd_count()));
+	Ok(size)
 }
 
 fn recv_msg(
 	socket: &socket2::Socket,
 	buffer: &mut [IoSliceMut],
 	ancillary: &mut SocketAncillary,
+	flags: std::os::raw::c_int,
 ) -> std::io::Result<usize> {
+	let (size, _msg_flags) = recv_msg_with_flags(socket, buffer, ancillary, flags)?;
+	Ok(size)
+}
+
+/// Receive a message and return the size along with the `msg_flags` reported by the kernel.
+fn recv_msg_with_flags(
+	socket: &socket2::Socket,
+	buffer: &mut [IoSliceMut],
+	ancillary: &mut SocketAncillary,
+	flags: std::os::raw::c_int,
+) -> std::io::Result<(usize, std::os::raw::c_int)> {
+	// The buffer is overwritten by the kernel, but only if the receive succeeds.
+	// File descriptors from a previous message are closed afterwards, so a failed receive leaves them intact.
+	let previous_fds = ancillary.unclaimed_fds();
+
 	let control_data = match ancillary.capacity() {
 		0 => std::ptr::null_mut(),
 		_ => ancillary.buffer.as_mut_ptr() as *mut std::os::raw::c_void,
@@ -289,10 +1846,120 @@ fn recv_msg(
 			.map_err(|_| std::io::ErrorKind::InvalidInput)?;
 	}
 
-	let size = unsafe { check_returned_size(libc::recvmsg(fd, &mut header as *mut _, RECV_MSG_DEFAULT_FLAGS))? };
-	ancillary.truncated = header.msg_flags & libc::MSG_CTRUNC != 0;
-	ancillary.length = header.msg_controllen as usize;
-	Ok(size)
+	let flags = RECV_MSG_DEFAULT_FLAGS | flags;
+	let size = unsafe { check_returned_size(libc::recvmsg(fd, &mut header as *mut _, flags))? };
+	ancillary.set_received(header.msg_controllen as usize, header.msg_flags & libc::MSG_CTRUNC != 0, previous_fds);
+	trace_event!(
+		trace,
+		fd,
+		size,
+		fds = ancillary.fd_count(),
+		truncated = header.msg_flags & libc::MSG_TRUNC != 0,
+		ancillary_truncated = ancillary.truncated,
+		"received message"
+	);
+	// Peeked messages are counted when they are actually received.
+	#[cfg(feature = "metrics")]
+	if flags & libc::MSG_PEEK == 0 {
+		let truncated = header.msg_flags & libc::MSG_TRUNC != 0;
+		let capacity: usize = buffer.iter().map(|x| x.len()).sum();
+		crate::metrics::record_received(fd, size.min(capacity), ancillary.fd_count(), truncated, ancillary.truncated);
+	}
+	Ok((size, header.msg_flags))
+}
+
+/// Send multiple messages with a single `sendmmsg` call.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn send_mmsg(socket: &socket2::Socket, messages: &[&[u8]]) -> std::io::Result<usize> {
+	// The kernel processes at most UIO_MAXIOV messages per call.
+	let messages = &messages[..messages.len().min(1024)];
+	let mut iovecs: Vec<libc::iovec> = messages
+		.iter()
+		.map(|message| libc::iovec {
+			iov_base: message.as_ptr() as *mut std::os::raw::c_void,
+			iov_len: message.len(),
+		})
+		.collect();
+	let mut headers: Vec<libc::mmsghdr> = iovecs
+		.iter_mut()
+		.map(|iovec| {
+			let mut header: libc::mmsghdr = unsafe { std::mem::zeroed() };
+			header.msg_hdr.msg_iov = iovec;
+			header.msg_hdr.msg_iovlen = 1;
+			header
+		})
+		.collect();
+
+	let fd = socket.as_raw_fd();
+	let count = headers.len() as std::os::raw::c_uint;
+	let ret = unsafe { libc::sendmmsg(fd, headers.as_mut_ptr(), count, SEND_MSG_DEFAULT_FLAGS as _) };
+	let sent = check_returned_size(ret as isize)?;
+	trace_event!(trace, fd, messages = sent, "sent batch of messages");
+	#[cfg(feature = "metrics")]
+	for message in &messages[..sent] {
+		crate::metrics::record_sent(fd, message.len(), 0);
+	}
+	Ok(sent)
+}
+
+/// Receive multiple messages with a single `recvmmsg` call.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn recv_mmsg(socket: &socket2::Socket, slots: &mut [RecvSlot]) -> std::io::Result<usize> {
+	// The kernel processes at most UIO_MAXIOV messages per call.
+	let slot_count = slots.len().min(1024);
+	let slots = &mut slots[..slot_count];
+	let mut iovecs: Vec<libc::iovec> = slots
+		.iter_mut()
+		.map(|slot| libc::iovec {
+			iov_base: slot.buffer.as_mut_ptr() as *mut std::os::raw::c_void,
+			iov_len: slot.buffer.len(),
+		})
+		.collect();
+	let mut headers: Vec<libc::mmsghdr> = iovecs
+		.iter_mut()
+		.map(|iovec| {
+			let mut header: libc::mmsghdr = unsafe { std::mem::zeroed() };
+			header.msg_hdr.msg_iov = iovec;
+			header.msg_hdr.msg_iovlen = 1;
+			header
+		})
+		.collect();
+
+	let fd = socket.as_raw_fd();
+	let count = headers.len() as std::os::raw::c_uint;
+	let flags = RECV_MSG_DEFAULT_FLAGS as _;
+	let ret = unsafe { libc::recvmmsg(fd, headers.as_mut_ptr(), count, flags, std::ptr::null_mut()) };
+	let received = check_returned_size(ret as isize)?;
+	for (slot, header) in slots.iter_mut().zip(&headers).take(received) {
+		slot.received = header.msg_len as usize;
+		slot.truncated = header.msg_hdr.msg_flags & libc::MSG_TRUNC != 0;
+		record_metric!(record_received(fd, slot.received, 0, slot.truncated, false));
+	}
+	trace_event!(trace, fd, messages = received, "received batch of messages");
+	Ok(received)
+}
+
+/// Receive a message into possibly uninitialized memory.
+///
+/// The kernel only writes to the buffer, so it never reads the uninitialized bytes.
+fn recv_uninit(
+	socket: &socket2::Socket,
+	buffer: &mut [MaybeUninit<u8>],
+	flags: std::os::raw::c_int,
+) -> std::io::Result<(usize, std::os::raw::c_int)> {
+	let fd = socket.as_raw_fd();
+	let mut iovec = libc::iovec {
+		iov_base: buffer.as_mut_ptr().cast(),
+		iov_len: buffer.len(),
+	};
+	let mut header: libc::msghdr = unsafe { std::mem::zeroed() };
+	header.msg_iov = &mut iovec;
+	header.msg_iovlen = 1;
+	let flags = RECV_MSG_DEFAULT_FLAGS | flags;
+	let size = unsafe { check_returned_size(libc::recvmsg(fd, &mut header, flags))? };
+	trace_event!(trace, fd, size, truncated = header.msg_flags & libc::MSG_TRUNC != 0, "received message");
+	record_metric!(record_received(fd, size.min(buffer.len()), 0, header.msg_flags & libc::MSG_TRUNC != 0, false));
+	Ok((size, header.msg_flags))
 }
 
 fn check_returned_size(ret: isize) -> std::io::Result<usize> {
@@ -310,8 +1977,39 @@ pub(crate) fn poll_send(socket: &UnixSeqpacket, cx: &mut Context, buffer: &[u8])
 	match socket.io.get_ref().send(buffer) {
 		Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
 			ready_guard.clear_ready();
+			record_metric!(record_would_block(socket.as_raw_fd()));
 			Poll::Pending
 		},
+		Ok(size) => {
+			trace_event!(trace, fd = socket.as_raw_fd(), size, "sent message");
+			record_metric!(record_sent(socket.as_raw_fd(), size, 0));
+			socket.record_sent(size);
+			Poll::Ready(Ok(size))
+		},
+		x => Poll::Ready(x),
+	}
+}
+
+/// Send data on the socket to the connected peer without blocking, with extra flags.
+pub(crate) fn poll_send_with_flags(
+	socket: &UnixSeqpacket,
+	cx: &mut Context,
+	buffer: &[u8],
+	flags: std::os::raw::c_int,
+) -> Poll<std::io::Result<usize>> {
+	let mut ready_guard = ready!(socket.io.poll_write_ready(cx)?);
+
+	let buffer = [IoSlice::new(buffer)];
+	match send_msg(socket.io.get_ref(), &buffer, &mut SocketAncillary::new(&mut []), flags) {
+		Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+			ready_guard.clear_ready();
+			record_metric!(record_would_block(socket.as_raw_fd()));
+			Poll::Pending
+		},
+		Ok(size) => {
+			socket.record_sent(size);
+			Poll::Ready(Ok(size))
+		},
 		x => Poll::Ready(x),
 	}
 }
@@ -334,25 +2032,97 @@ pub(crate) fn poll_send_vectored_with_ancillary(
 ) -> Poll<std::io::Result<usize>> {
 	let mut ready_guard = ready!(socket.io.poll_write_ready(cx)?);
 
-	match send_msg(socket.io.get_ref(), buffer, ancillary) {
+	match send_msg(socket.io.get_ref(), buffer, ancillary, 0) {
 		Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
 			ready_guard.clear_ready();
+			record_metric!(record_would_block(socket.as_raw_fd()));
 			Poll::Pending
 		},
+		Ok(size) => {
+			socket.record_sent(size);
+			Poll::Ready(Ok(size))
+		},
 		x => Poll::Ready(x),
 	}
 }
 
 /// Receive data on the socket from the connected peer without blocking.
 pub(crate) fn poll_recv(socket: &UnixSeqpacket, cx: &mut Context, buffer: &mut [u8]) -> Poll<std::io::Result<usize>> {
+	poll_recv_vectored(socket, cx, &mut [IoSliceMut::new(buffer)])
+}
+
+/// Receive data on the socket from the connected peer without blocking, with extra flags.
+pub(crate) fn poll_recv_with_flags(
+	socket: &UnixSeqpacket,
+	cx: &mut Context,
+	buffer: &mut [u8],
+	flags: std::os::raw::c_int,
+) -> Poll<std::io::Result<usize>> {
 	let mut ready_guard = ready!(socket.io.poll_read_ready(cx)?);
 
-	match socket.io.get_ref().recv(buffer) {
+	let capacity = buffer.len();
+	let mut buffer = [IoSliceMut::new(buffer)];
+	match recv_msg_with_flags(socket.io.get_ref(), &mut buffer, &mut SocketAncillary::new(&mut []), flags) {
 		Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
 			ready_guard.clear_ready();
+			record_metric!(record_would_block(socket.as_raw_fd()));
 			Poll::Pending
 		},
-		x => Poll::Ready(x),
+		Err(e) => Poll::Ready(Err(e)),
+		Ok((size, msg_flags)) => {
+			if flags & libc::MSG_PEEK == 0 {
+				socket.record_received(size.min(capacity), msg_flags);
+			}
+			Poll::Ready(Ok(size))
+		},
+	}
+}
+
+/// Receive data on the socket from the connected peer into uninitialized memory without blocking.
+pub(crate) fn poll_recv_uninit(
+	socket: &UnixSeqpacket,
+	cx: &mut Context,
+	buffer: &mut [MaybeUninit<u8>],
+) -> Poll<std::io::Result<usize>> {
+	let mut ready_guard = ready!(socket.io.poll_read_ready(cx)?);
+
+	match recv_uninit(socket.io.get_ref(), buffer, 0) {
+		Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+			ready_guard.clear_ready();
+			record_metric!(record_would_block(socket.as_raw_fd()));
+			Poll::Pending
+		},
+		Err(e) => Poll::Ready(Err(e)),
+		Ok((size, msg_flags)) => {
+			socket.record_received(size, msg_flags);
+			Poll::Ready(Ok(size))
+		},
+	}
+}
+
+/// Receive data on the socket from the connected peer without blocking, and report truncation.
+pub(crate) fn poll_recv_with_meta(
+	socket: &UnixSeqpacket,
+	cx: &mut Context,
+	buffer: &mut [u8],
+) -> Poll<std::io::Result<RecvMeta>> {
+	let mut ready_guard = ready!(socket.io.poll_read_ready(cx)?);
+
+	let received_max = buffer.len();
+	let mut buffer = [IoSliceMut::new(buffer)];
+	let ancillary = &mut SocketAncillary::new(&mut []);
+	match recv_msg_with_flags(socket.io.get_ref(), &mut buffer, ancillary, libc::MSG_TRUNC) {
+		Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+			ready_guard.clear_ready();
+			record_metric!(record_would_block(socket.as_raw_fd()));
+			Poll::Pending
+		},
+		Err(e) => Poll::Ready(Err(e)),
+		Ok((size, msg_flags)) => {
+			let meta = RecvMeta::new(size, received_max, msg_flags);
+			socket.record_received(meta.received(), msg_flags);
+			Poll::Ready(Ok(meta))
+		},
 	}
 }
 
@@ -374,11 +2144,41 @@ pub(crate) fn poll_recv_vectored_with_ancillary(
 ) -> Poll<std::io::Result<usize>> {
 	let mut ready_guard = ready!(socket.io.poll_read_ready(cx)?);
 
-	match recv_msg(socket.io.get_ref(), buffer, ancillary) {
+	match recv_msg_with_flags(socket.io.get_ref(), buffer, ancillary, 0) {
 		Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
 			ready_guard.clear_ready();
+			record_metric!(record_would_block(socket.as_raw_fd()));
 			Poll::Pending
 		},
-		x => Poll::Ready(x),
+		Err(e) => Poll::Ready(Err(e)),
+		Ok((size, msg_flags)) => {
+			socket.record_received(size, msg_flags);
+			Poll::Ready(Ok(size))
+		},
+	}
+}
+
+/// Receive data and ancillary data on the socket from the connected peer without blocking, reporting the message flags.
+pub(crate) fn poll_recv_vectored_with_ancillary_meta(
+	socket: &UnixSeqpacket,
+	cx: &mut Context,
+	buffer: &mut [IoSliceMut],
+	ancillary: &mut SocketAncillary,
+) -> Poll<std::io::Result<RecvMeta>> {
+	let mut ready_guard = ready!(socket.io.poll_read_ready(cx)?);
+
+	let capacity = buffer.iter().map(|x| x.len()).sum();
+	match recv_msg_with_flags(socket.io.get_ref(), buffer, ancillary, libc::MSG_TRUNC) {
+		Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+			ready_guard.clear_ready();
+			record_metric!(record_would_block(socket.as_raw_fd()));
+			Poll::Pending
+		},
+		Err(e) => Poll::Ready(Err(e)),
+		Ok((size, msg_flags)) => {
+			let meta = RecvMeta::new(size, capacity, msg_flags);
+			socket.record_received(meta.received(), msg_flags);
+			Poll::Ready(Ok(meta))
+		},
 	}
 }
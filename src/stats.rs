@@ -0,0 +1,100 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Cumulative statistics of a socket.
+///
+/// Retrieved with [`UnixSeqpacket::stats()`](crate::UnixSeqpacket::stats)
+/// after statistics were enabled with [`UnixSeqpacket::enable_stats()`](crate::UnixSeqpacket::enable_stats).
+///
+/// Peeking at a message does not count as receiving it.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct SocketStats {
+	/// The number of messages sent.
+	pub messages_sent: u64,
+
+	/// The number of bytes sent.
+	pub bytes_sent: u64,
+
+	/// The number of messages received.
+	pub messages_received: u64,
+
+	/// The number of bytes received into buffers.
+	///
+	/// The discarded part of truncated messages is not included.
+	pub bytes_received: u64,
+
+	/// The number of received messages that were truncated because the buffer was too small.
+	pub messages_truncated: u64,
+
+	/// The time a message was last sent or received, or `None` if no message was sent or received yet.
+	pub last_activity: Option<Instant>,
+}
+
+/// Counters of a socket that can be updated through a shared reference.
+#[derive(Debug)]
+pub(crate) struct StatsTracker {
+	/// The time the tracker was created, used as reference for `last_activity`.
+	created: Instant,
+	messages_sent: AtomicU64,
+	bytes_sent: AtomicU64,
+	messages_received: AtomicU64,
+	bytes_received: AtomicU64,
+	messages_truncated: AtomicU64,
+	/// Nanoseconds since `created` plus one, or zero if there was no activity yet.
+	last_activity: AtomicU64,
+}
+
+impl StatsTracker {
+	/// Create a new tracker with all counters at zero.
+	pub(crate) fn new() -> Self {
+		Self {
+			created: Instant::now(),
+			messages_sent: AtomicU64::new(0),
+			bytes_sent: AtomicU64::new(0),
+			messages_received: AtomicU64::new(0),
+			bytes_received: AtomicU64::new(0),
+			messages_truncated: AtomicU64::new(0),
+			last_activity: AtomicU64::new(0),
+		}
+	}
+
+	/// Record a sent message of `size` bytes.
+	pub(crate) fn record_sent(&self, size: usize) {
+		self.messages_sent.fetch_add(1, Ordering::Relaxed);
+		self.bytes_sent.fetch_add(size as u64, Ordering::Relaxed);
+		self.touch();
+	}
+
+	/// Record a received message of which `size` bytes were written to the buffer.
+	pub(crate) fn record_received(&self, size: usize, truncated: bool) {
+		self.messages_received.fetch_add(1, Ordering::Relaxed);
+		self.bytes_received.fetch_add(size as u64, Ordering::Relaxed);
+		if truncated {
+			self.messages_truncated.fetch_add(1, Ordering::Relaxed);
+		}
+		self.touch();
+	}
+
+	/// Get the current values of the counters.
+	pub(crate) fn snapshot(&self) -> SocketStats {
+		let last_activity = match self.last_activity.load(Ordering::Relaxed) {
+			0 => None,
+			nanos => Some(self.created + Duration::from_nanos(nanos - 1)),
+		};
+		SocketStats {
+			messages_sent: self.messages_sent.load(Ordering::Relaxed),
+			bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+			messages_received: self.messages_received.load(Ordering::Relaxed),
+			bytes_received: self.bytes_received.load(Ordering::Relaxed),
+			messages_truncated: self.messages_truncated.load(Ordering::Relaxed),
+			last_activity,
+		}
+	}
+
+	/// Update the time of the last activity.
+	fn touch(&self) {
+		let nanos = self.created.elapsed().as_nanos().min(u64::MAX as u128 - 1) as u64;
+		self.last_activity.fetch_max(nanos + 1, Ordering::Relaxed);
+	}
+}
@@ -0,0 +1,81 @@
+//! Support for systemd socket activation.
+
+use std::os::unix::io::RawFd;
+use std::sync::Mutex;
+
+/// The first file descriptor passed by systemd.
+const LISTEN_FDS_START: RawFd = 3;
+
+/// File descriptors that have already been taken from the environment.
+static TAKEN: Mutex<Vec<RawFd>> = Mutex::new(Vec::new());
+
+/// Take ownership of the file descriptor passed by systemd with the given name.
+///
+/// Each file descriptor can only be taken once.
+/// Later attempts to take the same file descriptor result in an error.
+pub(crate) fn take_listen_fd(name: &str) -> std::io::Result<socket2::Socket> {
+	use std::os::unix::io::FromRawFd;
+
+	let fd = find_listen_fd(name)?;
+
+	let mut taken = TAKEN.lock().unwrap_or_else(|e| e.into_inner());
+	if taken.contains(&fd) {
+		return Err(std::io::Error::new(
+			std::io::ErrorKind::AlreadyExists,
+			format!("file descriptor {} for socket {:?} has already been taken", fd, name),
+		));
+	}
+
+	crate::check_socket_type(fd, libc::SOCK_SEQPACKET)?;
+	if !crate::is_listening(fd)? {
+		return Err(std::io::Error::new(
+			std::io::ErrorKind::InvalidInput,
+			format!("file descriptor {} for socket {:?} is not a listening socket", fd, name),
+		));
+	}
+	crate::set_cloexec(fd, true)?;
+
+	taken.push(fd);
+	let socket = unsafe { socket2::Socket::from_raw_fd(fd) };
+	socket.set_nonblocking(true)?;
+	Ok(socket)
+}
+
+/// Find the file descriptor passed by systemd with the given name.
+fn find_listen_fd(name: &str) -> std::io::Result<RawFd> {
+	if let Some(pid) = std::env::var_os("LISTEN_PID") {
+		let pid: libc::pid_t = pid
+			.to_str()
+			.and_then(|x| x.parse().ok())
+			.ok_or_else(|| invalid_env("LISTEN_PID"))?;
+		if pid != unsafe { libc::getpid() } {
+			return Err(not_found("LISTEN_PID does not match the current process"));
+		}
+	}
+
+	let count = std::env::var_os("LISTEN_FDS").ok_or_else(|| not_found("LISTEN_FDS is not set"))?;
+	let count: RawFd = count
+		.to_str()
+		.and_then(|x| x.parse().ok())
+		.ok_or_else(|| invalid_env("LISTEN_FDS"))?;
+
+	let names = std::env::var("LISTEN_FDNAMES").unwrap_or_default();
+	let index = names
+		.split(':')
+		.take(count as usize)
+		.position(|x| x == name)
+		.ok_or_else(|| not_found(&format!("no socket named {:?} was passed by systemd", name)))?;
+
+	Ok(LISTEN_FDS_START + index as RawFd)
+}
+
+fn not_found(message: &str) -> std::io::Error {
+	std::io::Error::new(std::io::ErrorKind::NotFound, message)
+}
+
+fn invalid_env(name: &str) -> std::io::Error {
+	std::io::Error::new(
+		std::io::ErrorKind::InvalidData,
+		format!("invalid value for environment variable {}", name),
+	)
+}
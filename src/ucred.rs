@@ -2,7 +2,11 @@ use libc::{gid_t, pid_t, uid_t};
 use std::os::unix::io::AsRawFd;
 
 /// Credentials of a process
+///
+/// The [`Display`](std::fmt::Display) implementation formats the credentials as `pid=<pid> uid=<uid> gid=<gid>`,
+/// leaving out the PID if it is not known.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UCred {
 	/// PID (process ID) of the process
 	pid: Option<pid_t>,
@@ -18,6 +22,12 @@ impl UCred {
 		get_peer_cred(socket)
 	}
 
+	/// Create credentials from the raw values.
+	#[cfg(any(target_os = "android", target_os = "dragonfly", target_os = "freebsd", target_os = "linux"))]
+	pub(crate) fn from_raw(pid: Option<pid_t>, uid: uid_t, gid: gid_t) -> Self {
+		Self { pid, uid, gid }
+	}
+
 	/// Gets UID (user ID) of the process.
 	pub fn uid(&self) -> uid_t {
 		self.uid
@@ -37,6 +47,81 @@ impl UCred {
 	}
 }
 
+impl std::fmt::Display for UCred {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		if let Some(pid) = self.pid {
+			write!(f, "pid={} ", pid)?;
+		}
+		write!(f, "uid={} gid={}", self.uid, self.gid)
+	}
+}
+
+/// Credentials of a process including its supplementary groups.
+///
+/// Retrieved with [`UnixSeqpacket::peer_cred_full()`](crate::UnixSeqpacket::peer_cred_full).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct UCredWithGroups {
+	/// The basic credentials of the process.
+	cred: UCred,
+	/// The supplementary groups of the process.
+	groups: Vec<gid_t>,
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl UCredWithGroups {
+	/// Create credentials with supplementary groups.
+	pub(crate) fn new(cred: UCred, groups: Vec<gid_t>) -> Self {
+		Self { cred, groups }
+	}
+
+	/// Get the basic credentials without the supplementary groups.
+	pub fn ucred(&self) -> UCred {
+		self.cred
+	}
+
+	/// Gets UID (user ID) of the process.
+	pub fn uid(&self) -> uid_t {
+		self.cred.uid
+	}
+
+	/// Gets GID (group ID) of the process.
+	pub fn gid(&self) -> gid_t {
+		self.cred.gid
+	}
+
+	/// Gets PID (process ID) of the process.
+	pub fn pid(&self) -> Option<pid_t> {
+		self.cred.pid
+	}
+
+	/// Gets the supplementary groups of the process.
+	pub fn groups(&self) -> &[gid_t] {
+		&self.groups
+	}
+
+	/// Check if the process is a member of a group, either as primary group or as supplementary group.
+	pub fn in_group(&self, gid: gid_t) -> bool {
+		self.cred.gid == gid || self.groups.contains(&gid)
+	}
+}
+
+/// Read the supplementary groups of a process from `/proc/<pid>/status`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn read_proc_groups(pid: pid_t) -> std::io::Result<Vec<gid_t>> {
+	let path = format!("/proc/{}/status", pid);
+	let status = std::fs::read_to_string(&path)?;
+	let invalid = || std::io::Error::new(std::io::ErrorKind::InvalidData, format!("failed to parse groups from {}", path));
+	let groups = status
+		.lines()
+		.find_map(|line| line.strip_prefix("Groups:"))
+		.ok_or_else(invalid)?;
+	groups
+		.split_ascii_whitespace()
+		.map(|gid| gid.parse().map_err(|_| invalid()))
+		.collect()
+}
+
 #[cfg(any(target_os = "linux", target_os = "android"))]
 fn get_peer_cred<T: AsRawFd>(sock: &T) -> std::io::Result<UCred> {
 	use libc::{c_void, getsockopt, socklen_t, ucred, SOL_SOCKET, SO_PEERCRED};
@@ -0,0 +1,780 @@
+use assert2::{assert, let_assert};
+use std::io::{IoSliceMut, Read, Seek, Write};
+use std::os::unix::io::{AsFd, AsRawFd, FromRawFd, IntoRawFd, RawFd};
+use std::os::unix::net::UnixStream;
+use tokio_seqpacket::ancillary::{AncillaryData, SocketAncillary};
+use tokio_seqpacket::UnixSeqpacket;
+
+/// Ancillary buffer with the alignment required for control messages.
+#[repr(align(8))]
+struct AncillaryBuffer([u8; 128]);
+
+/// Receive a message and collect all received file descriptors.
+async fn recv_with_fds(socket: &UnixSeqpacket, buffer: &mut [u8]) -> (usize, Vec<RawFd>) {
+	let mut ancillary_buffer = AncillaryBuffer([0; 128]);
+	let mut ancillary = SocketAncillary::new(&mut ancillary_buffer.0);
+	let_assert!(Ok(len) = socket.recv_vectored_with_ancillary(&mut [IoSliceMut::new(buffer)], &mut ancillary).await);
+
+	let fds = ancillary.take_fds().into_iter().map(IntoRawFd::into_raw_fd).collect();
+	(len, fds)
+}
+
+/// Create a connected pair of streams to detect when a file descriptor is closed.
+///
+/// Send the first stream as file descriptor and drop the local copy.
+/// The second stream reports end-of-file once every copy of the sent stream has been closed.
+/// Unlike checking the file descriptor number, this is not affected by other threads reusing the number.
+fn close_probe() -> (UnixStream, UnixStream) {
+	let_assert!(Ok((sent, probe)) = UnixStream::pair());
+	assert!(let Ok(()) = probe.set_nonblocking(true));
+	(sent, probe)
+}
+
+/// Check if every copy of the sent end of a close probe has been closed.
+fn is_closed(probe: &UnixStream) -> bool {
+	let mut buffer = [0u8; 16];
+	match (&*probe).read(&mut buffer) {
+		Ok(0) => true,
+		Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => false,
+		other => panic!("unexpected result from close probe: {:?}", other),
+	}
+}
+
+/// Test sending file descriptors without setting up an ancillary buffer.
+#[tokio::test]
+async fn send_fds() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let_assert!(Ok(mut file) = tempfile::tempfile());
+
+	assert!(let Ok(5) = a.send_fds(b"hello", &[file.as_fd()]).await);
+
+	let mut buffer = [0u8; 16];
+	let (len, fds) = recv_with_fds(&b, &mut buffer).await;
+	assert!(len == 5);
+	assert!(&buffer[..5] == b"hello");
+	assert!(fds.len() == 1);
+
+	// The received file descriptor refers to the same file.
+	let mut received = unsafe { std::fs::File::from_raw_fd(fds[0]) };
+	assert!(let Ok(()) = received.write_all(b"written by peer"));
+	let_assert!(Ok(_) = file.rewind());
+	let mut contents = String::new();
+	assert!(let Ok(_) = file.read_to_string(&mut contents));
+	assert!(contents == "written by peer");
+}
+
+/// Test receiving file descriptors as owned file descriptors.
+#[tokio::test]
+async fn recv_fds() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let_assert!(Ok(file_a) = tempfile::tempfile());
+	let_assert!(Ok(file_b) = tempfile::tempfile());
+
+	assert!(let Ok(5) = a.send_fds(b"hello", &[file_a.as_fd(), file_b.as_fd()]).await);
+	assert!(let Ok(5) = a.send(b"world").await);
+
+	let mut buffer = [0u8; 16];
+	let_assert!(Ok((5, fds)) = b.recv_fds(&mut buffer).await);
+	assert!(&buffer[..5] == b"hello");
+	assert!(fds.len() == 2);
+
+	// A message without file descriptors results in an empty list.
+	let_assert!(Ok((5, fds)) = b.recv_fds(&mut buffer).await);
+	assert!(&buffer[..5] == b"world");
+	assert!(fds.is_empty());
+}
+
+/// Test passing a single file descriptor with a message.
+#[tokio::test]
+async fn send_recv_with_fd() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let_assert!(Ok(file) = tempfile::tempfile());
+	let_assert!(Ok(metadata) = file.metadata());
+
+	assert!(let Ok(5) = a.send_with_fd(b"hello", file.as_fd()).await);
+	assert!(let Ok(5) = a.send(b"world").await);
+
+	let mut buffer = [0u8; 16];
+	let_assert!(Ok((5, Some(fd))) = b.recv_with_fd(&mut buffer).await);
+	let_assert!(Ok(received) = fd.into_file());
+	let_assert!(Ok(received_metadata) = received.metadata());
+	{
+		use std::os::unix::fs::MetadataExt;
+		assert!(received_metadata.ino() == metadata.ino());
+	}
+
+	let_assert!(Ok((5, None)) = b.recv_with_fd(&mut buffer).await);
+}
+
+/// Test sending explicit credentials to a peer with SO_PASSCRED enabled.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn send_with_creds() {
+	use tokio_seqpacket::ancillary::SocketCred;
+	use tokio_seqpacket::UnixSeqpacketBuilder;
+
+	let_assert!(Ok((a, b)) = UnixSeqpacketBuilder::new().passcred(true).pair());
+	assert!(let Ok(5) = a.send_with_creds(b"hello", &SocketCred::from_process()).await);
+
+	let mut buffer = [0u8; 16];
+	let mut ancillary_buffer = AncillaryBuffer([0; 128]);
+	let mut ancillary = SocketAncillary::new(&mut ancillary_buffer.0);
+	let_assert!(Ok(5) = b.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary).await);
+
+	let mut creds = Vec::new();
+	for message in ancillary.messages() {
+		if let Ok(AncillaryData::ScmCredentials(received)) = message {
+			creds.extend(received);
+		}
+	}
+	assert!(creds.len() == 1);
+	assert!(creds[0].get_pid() == std::process::id() as i32);
+	assert!(creds[0].get_uid() == unsafe { libc::getuid() });
+	assert!(creds[0].get_gid() == unsafe { libc::getgid() });
+}
+
+/// Test sending credentials and file descriptors in the same message.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn send_creds_with_fds() {
+	use std::io::IoSlice;
+	use tokio_seqpacket::ancillary::SocketCred;
+	use tokio_seqpacket::UnixSeqpacketBuilder;
+
+	let_assert!(Ok((a, b)) = UnixSeqpacketBuilder::new().passcred(true).pair());
+	let_assert!(Ok(file) = tempfile::tempfile());
+
+	let mut ancillary = SocketAncillary::with_capacity(0);
+	assert!(ancillary.add_fds(&[file.as_raw_fd()]));
+	assert!(ancillary.add_creds(&[SocketCred::from_process()]));
+	assert!(let Ok(5) = a.send_vectored_with_ancillary(&[IoSlice::new(b"hello")], &mut ancillary).await);
+
+	let mut buffer = [0u8; 16];
+	let mut ancillary_buffer = AncillaryBuffer([0; 128]);
+	let mut ancillary = SocketAncillary::new(&mut ancillary_buffer.0);
+	let_assert!(Ok(5) = b.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary).await);
+	assert!(!ancillary.truncated());
+
+	let mut fds = Vec::new();
+	let mut creds = Vec::new();
+	for message in ancillary.messages() {
+		match message {
+			Ok(AncillaryData::ScmRights(received)) => fds.extend(received),
+			Ok(AncillaryData::ScmCredentials(received)) => creds.extend(received),
+			_ => (),
+		}
+	}
+	assert!(fds.len() == 1);
+	assert!(creds.len() == 1);
+	assert!(creds[0].get_pid() == std::process::id() as i32);
+}
+
+/// Test decoding received credentials into `UCred`.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn recv_creds_as_ucred() {
+	use tokio_seqpacket::UnixSeqpacketBuilder;
+
+	let_assert!(Ok((a, b)) = UnixSeqpacketBuilder::new().passcred(true).pair());
+	assert!(let Ok(5) = a.send(b"hello").await);
+
+	let mut buffer = [0u8; 16];
+	let mut ancillary_buffer = AncillaryBuffer([0; 128]);
+	let mut ancillary = SocketAncillary::new(&mut ancillary_buffer.0);
+	let_assert!(Ok(5) = b.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary).await);
+
+	let mut creds = Vec::new();
+	for message in ancillary.messages() {
+		if let Ok(AncillaryData::ScmCredentials(received)) = message {
+			creds.extend(received.ucreds());
+		}
+	}
+	assert!(creds.len() == 1);
+	let_assert!(Ok(peer) = b.peer_cred());
+	assert!(creds[0] == peer);
+}
+
+/// Test receiving the credentials of the sender with a message.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn recv_with_creds() {
+	use tokio_seqpacket::UnixSeqpacketBuilder;
+
+	let_assert!(Ok((a, b)) = UnixSeqpacketBuilder::new().passcred(true).pair());
+	assert!(let Ok(5) = a.send(b"hello").await);
+
+	let mut buffer = [0u8; 16];
+	let_assert!(Ok((5, Some(creds))) = b.recv_with_creds(&mut buffer).await);
+	assert!(creds.pid() == Some(std::process::id() as i32));
+	assert!(creds.uid() == unsafe { libc::getuid() });
+
+	// Without SO_PASSCRED, there are no credentials.
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	assert!(let Ok(5) = a.send(b"hello").await);
+	let_assert!(Ok((5, None)) = b.recv_with_creds(&mut buffer).await);
+}
+
+/// Test enabling SO_PASSCRED on a connected socket.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn set_passcred() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let mut buffer = [0u8; 16];
+	assert!(let Ok(false) = b.passcred());
+
+	assert!(let Ok(()) = b.set_passcred(true));
+	assert!(let Ok(true) = b.passcred());
+	assert!(let Ok(5) = a.send(b"hello").await);
+	let_assert!(Ok((5, Some(creds))) = b.recv_with_creds(&mut buffer).await);
+	assert!(creds.pid() == Some(std::process::id() as i32));
+
+	assert!(let Ok(()) = b.set_passcred(false));
+	assert!(let Ok(false) = b.passcred());
+	assert!(let Ok(5) = a.send(b"hello").await);
+	let_assert!(Ok((5, None)) = b.recv_with_creds(&mut buffer).await);
+}
+
+/// Test that the message flags report truncation of both the data and the ancillary data.
+#[tokio::test]
+async fn recv_meta_truncated() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let_assert!(Ok(file) = tempfile::tempfile());
+	assert!(let Ok(11) = a.send_fds(b"hello world", &[file.as_fd(), file.as_fd(), file.as_fd()]).await);
+
+	let mut buffer = [0u8; 5];
+	let mut ancillary_buffer = AncillaryBuffer([0; 128]);
+	// Room for the header and two file descriptors only.
+	let mut ancillary = SocketAncillary::new(&mut ancillary_buffer.0[..24]);
+	let mut data = [IoSliceMut::new(&mut buffer)];
+	let_assert!(Ok(meta) = b.recv_vectored_with_ancillary_meta(&mut data, &mut ancillary).await);
+	assert!(meta.size() == 11);
+	assert!(meta.received() == 5);
+	assert!(meta.truncated());
+	assert!(meta.ancillary_truncated());
+	assert!(meta.flags() & libc::MSG_CTRUNC != 0);
+	assert!(&buffer == b"hello");
+}
+
+/// Test that file descriptors from owned messages are closed when the iterator is dropped.
+#[tokio::test]
+async fn into_messages_closes_unclaimed() {
+	use tokio_seqpacket::ancillary::OwnedAncillaryData;
+
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let_assert!(Ok(file) = tempfile::tempfile());
+	let (sent_a, probe_a) = close_probe();
+	let (sent_b, probe_b) = close_probe();
+	assert!(let Ok(5) = a.send_fds(b"hello", &[sent_a.as_fd()]).await);
+	assert!(let Ok(5) = a.send_fds(b"world", &[sent_b.as_fd()]).await);
+	drop(sent_a);
+	drop(sent_b);
+
+	let mut buffer = [0u8; 16];
+	let mut ancillary_buffer = AncillaryBuffer([0; 128]);
+	let mut ancillary = SocketAncillary::new(&mut ancillary_buffer.0);
+	let mut data = [IoSliceMut::new(&mut buffer)];
+	assert!(let Ok(5) = b.recv_vectored_with_ancillary(&mut data, &mut ancillary).await);
+	let mut messages = ancillary.into_messages();
+	let_assert!(Some(Ok(OwnedAncillaryData::ScmRights(fds))) = messages.next());
+	assert!(fds.len() == 1);
+	drop(messages);
+	assert!(!is_closed(&probe_a));
+	drop(fds);
+	assert!(is_closed(&probe_a));
+
+	// Unclaimed file descriptors are closed when the iterator is dropped.
+	let mut ancillary_buffer = AncillaryBuffer([0; 128]);
+	let mut ancillary = SocketAncillary::new(&mut ancillary_buffer.0);
+	let mut data = [IoSliceMut::new(&mut buffer)];
+	assert!(let Ok(5) = b.recv_vectored_with_ancillary(&mut data, &mut ancillary).await);
+	assert!(!is_closed(&probe_b));
+	drop(ancillary.into_messages());
+	assert!(is_closed(&probe_b));
+
+	// Ancillary data that was not received does not own file descriptors.
+	let mut ancillary_buffer = AncillaryBuffer([0; 128]);
+	let mut ancillary = SocketAncillary::new(&mut ancillary_buffer.0);
+	assert!(ancillary.add_fds(&[file.as_raw_fd()]));
+	assert!(ancillary.into_messages().count() == 0);
+	assert!(unsafe { libc::fcntl(file.as_raw_fd(), libc::F_GETFD) } != -1);
+}
+
+/// Test that heap allocated ancillary buffers grow when adding control messages.
+#[tokio::test]
+async fn heap_ancillary() {
+	use std::io::IoSlice;
+	use tokio_seqpacket::ancillary::OwnedAncillaryData;
+
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let_assert!(Ok(file) = tempfile::tempfile());
+
+	let mut ancillary = SocketAncillary::with_capacity(0);
+	assert!(ancillary.capacity() == 0);
+	assert!(ancillary.add_fds(&[file.as_raw_fd(), file.as_raw_fd()]));
+	assert!(ancillary.capacity() >= ancillary.len());
+	assert!(let Ok(5) = a.send_vectored_with_ancillary(&[IoSlice::new(b"hello")], &mut ancillary).await);
+
+	let mut buffer = [0u8; 16];
+	let mut ancillary = SocketAncillary::with_capacity(0);
+	ancillary.reserve(128);
+	assert!(ancillary.capacity() >= 128);
+	let mut data = [IoSliceMut::new(&mut buffer)];
+	assert!(let Ok(5) = b.recv_vectored_with_ancillary(&mut data, &mut ancillary).await);
+	assert!(!ancillary.truncated());
+	let_assert!(Some(Ok(OwnedAncillaryData::ScmRights(fds))) = ancillary.into_messages().next());
+	assert!(fds.len() == 2);
+}
+
+/// Test that SO_PASSSEC can be enabled and security labels are parsed if the kernel sends them.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn passsec() {
+	use std::os::unix::ffi::OsStrExt;
+	use tokio_seqpacket::UnixSeqpacketBuilder;
+
+	let_assert!(Ok((a, b)) = UnixSeqpacketBuilder::new().passsec(true).pair());
+	let mut value: libc::c_int = 0;
+	let mut len = std::mem::size_of_val(&value) as libc::socklen_t;
+	let ret = unsafe {
+		libc::getsockopt(
+			b.as_raw_fd(),
+			libc::SOL_SOCKET,
+			34, // SO_PASSSEC
+			&mut value as *mut _ as *mut libc::c_void,
+			&mut len,
+		)
+	};
+	assert!(ret == 0);
+	assert!(value == 1);
+
+	assert!(let Ok(5) = a.send(b"hello").await);
+	let mut buffer = [0u8; 16];
+	let mut ancillary_buffer = AncillaryBuffer([0; 128]);
+	let mut ancillary = SocketAncillary::new(&mut ancillary_buffer.0);
+	let_assert!(Ok(5) = b.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary).await);
+
+	// Without an active LSM, the kernel does not send a label at all.
+	for message in ancillary.messages() {
+		let_assert!(Ok(AncillaryData::ScmSecurity(label)) = message);
+		assert!(!label.is_empty());
+		assert!(!label.as_bytes().ends_with(&[0]));
+	}
+}
+
+/// Test that the space helpers give enough room for the control messages.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn ancillary_space() {
+	use std::io::IoSlice;
+	use tokio_seqpacket::ancillary::{ancillary_space_for_creds, ancillary_space_for_fds, SocketCred};
+	use tokio_seqpacket::UnixSeqpacketBuilder;
+
+	const SPACE: usize = ancillary_space_for_fds(2) + ancillary_space_for_creds();
+
+	#[repr(align(8))]
+	struct Buffer([u8; SPACE]);
+
+	let_assert!(Ok((a, b)) = UnixSeqpacketBuilder::new().passcred(true).pair());
+	let_assert!(Ok(file) = tempfile::tempfile());
+
+	let mut buffer = Buffer([0; SPACE]);
+	let mut ancillary = SocketAncillary::new(&mut buffer.0);
+	assert!(ancillary.add_fds(&[file.as_raw_fd(), file.as_raw_fd()]));
+	assert!(ancillary.add_creds(&[SocketCred::from_process()]));
+	assert!(ancillary.len() == SPACE);
+	assert!(!ancillary.add_fds(&[file.as_raw_fd()]));
+	assert!(let Ok(5) = a.send_vectored_with_ancillary(&[IoSlice::new(b"hello")], &mut ancillary).await);
+
+	let mut data = [0u8; 16];
+	let mut buffer = Buffer([0; SPACE]);
+	let mut ancillary = SocketAncillary::new(&mut buffer.0);
+	let_assert!(Ok(5) = b.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut data)], &mut ancillary).await);
+	assert!(!ancillary.truncated());
+	for message in ancillary.into_messages() {
+		let_assert!(Ok(_) = message);
+	}
+}
+
+/// Test that received file descriptors are closed unless they are taken.
+#[tokio::test]
+async fn close_unclaimed_fds() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let mut buffer = [0u8; 16];
+	let mut ancillary_buffer = AncillaryBuffer([0; 128]);
+
+	// Unclaimed file descriptors are closed when the ancillary data is dropped.
+	let (sent, probe) = close_probe();
+	assert!(let Ok(5) = a.send_fds(b"hello", &[sent.as_fd()]).await);
+	drop(sent);
+	let mut ancillary = SocketAncillary::new(&mut ancillary_buffer.0);
+	assert!(let Ok(5) = b.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary).await);
+	assert!(!is_closed(&probe));
+	drop(ancillary);
+	assert!(is_closed(&probe));
+
+	// Unclaimed file descriptors are closed when the ancillary data is reused.
+	let (sent, probe) = close_probe();
+	assert!(let Ok(5) = a.send_fds(b"hello", &[sent.as_fd()]).await);
+	assert!(let Ok(5) = a.send(b"world").await);
+	drop(sent);
+	let mut ancillary = SocketAncillary::new(&mut ancillary_buffer.0);
+	assert!(let Ok(5) = b.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary).await);
+	assert!(!is_closed(&probe));
+	assert!(let Ok(5) = b.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary).await);
+	assert!(is_closed(&probe));
+
+	// Taken file descriptors stay open and are no longer reported.
+	let (sent, probe) = close_probe();
+	assert!(let Ok(5) = a.send_fds(b"hello", &[sent.as_fd()]).await);
+	drop(sent);
+	assert!(let Ok(5) = b.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary).await);
+	let fds = ancillary.take_fds();
+	assert!(fds.len() == 1);
+	let_assert!(Some(Ok(AncillaryData::ScmRights(mut rights))) = ancillary.messages().next());
+	assert!(rights.next().is_none());
+	drop(ancillary);
+	assert!(!is_closed(&probe));
+	drop(fds);
+	assert!(is_closed(&probe));
+}
+
+/// Test that file descriptors yielded by `messages()` are owned by the caller.
+#[tokio::test]
+async fn messages_releases_fds() {
+	use std::os::unix::io::OwnedFd;
+
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let (sent, probe) = close_probe();
+	assert!(let Ok(5) = a.send_fds(b"hello", &[sent.as_fd()]).await);
+	drop(sent);
+
+	let mut buffer = [0u8; 16];
+	let mut ancillary_buffer = AncillaryBuffer([0; 128]);
+	let mut ancillary = SocketAncillary::new(&mut ancillary_buffer.0);
+	assert!(let Ok(5) = b.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary).await);
+	let_assert!(Some(Ok(AncillaryData::ScmRights(mut fds))) = ancillary.messages().next());
+	let_assert!(Some(raw_fd) = fds.next());
+	let fd = unsafe { OwnedFd::from_raw_fd(raw_fd) };
+
+	// The file descriptor is not yielded again, not taken and not closed by the ancillary data.
+	let_assert!(Some(Ok(AncillaryData::ScmRights(mut fds))) = ancillary.messages().next());
+	assert!(fds.next().is_none());
+	assert!(ancillary.take_fds().is_empty());
+	drop(ancillary);
+	assert!(!is_closed(&probe));
+	drop(fd);
+	assert!(is_closed(&probe));
+}
+
+/// Test that a failed receive does not close the file descriptors of the previous message.
+#[tokio::test]
+async fn failed_recv_keeps_fds() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let (sent, probe) = close_probe();
+	assert!(let Ok(5) = a.send_fds(b"hello", &[sent.as_fd()]).await);
+	drop(sent);
+
+	let mut buffer = [0u8; 16];
+	let mut ancillary_buffer = AncillaryBuffer([0; 128]);
+	let mut ancillary = SocketAncillary::new(&mut ancillary_buffer.0);
+	assert!(let Ok(5) = b.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary).await);
+	let_assert!(Err(e) = b.try_recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary));
+	assert!(e.kind() == std::io::ErrorKind::WouldBlock);
+	assert!(!is_closed(&probe));
+
+	let fds = ancillary.take_fds();
+	assert!(fds.len() == 1);
+	drop(ancillary);
+	assert!(!is_closed(&probe));
+	drop(fds);
+	assert!(is_closed(&probe));
+}
+
+/// Test that received ancillary data can be forwarded unchanged.
+#[tokio::test]
+async fn forward_received_fds() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let_assert!(Ok((c, d)) = UnixSeqpacket::pair());
+	let (sent, probe) = close_probe();
+
+	assert!(let Ok(5) = a.send_fds(b"hello", &[sent.as_fd()]).await);
+	drop(sent);
+	let mut buffer = [0u8; 16];
+	let mut ancillary_buffer = AncillaryBuffer([0; 128]);
+	let mut ancillary = SocketAncillary::new(&mut ancillary_buffer.0);
+	assert!(let Ok(5) = b.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary).await);
+
+	// Forwarding keeps the received file descriptors owned by the ancillary data.
+	assert!(let Ok(5) = c.send_vectored_with_ancillary(&[std::io::IoSlice::new(&buffer[..5])], &mut ancillary).await);
+	drop(ancillary);
+	assert!(!is_closed(&probe));
+
+	let mut buffer = [0u8; 16];
+	let_assert!(Ok((5, Some(fd))) = d.recv_with_fd(&mut buffer).await);
+	assert!(&buffer[..5] == b"hello");
+	let_assert!(Ok(mut forwarded) = fd.into_std_unix_stream());
+	assert!(let Ok(()) = forwarded.write_all(b"ping"));
+	let mut data = [0u8; 4];
+	assert!(let Ok(4) = (&probe).read(&mut data));
+	assert!(&data == b"ping");
+	drop(forwarded);
+	assert!(is_closed(&probe));
+}
+
+/// Test converting received file descriptors into specific types.
+#[tokio::test]
+async fn received_fd_conversions() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let_assert!(Ok((c, d)) = UnixSeqpacket::pair());
+	let_assert!(Ok((stream, _peer)) = UnixStream::pair());
+	let_assert!(Ok(file) = tempfile::tempfile());
+	let mut buffer = [0u8; 16];
+
+	// A seqpacket socket can be adopted as `UnixSeqpacket`, but not as a file or stream.
+	assert!(let Ok(5) = a.send_fds(b"hello", &[c.as_fd(), c.as_fd(), c.as_fd()]).await);
+	let_assert!(Ok((5, fds)) = b.recv_fds(&mut buffer).await);
+	let mut fds = fds.into_iter();
+	let_assert!(Err(e) = fds.next().unwrap().into_file());
+	assert!(e.kind() == std::io::ErrorKind::InvalidInput);
+	let_assert!(Err(e) = fds.next().unwrap().into_unix_stream());
+	assert!(e.kind() == std::io::ErrorKind::InvalidInput);
+	let_assert!(Ok(received) = fds.next().unwrap().into_seqpacket());
+	assert!(let Ok(5) = received.send(b"world").await);
+	assert!(let Ok(5) = d.recv(&mut buffer).await);
+	assert!(&buffer[..5] == b"world");
+
+	// A unix stream socket can be adopted as tokio `UnixStream`.
+	assert!(let Ok(5) = a.send_with_fd(b"hello", stream.as_fd()).await);
+	let_assert!(Ok((5, Some(fd))) = b.recv_with_fd(&mut buffer).await);
+	let_assert!(Ok(_) = fd.into_unix_stream());
+
+	// A regular file can be converted to a `File`, but not to a socket.
+	assert!(let Ok(5) = a.send_fds(b"hello", &[file.as_fd(), file.as_fd()]).await);
+	let_assert!(Ok((5, fds)) = b.recv_fds(&mut buffer).await);
+	let mut fds = fds.into_iter();
+	let_assert!(Err(e) = fds.next().unwrap().into_seqpacket());
+	assert!(e.kind() == std::io::ErrorKind::InvalidInput);
+	let_assert!(Ok(_) = fds.next().unwrap().into_file());
+}
+
+/// Test accessing control messages that are not decoded by the crate.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn raw_messages() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let enable: libc::c_int = 1;
+	let ret = unsafe {
+		libc::setsockopt(
+			b.as_raw_fd(),
+			libc::SOL_SOCKET,
+			libc::SO_TIMESTAMP,
+			&enable as *const _ as *const libc::c_void,
+			std::mem::size_of_val(&enable) as libc::socklen_t,
+		)
+	};
+	assert!(ret == 0);
+
+	assert!(let Ok(5) = a.send(b"hello").await);
+	let mut buffer = [0u8; 16];
+	let mut ancillary_buffer = AncillaryBuffer([0; 128]);
+	let mut ancillary = SocketAncillary::new(&mut ancillary_buffer.0);
+	let_assert!(Ok(5) = b.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary).await);
+
+	let_assert!(Some(Err(_)) = ancillary.messages().next());
+	let raw: Vec<_> = ancillary.raw_messages().collect();
+	assert!(raw.len() == 1);
+	assert!(raw[0].cmsg_level() == libc::SOL_SOCKET);
+	assert!(raw[0].cmsg_type() == libc::SCM_TIMESTAMP);
+	assert!(raw[0].data().len() == std::mem::size_of::<libc::timeval>());
+}
+
+/// Test that the same ancillary data can be reused for multiple messages.
+#[tokio::test]
+async fn reuse_ancillary() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let_assert!(Ok(file) = tempfile::tempfile());
+
+	assert!(let Ok(5) = a.send_fds(b"hello", &[file.as_fd(), file.as_fd(), file.as_fd()]).await);
+	assert!(let Ok(5) = a.send(b"world").await);
+	assert!(let Ok(5) = a.send_fds(b"hello", &[file.as_fd()]).await);
+
+	// The first message does not fit in the buffer.
+	let mut ancillary = SocketAncillary::with_capacity(tokio_seqpacket::ancillary::ancillary_space_for_fds(1));
+	let capacity = ancillary.capacity();
+	let mut buffer = [0u8; 16];
+	assert!(let Ok(5) = b.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary).await);
+	assert!(ancillary.truncated());
+
+	assert!(let Ok(5) = b.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary).await);
+	assert!(!ancillary.truncated());
+	assert!(ancillary.is_empty());
+
+	assert!(let Ok(5) = b.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary).await);
+	assert!(!ancillary.truncated());
+	assert!(ancillary.take_fds().len() == 1);
+
+	// A failed receive leaves the previous message intact.
+	let len = ancillary.len();
+	let_assert!(Err(_) = b.try_recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary));
+	assert!(ancillary.len() == len);
+	assert!(ancillary.capacity() == capacity);
+
+	// Adding control messages after receiving does not keep the received messages.
+	assert!(let Ok(5) = a.send_fds(b"hello", &[file.as_fd()]).await);
+	assert!(let Ok(5) = b.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary).await);
+	assert!(ancillary.add_fds(&[file.as_raw_fd()]));
+	assert!(ancillary.len() == ancillary.capacity());
+}
+
+/// Test receiving control messages into an uninitialized and unaligned buffer.
+#[tokio::test]
+async fn uninit_ancillary() {
+	use std::mem::MaybeUninit;
+	use tokio_seqpacket::ancillary::ancillary_space_for_fds;
+
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let_assert!(Ok(file) = tempfile::tempfile());
+	assert!(let Ok(5) = a.send_fds(b"hello", &[file.as_fd(), file.as_fd()]).await);
+
+	// Skip the first byte so the buffer is never aligned.
+	let mut ancillary_buffer = [MaybeUninit::<u64>::uninit(); 16];
+	let ancillary_buffer: &mut [MaybeUninit<u8>] = unsafe { std::slice::from_raw_parts_mut(ancillary_buffer.as_mut_ptr().cast(), 128) };
+	let mut ancillary = SocketAncillary::new_uninit(&mut ancillary_buffer[1..]);
+	assert!(ancillary.capacity() < 127);
+	assert!(ancillary.capacity() >= ancillary_space_for_fds(2));
+
+	let mut buffer = [0u8; 16];
+	assert!(let Ok(5) = b.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary).await);
+	assert!(!ancillary.truncated());
+	assert!(ancillary.take_fds().len() == 2);
+}
+
+/// Test enabling SO_PASSSEC on a connected socket.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn set_passsec() {
+	let_assert!(Ok((_a, b)) = UnixSeqpacket::pair());
+	assert!(let Ok(false) = b.passsec());
+	assert!(let Ok(()) = b.set_passsec(true));
+	assert!(let Ok(true) = b.passsec());
+	assert!(let Ok(()) = b.set_passsec(false));
+	assert!(let Ok(false) = b.passsec());
+}
+
+/// Test composing ancillary data with the builder.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn ancillary_builder() {
+	use std::io::IoSlice;
+	use tokio_seqpacket::ancillary::{AncillaryBuilder, SocketCred};
+	use tokio_seqpacket::UnixSeqpacketBuilder;
+
+	let_assert!(Ok((a, b)) = UnixSeqpacketBuilder::new().passcred(true).pair());
+	let_assert!(Ok(file) = tempfile::tempfile());
+
+	let builder = AncillaryBuilder::new()
+		.with_fd(file.as_fd())
+		.with_fds(&[file.as_fd(), file.as_fd()])
+		.with_creds(SocketCred::from_process());
+	let_assert!(Ok(mut ancillary) = builder.build());
+	assert!(ancillary.len() == builder.space());
+	assert!(let Ok(5) = a.send_vectored_with_ancillary(&[IoSlice::new(b"hello")], &mut ancillary).await);
+
+	let mut buffer = [0u8; 16];
+	let mut ancillary_buffer = AncillaryBuffer([0; 128]);
+	let mut ancillary = SocketAncillary::new(&mut ancillary_buffer.0);
+	let_assert!(Ok(5) = b.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary).await);
+	assert!(ancillary.take_fds().len() == 3);
+	assert!(ancillary.messages().any(|message| matches!(message, Ok(AncillaryData::ScmCredentials(_)))));
+
+	// An empty builder gives empty ancillary data.
+	let_assert!(Ok(ancillary) = AncillaryBuilder::new().build());
+	assert!(ancillary.is_empty());
+}
+
+/// Test sending more file descriptors than fit in a single message.
+#[tokio::test]
+async fn send_fds_chunked() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let_assert!(Ok(file_a) = tempfile::tempfile());
+	let_assert!(Ok(file_b) = tempfile::tempfile());
+
+	// Alternate the files so we can check that the order is preserved.
+	let fds: Vec<_> = (0..600).map(|i| if i % 2 == 0 { file_a.as_fd() } else { file_b.as_fd() }).collect();
+	assert!(let Ok(5) = a.send_fds_chunked(b"hello", &fds).await);
+
+	let mut buffer = [0u8; 16];
+	let_assert!(Ok((5, received)) = b.recv_fds_chunked(&mut buffer).await);
+	assert!(&buffer[..5] == b"hello");
+	assert!(received.len() == 600);
+
+	let_assert!(Ok(inode_a) = file_a.metadata().map(|x| std::os::unix::fs::MetadataExt::ino(&x)));
+	for (i, fd) in received.into_iter().enumerate() {
+		let_assert!(Ok(file) = fd.into_file());
+		let_assert!(Ok(metadata) = file.metadata());
+		assert!((std::os::unix::fs::MetadataExt::ino(&metadata) == inode_a) == (i % 2 == 0));
+	}
+
+	// Without file descriptors, a single message is sent.
+	assert!(let Ok(5) = a.send_fds_chunked(b"world", &[]).await);
+	let_assert!(Ok((5, received)) = b.recv_fds_chunked(&mut buffer).await);
+	assert!(&buffer[..5] == b"world");
+	assert!(received.is_empty());
+
+	// A message without a chunk header is rejected.
+	assert!(let Ok(1) = a.send(b"x").await);
+	let_assert!(Err(e) = b.recv_fds_chunked(&mut buffer).await);
+	assert!(e.kind() == std::io::ErrorKind::InvalidData);
+}
+
+/// Test adopting a seqpacket socket that was passed over another socket.
+#[tokio::test]
+async fn from_received_fd() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let_assert!(Ok((c, d)) = UnixSeqpacket::pair());
+	let_assert!(Ok((stream, _peer)) = UnixStream::pair());
+	let mut buffer = [0u8; 16];
+
+	// Pass one end of the connection and close the original handle.
+	assert!(let Ok(5) = a.send_with_fd(b"hello", c.as_fd()).await);
+	drop(c);
+	let_assert!(Ok((5, Some(fd))) = b.recv_with_fd(&mut buffer).await);
+	let_assert!(Ok(received) = UnixSeqpacket::from_received_fd(fd));
+	assert!(let Ok(5) = received.send(b"world").await);
+	assert!(let Ok(5) = d.recv(&mut buffer).await);
+	assert!(&buffer[..5] == b"world");
+
+	// Other socket types are rejected.
+	assert!(let Ok(5) = a.send_with_fd(b"hello", stream.as_fd()).await);
+	let_assert!(Ok((5, Some(fd))) = b.recv_with_fd(&mut buffer).await);
+	let_assert!(Err(e) = UnixSeqpacket::from_received_fd(std::os::unix::io::OwnedFd::from(fd)));
+	assert!(e.kind() == std::io::ErrorKind::InvalidInput);
+}
+
+/// Test the details reported for truncated ancillary data.
+#[tokio::test]
+async fn ancillary_truncation() {
+	use tokio_seqpacket::ancillary::ancillary_space_for_fds;
+	use tokio_seqpacket::AncillaryTruncationReason;
+
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let_assert!(Ok(file) = tempfile::tempfile());
+	let mut buffer = [0u8; 16];
+
+	// Nothing to report if everything fits.
+	assert!(let Ok(5) = a.send_with_fd(b"hello", file.as_fd()).await);
+	let mut ancillary = SocketAncillary::with_capacity(ancillary_space_for_fds(1));
+	let_assert!(Ok(5) = b.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary).await);
+	assert!(ancillary.truncation() == None);
+
+	// Three file descriptors do not fit in a buffer for one.
+	assert!(let Ok(5) = a.send_fds(b"hello", &[file.as_fd(), file.as_fd(), file.as_fd()]).await);
+	let_assert!(Ok(5) = b.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary).await);
+	let_assert!(Some(truncation) = ancillary.truncation());
+	assert!(truncation.reason() == AncillaryTruncationReason::BufferTooSmall);
+	assert!(truncation.capacity() == ancillary.capacity());
+	assert!(truncation.used() == ancillary.len());
+
+	let error = std::io::Error::from(truncation);
+	assert!(error.kind() == std::io::ErrorKind::InvalidData);
+}
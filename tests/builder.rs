@@ -0,0 +1,94 @@
+use assert2::{assert, let_assert};
+use tempfile::tempdir;
+use tokio_seqpacket::UnixSeqpacketBuilder;
+
+/// Test that options from the builder are applied to listeners and connected sockets.
+#[tokio::test]
+async fn builder_connect_bind() {
+	use std::os::unix::fs::PermissionsExt;
+
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	let builder = UnixSeqpacketBuilder::new()
+		.recv_buffer_size(64 * 1024)
+		.backlog(16)
+		.mode(0o600);
+
+	let_assert!(Ok(listener) = builder.bind(&path));
+	assert!(listener.backlog() == Some(16));
+	let_assert!(Ok(metadata) = std::fs::metadata(&path));
+	assert!(metadata.permissions().mode() & 0o777 == 0o600);
+
+	let_assert!(Ok(client) = builder.connect(&path).await);
+	let_assert!(Ok(_) = listener.accept().await);
+
+	let mut value: libc::c_int = 0;
+	let mut len = std::mem::size_of_val(&value) as libc::socklen_t;
+	let ret = unsafe {
+		libc::getsockopt(
+			client.as_raw_fd(),
+			libc::SOL_SOCKET,
+			libc::SO_RCVBUF,
+			&mut value as *mut _ as *mut libc::c_void,
+			&mut len,
+		)
+	};
+	assert!(ret == 0);
+	// Linux doubles the requested value to account for bookkeeping overhead.
+	assert!(value >= 64 * 1024);
+}
+
+/// Test that the close-on-exec flag can be cleared with the builder.
+#[tokio::test]
+async fn builder_cloexec() {
+	use std::os::unix::io::AsRawFd;
+
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	let_assert!(Ok(listener) = UnixSeqpacketBuilder::new().cloexec(false).bind(&path));
+	let flags = unsafe { libc::fcntl(listener.as_raw_fd(), libc::F_GETFD) };
+	assert!(flags & libc::FD_CLOEXEC == 0);
+}
+
+/// Test that the options are applied to both sockets of a pair.
+#[tokio::test]
+async fn builder_pair() {
+	use tokio_seqpacket::UnixSeqpacket;
+
+	let options = UnixSeqpacketBuilder::new().cloexec(false);
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair_with_options(&options));
+	for socket in [&a, &b] {
+		let flags = unsafe { libc::fcntl(socket.as_raw_fd(), libc::F_GETFD) };
+		assert!(flags & libc::FD_CLOEXEC == 0);
+	}
+
+	assert!(let Ok(12) = a.send(b"Hello world!").await);
+	let mut buffer = [0u8; 128];
+	assert!(let Ok(12) = b.recv(&mut buffer).await);
+	assert!(&buffer[..12] == b"Hello world!");
+}
+
+/// Test that autobind gives both listeners and connecting sockets a unique abstract name.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn builder_autobind() {
+	use std::os::linux::net::SocketAddrExt;
+	use tokio_seqpacket::UnixSeqpacketListener;
+
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind_abstract(b""));
+	let_assert!(Ok(listener_addr) = listener.local_addr());
+	let_assert!(Some(listener_name) = listener_addr.as_abstract_name());
+	assert!(!listener_name.is_empty());
+
+	let options = UnixSeqpacketBuilder::new().autobind(true);
+	let_assert!(Ok(client) = options.connect_addr(&listener_addr).await);
+	let_assert!(Ok((peer, _address)) = listener.accept().await);
+
+	let_assert!(Ok(client_addr) = client.local_addr());
+	let_assert!(Some(client_name) = client_addr.as_abstract_name());
+	let_assert!(Ok(peer_addr) = peer.peer_addr());
+	assert!(peer_addr.as_abstract_name() == Some(client_name));
+	assert!(client_name != listener_name);
+}
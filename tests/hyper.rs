@@ -0,0 +1,30 @@
+#![cfg(feature = "hyper")]
+
+use assert2::{assert, let_assert};
+use tempfile::tempdir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio_seqpacket::{UnixSeqpacket, UnixSeqpacketListener};
+
+/// Test that we can accept connections through the hyper `Accept` trait.
+#[tokio::test]
+async fn hyper_accept() {
+	use hyper::server::accept::Accept;
+
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	let_assert!(Ok(mut listener) = UnixSeqpacketListener::bind(&path));
+	let_assert!(Ok(client) = UnixSeqpacket::connect(&path).await);
+
+	let mut listener = std::pin::Pin::new(&mut listener);
+	let_assert!(Some(Ok(mut conn)) = futures::future::poll_fn(|cx| listener.as_mut().poll_accept(cx)).await);
+	assert!(let Ok(()) = conn.write_all(b"Hello!").await);
+
+	let mut buf = [0u8; 128];
+	let_assert!(Ok(len) = client.recv(&mut buf).await);
+	assert!(&buf[..len] == b"Hello!");
+
+	assert!(let Ok(_) = client.send(b"Goodbye!").await);
+	let_assert!(Ok(len) = conn.read(&mut buf).await);
+	assert!(&buf[..len] == b"Goodbye!");
+}
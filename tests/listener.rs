@@ -8,15 +8,15 @@ fn unix_seqpacket_listener() {
 	let dir = tempdir().unwrap();
 	let listener = dir.path().join("listener.sock");
 
-	let mut runtime = tokio::runtime::Builder::new_current_thread()
+	let runtime = tokio::runtime::Builder::new_current_thread()
 		.enable_all()
 		.build()
 		.unwrap();
 	let local = tokio::task::LocalSet::new();
 
-	local.block_on(&mut runtime, async move {
+	local.block_on(&runtime, async move {
 		let server_task = tokio::task::spawn_local({
-			let_assert!(Ok(mut listener) = UnixSeqpacketListener::bind(&listener));
+			let_assert!(Ok(listener) = UnixSeqpacketListener::bind(&listener));
 			async move {
 				for _ in 0..2 {
 					let_assert!(Ok((peer, addr)) = listener.accept().await);
@@ -40,3 +40,372 @@ fn unix_seqpacket_listener() {
 		assert!(let Ok(()) = server_task.await);
 	})
 }
+
+/// Test that we can accept connections using the incoming stream.
+#[tokio::test]
+async fn unix_seqpacket_listener_incoming() {
+	use futures::StreamExt;
+
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind(&path));
+	let_assert!(Ok(client) = UnixSeqpacket::connect(&path).await);
+
+	let mut incoming = listener.incoming();
+	let_assert!(Some(Ok(peer)) = incoming.next().await);
+	assert!(let Ok(_) = peer.send(b"Hello!").await);
+
+	let mut buf = [0u8; 128];
+	let_assert!(Ok(len) = client.recv(&mut buf).await);
+	assert!(&buf[..len] == b"Hello!");
+}
+
+/// Test that we can drive the listener manually with `poll_accept()`.
+#[tokio::test]
+async fn unix_seqpacket_listener_poll_accept() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind(&path));
+	let_assert!(Ok(_client) = UnixSeqpacket::connect(&path).await);
+
+	let listener = &listener;
+	let_assert!(Ok((_peer, addr)) = futures::future::poll_fn(|cx| listener.poll_accept(cx)).await);
+	assert!(let None = addr.as_pathname());
+}
+
+/// Test that the listener remembers the requested backlog.
+#[tokio::test]
+async fn unix_seqpacket_listener_backlog() {
+	let dir = tempdir().unwrap();
+
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind(dir.path().join("a.sock")));
+	assert!(listener.backlog() == Some(UnixSeqpacketListener::DEFAULT_BACKLOG));
+
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind_with_backlog(dir.path().join("b.sock"), 16));
+	assert!(listener.backlog() == Some(16));
+}
+
+/// Test that we can listen and connect in the abstract namespace.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn unix_seqpacket_listener_abstract() {
+	use std::os::linux::net::SocketAddrExt;
+
+	let name = format!("tokio-seqpacket-test-{}", std::process::id());
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind_abstract(name.as_bytes()));
+	let_assert!(Ok(local_addr) = listener.local_addr());
+	assert!(local_addr.as_abstract_name() == Some(name.as_bytes()));
+
+	let_assert!(Ok(client) = UnixSeqpacket::connect_abstract(name.as_bytes()).await);
+	let_assert!(Ok((peer, _addr)) = listener.accept().await);
+	assert!(let Ok(_) = peer.send(b"Hello!").await);
+
+	let mut buf = [0u8; 128];
+	let_assert!(Ok(len) = client.recv(&mut buf).await);
+	assert!(&buf[..len] == b"Hello!");
+}
+
+/// Test that the socket file gets the requested permissions.
+#[tokio::test]
+async fn unix_seqpacket_listener_mode() {
+	use std::os::unix::fs::PermissionsExt;
+
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	let_assert!(Ok(_listener) = UnixSeqpacketListener::bind_with_mode(&path, 0o600));
+	let_assert!(Ok(metadata) = std::fs::metadata(&path));
+	assert!(metadata.permissions().mode() & 0o777 == 0o600);
+}
+
+/// Test that the socket file is removed when requested.
+#[tokio::test]
+async fn unix_seqpacket_listener_unlink_on_drop() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind(&path));
+	assert!(listener.unlink_on_drop() == false);
+	drop(listener);
+	assert!(path.exists());
+
+	std::fs::remove_file(&path).unwrap();
+	let_assert!(Ok(mut listener) = UnixSeqpacketListener::bind(&path));
+	listener.set_unlink_on_drop(true);
+	assert!(listener.unlink_on_drop() == true);
+	drop(listener);
+	assert!(!path.exists());
+}
+
+/// Test that the listener does not remove a socket file that was replaced.
+#[tokio::test]
+async fn unix_seqpacket_listener_unlink_on_drop_replaced() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	let_assert!(Ok(mut listener) = UnixSeqpacketListener::bind(&path));
+	listener.set_unlink_on_drop(true);
+
+	std::fs::remove_file(&path).unwrap();
+	std::fs::write(&path, b"not a socket").unwrap();
+	drop(listener);
+	assert!(path.exists());
+}
+
+/// Test that socket activation fails cleanly without sockets from systemd.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn unix_seqpacket_listener_from_systemd_missing() {
+	let_assert!(Err(e) = UnixSeqpacketListener::from_systemd("tokio-seqpacket-test"));
+	assert!(e.kind() == std::io::ErrorKind::NotFound);
+}
+
+/// Test that we can get the peer credentials when accepting a connection.
+#[tokio::test]
+async fn unix_seqpacket_listener_accept_with_credentials() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind(&path));
+	let_assert!(Ok(_client) = UnixSeqpacket::connect(&path).await);
+	let_assert!(Ok((_peer, cred)) = listener.accept_with_credentials().await);
+	assert!(cred.uid() == unsafe { libc::getuid() });
+	assert!(cred.gid() == unsafe { libc::getgid() });
+}
+
+/// Test that the listener reports the address it is bound to.
+#[tokio::test]
+async fn unix_seqpacket_listener_local_addr() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind(&path));
+	let_assert!(Ok(addr) = listener.local_addr());
+	assert!(addr.as_pathname() == Some(path.as_path()));
+}
+
+/// Test that a fresh listener has no pending error.
+#[tokio::test]
+async fn unix_seqpacket_listener_take_error() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind(&path));
+	assert!(let Ok(None) = listener.take_error());
+}
+
+/// Test getting and setting the buffer sizes of the listener.
+#[tokio::test]
+async fn unix_seqpacket_listener_buffer_size() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind(&path));
+	assert!(let Ok(()) = listener.set_send_buffer_size(64 * 1024));
+	let_assert!(Ok(size) = listener.send_buffer_size());
+	assert!(size >= 64 * 1024);
+	assert!(let Ok(()) = listener.set_recv_buffer_size(64 * 1024));
+	let_assert!(Ok(size) = listener.recv_buffer_size());
+	assert!(size >= 64 * 1024);
+}
+
+/// Test that we can drain pending connections after shutting down the listener.
+#[tokio::test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+async fn unix_seqpacket_listener_shutdown() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind(&path));
+	let_assert!(Ok(client_a) = UnixSeqpacket::connect(&path).await);
+	let_assert!(Ok(_client_b) = UnixSeqpacket::connect(&path).await);
+
+	assert!(format!("{:?}", listener).contains("shutdown: None"));
+	assert!(let Ok(()) = listener.shutdown());
+	assert!(format!("{:?}", listener).contains("shutdown: Some(Read)"));
+	let_assert!(Err(e) = UnixSeqpacket::connect(&path).await);
+	assert!(e.kind() == std::io::ErrorKind::ConnectionRefused);
+
+	let_assert!(Ok(Some((peer, _addr))) = listener.accept_pending());
+	assert!(let Ok(_) = peer.send(b"Hello!").await);
+	let mut buf = [0u8; 128];
+	let_assert!(Ok(len) = client_a.recv(&mut buf).await);
+	assert!(&buf[..len] == b"Hello!");
+
+	assert!(let Ok(1) = listener.reject_pending());
+	assert!(let Ok(None) = listener.accept_pending());
+}
+
+/// Test that the listener waits for a connection to close when the connection limit is reached.
+#[tokio::test]
+async fn unix_seqpacket_listener_max_connections() {
+	use std::time::Duration;
+
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	let_assert!(Ok(mut listener) = UnixSeqpacketListener::bind(&path));
+	listener.set_max_connections(Some(1));
+	assert!(listener.max_connections() == Some(1));
+
+	let_assert!(Ok(_client_a) = UnixSeqpacket::connect(&path).await);
+	let_assert!(Ok(_client_b) = UnixSeqpacket::connect(&path).await);
+
+	let_assert!(Ok((peer_a, _addr)) = listener.accept().await);
+	assert!(listener.connection_count() == Some(1));
+	assert!(let Err(_) = tokio::time::timeout(Duration::from_millis(10), listener.accept()).await);
+
+	drop(peer_a);
+	assert!(listener.connection_count() == Some(0));
+	let_assert!(Ok(Ok((_peer_b, _addr))) = tokio::time::timeout(Duration::from_secs(1), listener.accept()).await);
+	assert!(listener.connection_count() == Some(1));
+}
+
+/// Test that we can bind a listener to an address from `local_addr()`.
+#[tokio::test]
+async fn unix_seqpacket_listener_bind_addr() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind(&path));
+	let_assert!(Ok(addr) = listener.local_addr());
+	drop(listener);
+	std::fs::remove_file(&path).unwrap();
+
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind_addr(&addr));
+	let_assert!(Ok(local_addr) = listener.local_addr());
+	assert!(local_addr.as_pathname() == Some(path.as_path()));
+}
+
+/// Test that binding to an unnamed address makes the kernel choose an abstract name.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn unix_seqpacket_listener_bind_addr_unnamed() {
+	use std::os::linux::net::SocketAddrExt;
+
+	let_assert!(Ok((socket, _other)) = UnixSeqpacket::pair());
+	let_assert!(Ok(unnamed) = socket.local_addr());
+	assert!(unnamed.is_unnamed());
+
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind_addr(&unnamed));
+	let_assert!(Ok(local_addr) = listener.local_addr());
+	assert!(let Some(_) = local_addr.as_abstract_name());
+}
+
+/// Test the file descriptor flags of accepted sockets.
+#[tokio::test]
+async fn unix_seqpacket_listener_accept_cloexec() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	let fd_flags = |fd| unsafe { libc::fcntl(fd, libc::F_GETFD) };
+	let status_flags = |fd| unsafe { libc::fcntl(fd, libc::F_GETFL) };
+
+	let_assert!(Ok(mut listener) = UnixSeqpacketListener::bind(&path));
+	assert!(listener.accept_cloexec() == true);
+
+	let_assert!(Ok(_client) = UnixSeqpacket::connect(&path).await);
+	let_assert!(Ok((peer, _addr)) = listener.accept().await);
+	assert!(fd_flags(peer.as_raw_fd()) & libc::FD_CLOEXEC != 0);
+	assert!(status_flags(peer.as_raw_fd()) & libc::O_NONBLOCK != 0);
+
+	listener.set_accept_cloexec(false);
+	let_assert!(Ok(_client) = UnixSeqpacket::connect(&path).await);
+	let_assert!(Ok((peer, _addr)) = listener.accept().await);
+	assert!(fd_flags(peer.as_raw_fd()) & libc::FD_CLOEXEC == 0);
+	assert!(status_flags(peer.as_raw_fd()) & libc::O_NONBLOCK != 0);
+}
+
+/// Test that we can accept connections from multiple listeners in a listener set.
+#[tokio::test]
+async fn listener_set() {
+	use tokio_seqpacket::ListenerSet;
+
+	let dir = tempdir().unwrap();
+	let path_a = dir.path().join("a.sock");
+	let path_b = dir.path().join("b.sock");
+
+	let mut set = ListenerSet::new();
+	let_assert!(Ok(listener_a) = UnixSeqpacketListener::bind(&path_a));
+	let_assert!(Ok(listener_b) = UnixSeqpacketListener::bind(&path_b));
+	let id_a = set.insert(listener_a);
+	let id_b = set.insert(listener_b);
+	assert!(set.len() == 2);
+
+	// Connect twice to both listeners, and check that they are served in turn.
+	let mut clients = Vec::new();
+	for _ in 0..2 {
+		let_assert!(Ok(client) = UnixSeqpacket::connect(&path_a).await);
+		clients.push(client);
+		let_assert!(Ok(client) = UnixSeqpacket::connect(&path_b).await);
+		clients.push(client);
+	}
+
+	let_assert!(Ok((id, _peer, _addr)) = set.accept().await);
+	assert!(id == id_a);
+	let_assert!(Ok((id, _peer, _addr)) = set.accept().await);
+	assert!(id == id_b);
+	let_assert!(Ok((id, _peer, _addr)) = set.accept().await);
+	assert!(id == id_a);
+
+	assert!(let Some(_) = set.remove(id_a));
+	assert!(let None = set.remove(id_a));
+	let_assert!(Ok((id, _peer, _addr)) = set.accept().await);
+	assert!(id == id_b);
+}
+
+/// Test that a stale socket file is replaced, but a live one is not.
+#[tokio::test]
+async fn unix_seqpacket_listener_bind_replacing_stale() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	// A live listener must not be replaced.
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind(&path));
+	let_assert!(Err(e) = UnixSeqpacketListener::bind_replacing_stale(&path));
+	assert!(e.kind() == std::io::ErrorKind::AddrInUse);
+
+	// After dropping the listener, the socket file is stale and can be replaced.
+	drop(listener);
+	assert!(path.exists());
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind_replacing_stale(&path));
+	let_assert!(Ok(_client) = UnixSeqpacket::connect(&path).await);
+	let_assert!(Ok(_) = listener.accept().await);
+	drop(listener);
+
+	// Regular files must not be removed.
+	std::fs::remove_file(&path).unwrap();
+	std::fs::write(&path, b"not a socket").unwrap();
+	let_assert!(Err(e) = UnixSeqpacketListener::bind_replacing_stale(&path));
+	assert!(e.kind() == std::io::ErrorKind::AddrInUse);
+	assert!(path.exists());
+}
+
+/// Test that we can create a listener before the runtime starts and convert it later.
+#[test]
+fn unix_seqpacket_listener_from_std() {
+	use tokio_seqpacket::blocking;
+
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	let_assert!(Ok(listener) = blocking::UnixSeqpacketListener::bind(&path));
+
+	let runtime = tokio::runtime::Builder::new_current_thread()
+		.enable_all()
+		.build()
+		.unwrap();
+
+	runtime.block_on(async move {
+		let_assert!(Ok(listener) = UnixSeqpacketListener::from_std(listener));
+		assert!(listener.backlog() == Some(UnixSeqpacketListener::DEFAULT_BACKLOG));
+		let_assert!(Ok(_client) = UnixSeqpacket::connect(&path).await);
+		let_assert!(Ok(_) = listener.accept().await);
+
+		let_assert!(Ok(listener) = listener.into_std());
+		let_assert!(Ok(local_addr) = listener.local_addr());
+		assert!(local_addr.as_pathname() == Some(path.as_path()));
+	});
+}
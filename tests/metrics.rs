@@ -0,0 +1,49 @@
+#![cfg(feature = "metrics")]
+
+use assert2::{assert, let_assert};
+use std::io::{IoSlice, IoSliceMut};
+use std::os::unix::io::AsRawFd;
+use tokio_seqpacket::ancillary::SocketAncillary;
+use tokio_seqpacket::metrics::{self, Counters};
+use tokio_seqpacket::UnixSeqpacket;
+
+static COUNTERS: Counters = Counters::new();
+
+/// Test that sending and receiving messages updates the counters of the installed recorder.
+#[tokio::test]
+async fn metrics_counters() {
+	assert!(let Ok(()) = metrics::set_recorder(&COUNTERS));
+	assert!(let Err(_) = metrics::set_recorder(&COUNTERS));
+
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let_assert!(Ok(file) = tempfile::tempfile());
+	let before = COUNTERS.snapshot();
+
+	assert!(let Ok(5) = a.send(b"hello").await);
+	let mut buffer = [0u8; 16];
+	assert!(let Ok(5) = b.peek(&mut buffer).await);
+	assert!(let Ok(5) = b.recv(&mut buffer).await);
+
+	let mut ancillary = SocketAncillary::with_capacity(0);
+	assert!(ancillary.add_fds(&[file.as_raw_fd(), file.as_raw_fd()]));
+	assert!(let Ok(5) = a.send_vectored_with_ancillary(&[IoSlice::new(b"world")], &mut ancillary).await);
+	let mut ancillary = SocketAncillary::with_capacity(128);
+	assert!(let Ok(5) = b.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buffer)], &mut ancillary).await);
+
+	assert!(let Ok(20) = a.send(&[0u8; 20]).await);
+	let_assert!(Ok(meta) = b.recv_with_meta(&mut buffer).await);
+	assert!(meta.truncated());
+
+	assert!(let Ok(1) = a.try_send(b"!"));
+	assert!(let Ok(1) = b.recv(&mut buffer).await);
+
+	let after = COUNTERS.snapshot();
+	assert!(after.messages_sent - before.messages_sent == 4);
+	assert!(after.bytes_sent - before.bytes_sent == 31);
+	assert!(after.fds_sent - before.fds_sent == 2);
+	assert!(after.messages_received - before.messages_received == 4);
+	assert!(after.bytes_received - before.bytes_received == 27);
+	assert!(after.fds_received - before.fds_received == 2);
+	assert!(after.messages_truncated - before.messages_truncated == 1);
+	assert!(after.ancillary_truncated - before.ancillary_truncated == 0);
+}
@@ -0,0 +1,31 @@
+#![cfg(feature = "process")]
+
+use assert2::{assert, let_assert};
+use tokio::process::Command;
+use tokio_seqpacket::process::spawn_with_seqpacket;
+
+/// Test that the child process can use the socket at the requested file descriptor.
+#[tokio::test]
+async fn spawn_child_with_socket() {
+	let mut command = Command::new("sh");
+	command.args(["-c", "printf 'Hello parent!' >&5"]);
+	let_assert!(Ok((mut child, socket)) = spawn_with_seqpacket(&mut command, 5));
+
+	let mut buffer = [0u8; 128];
+	assert!(let Ok(13) = socket.recv(&mut buffer).await);
+	assert!(&buffer[..13] == b"Hello parent!");
+
+	let_assert!(Ok(status) = child.wait().await);
+	assert!(status.success());
+
+	// The child closed its end, so the connection is closed now.
+	assert!(let Ok(0) = socket.recv(&mut buffer).await);
+}
+
+/// Test that a negative file descriptor is rejected.
+#[tokio::test]
+async fn spawn_child_invalid_fd() {
+	let mut command = Command::new("true");
+	let_assert!(Err(e) = spawn_with_seqpacket(&mut command, -1));
+	assert!(e.kind() == std::io::ErrorKind::InvalidInput);
+}
@@ -17,3 +17,106 @@ async fn send_recv() {
 	assert!(let Ok(12) = b.recv(&mut buffer).await);
 	assert!(&buffer[..12] == b"Hello world!");
 }
+
+/// Test converting a listener to and from an owned file descriptor.
+#[tokio::test]
+async fn listener_owned_fd() {
+	use std::convert::TryFrom;
+	use std::os::unix::io::{AsFd, AsRawFd, OwnedFd};
+	use tokio_seqpacket::UnixSeqpacketListener;
+
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind(&path));
+	assert!(listener.as_fd().as_raw_fd() == listener.as_raw_fd());
+
+	// Round-trip through an owned fd.
+	let fd = OwnedFd::from(listener);
+	let_assert!(Ok(listener) = UnixSeqpacketListener::try_from(fd));
+
+	// Check that the listener still works.
+	let_assert!(Ok(client) = UnixSeqpacket::connect(&path).await);
+	let_assert!(Ok((peer, _addr)) = listener.accept().await);
+	assert!(let Ok(12) = peer.send(b"Hello world!").await);
+
+	let mut buffer = [0u8; 128];
+	assert!(let Ok(12) = client.recv(&mut buffer).await);
+	assert!(&buffer[..12] == b"Hello world!");
+}
+
+/// Test that a connected socket can not be used as listener.
+#[tokio::test]
+async fn listener_owned_fd_not_listening() {
+	use std::convert::TryFrom;
+	use std::os::unix::io::{FromRawFd, OwnedFd};
+	use tokio_seqpacket::UnixSeqpacketListener;
+
+	let_assert!(Ok((a, _b)) = UnixSeqpacket::pair());
+	let fd = unsafe { OwnedFd::from_raw_fd(a.into_raw_fd()) };
+	let_assert!(Err(e) = UnixSeqpacketListener::try_from(fd));
+	assert!(e.kind() == std::io::ErrorKind::InvalidInput);
+}
+
+/// Test converting a socket to and from an owned file descriptor.
+#[tokio::test]
+async fn socket_owned_fd() {
+	use std::convert::TryFrom;
+	use std::os::unix::io::{AsFd, AsRawFd, OwnedFd};
+
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	assert!(a.as_fd().as_raw_fd() == a.as_raw_fd());
+
+	// Round-trip through an owned fd.
+	let_assert!(Ok(a) = UnixSeqpacket::try_from(OwnedFd::from(a)));
+	let_assert!(Ok(b) = UnixSeqpacket::try_from(OwnedFd::from(b)));
+
+	// Check that the sockets still work.
+	assert!(let Ok(12) = a.send(b"Hello world!").await);
+
+	let mut buffer = [0u8; 128];
+	assert!(let Ok(12) = b.recv(&mut buffer).await);
+	assert!(&buffer[..12] == b"Hello world!");
+}
+
+/// Test that a listener or a non-seqpacket socket can not be used as socket.
+#[tokio::test]
+async fn socket_owned_fd_wrong_type() {
+	use std::convert::TryFrom;
+	use std::os::unix::io::OwnedFd;
+	use tokio_seqpacket::UnixSeqpacketListener;
+
+	let dir = tempfile::tempdir().unwrap();
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind(dir.path().join("listener.sock")));
+	let_assert!(Err(e) = UnixSeqpacket::try_from(OwnedFd::from(listener)));
+	assert!(e.kind() == std::io::ErrorKind::InvalidInput);
+
+	let_assert!(Ok((stream, _other)) = std::os::unix::net::UnixStream::pair());
+	let_assert!(Err(e) = UnixSeqpacket::try_from(OwnedFd::from(stream)));
+	assert!(e.kind() == std::io::ErrorKind::InvalidInput);
+}
+
+/// Test that adopting a file descriptor checks the type and connection state.
+#[tokio::test]
+async fn socket_try_from_fd() {
+	use std::os::unix::io::OwnedFd;
+
+	let_assert!(Ok((a, _b)) = UnixSeqpacket::pair());
+	let_assert!(Ok(_a) = UnixSeqpacket::try_from_fd(OwnedFd::from(a)));
+
+	let_assert!(Ok((stream, _other)) = std::os::unix::net::UnixStream::pair());
+	let_assert!(Err(e) = UnixSeqpacket::try_from_fd(OwnedFd::from(stream)));
+	assert!(e.kind() == std::io::ErrorKind::InvalidInput);
+	assert!(e.to_string().contains("expected SOCK_SEQPACKET, got SOCK_STREAM"));
+
+	let_assert!(Ok(file) = std::fs::File::open("/dev/null"));
+	let_assert!(Err(e) = UnixSeqpacket::try_from_fd(OwnedFd::from(file)));
+	assert!(e.kind() == std::io::ErrorKind::InvalidInput);
+	assert!(e.to_string().contains("not a socket"));
+
+	let socket = unsafe { libc::socket(libc::AF_UNIX, libc::SOCK_SEQPACKET | libc::SOCK_CLOEXEC, 0) };
+	assert!(socket >= 0);
+	let fd = unsafe { std::os::unix::io::FromRawFd::from_raw_fd(socket) };
+	let_assert!(Err(e) = UnixSeqpacket::try_from_fd(fd));
+	assert!(e.kind() == std::io::ErrorKind::NotConnected);
+}
@@ -0,0 +1,116 @@
+use assert2::{assert, let_assert};
+use std::time::Duration;
+use tempfile::tempdir;
+use tokio_seqpacket::{ReconnectingSeqpacket, UnixSeqpacketListener};
+
+/// Test that the client reconnects when the server closes the connection.
+#[tokio::test]
+async fn reconnect_after_reset() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind(&path));
+
+	let_assert!(Ok(mut client) = ReconnectingSeqpacket::connect(&path).await);
+	let_assert!(Ok((peer, _address)) = listener.accept().await);
+	assert!(let Ok(5) = client.send(b"hello").await);
+	drop(peer);
+
+	// The old connection is gone, so the message must arrive on a new connection.
+	assert!(let Ok(5) = client.send(b"world").await);
+	let_assert!(Ok((peer, _address)) = listener.accept().await);
+	let mut buffer = [0u8; 16];
+	assert!(let Ok(5) = peer.recv(&mut buffer).await);
+	assert!(&buffer[..5] == b"world");
+}
+
+/// Test that an empty message on a live connection is received without reconnecting.
+#[tokio::test]
+async fn recv_empty_message() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind(&path));
+
+	let_assert!(Ok(mut client) = ReconnectingSeqpacket::connect(&path).await);
+	let_assert!(Ok((peer, _address)) = listener.accept().await);
+	assert!(let Ok(0) = peer.send(b"").await);
+	assert!(let Ok(5) = peer.send(b"hello").await);
+
+	let mut buffer = [0u8; 16];
+	assert!(let Ok(0) = client.recv(&mut buffer).await);
+	assert!(let Ok(5) = client.recv(&mut buffer).await);
+	assert!(&buffer[..5] == b"hello");
+}
+
+/// Test that receiving reconnects when the server is restarted.
+#[tokio::test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+async fn recv_reconnects_after_server_restart() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind(&path));
+
+	let_assert!(Ok(mut client) = ReconnectingSeqpacket::connect(&path).await);
+	let_assert!(Ok((peer, _address)) = listener.accept().await);
+	assert!(let Ok(5) = peer.send(b"hello").await);
+	let mut buffer = [0u8; 16];
+	assert!(let Ok(5) = client.recv(&mut buffer).await);
+	assert!(&buffer[..5] == b"hello");
+
+	// Restart the server: the client sees end-of-file on the old connection.
+	drop(peer);
+	drop(listener);
+	std::fs::remove_file(&path).unwrap();
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind(&path));
+	let server = tokio::spawn(async move {
+		let (peer, _address) = listener.accept().await.unwrap();
+		peer.send(b"world").await.unwrap();
+		peer
+	});
+
+	assert!(let Ok(5) = client.recv(&mut buffer).await);
+	assert!(&buffer[..5] == b"world");
+	let_assert!(Ok(_peer) = server.await);
+}
+
+/// Test that reconnecting gives up after the maximum number of attempts.
+#[tokio::test]
+async fn reconnect_max_attempts() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("missing.sock");
+
+	let mut client = ReconnectingSeqpacket::new(&path);
+	client.set_initial_backoff(Duration::from_millis(1));
+	client.set_jitter(0.5);
+	client.set_max_attempts(Some(3));
+	assert!(!client.is_connected());
+	let_assert!(Err(e) = client.send(b"hello").await);
+	assert!(e.kind() == std::io::ErrorKind::NotFound);
+	assert!(!client.is_connected());
+}
+
+/// Test that the client keeps retrying until the server becomes available.
+#[tokio::test]
+async fn reconnect_waits_for_server() {
+	let dir = tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	let mut client = ReconnectingSeqpacket::new(&path);
+	client.set_initial_backoff(Duration::from_millis(5));
+	client.set_max_backoff(Duration::from_millis(20));
+
+	let server = tokio::spawn({
+		let path = path.clone();
+		async move {
+			tokio::time::sleep(Duration::from_millis(50)).await;
+			let listener = UnixSeqpacketListener::bind(&path).unwrap();
+			let (peer, _address) = listener.accept().await.unwrap();
+			let mut buffer = [0u8; 16];
+			let len = peer.recv(&mut buffer).await.unwrap();
+			buffer[..len].to_vec()
+		}
+	});
+
+	assert!(let Ok(5) = client.send(b"hello").await);
+	let_assert!(Ok(message) = server.await);
+	assert!(message == b"hello");
+}
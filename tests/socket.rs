@@ -17,13 +17,13 @@ async fn send_recv() {
 fn send_recv_out_of_order() {
 	use std::sync::atomic::{AtomicBool, Ordering};
 
-	let mut runtime = tokio::runtime::Builder::new_current_thread()
+	let runtime = tokio::runtime::Builder::new_current_thread()
 		.enable_all()
 		.build()
 		.unwrap();
 	let local = tokio::task::LocalSet::new();
 
-	local.block_on(&mut runtime, async {
+	local.block_on(&runtime, async {
 		// Atomic bools to verify things happen in the order we want.
 		// We're using a local task set to ensure we're single threaded.
 		static ABOUT_TO_READ: AtomicBool = AtomicBool::new(false);
@@ -75,3 +75,1007 @@ async fn send_recv_vectored() {
 	assert!(&world == b"world");
 	assert!(&punct == b"!");
 }
+
+/// Test that we can connect to an address from `local_addr()`.
+#[tokio::test]
+async fn connect_addr() {
+	use tokio_seqpacket::UnixSeqpacketListener;
+
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind(&path));
+	let_assert!(Ok(addr) = listener.local_addr());
+	let_assert!(Ok(client) = UnixSeqpacket::connect_addr(&addr).await);
+	let_assert!(Ok(_) = listener.accept().await);
+
+	// Connecting to an unnamed address is not possible.
+	let_assert!(Ok(unnamed) = client.local_addr());
+	let_assert!(Err(e) = UnixSeqpacket::connect_addr(&unnamed).await);
+	assert!(e.kind() == std::io::ErrorKind::InvalidInput);
+}
+
+/// Test that we can connect to an autobound abstract address.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn connect_addr_abstract() {
+	use tokio_seqpacket::UnixSeqpacketListener;
+
+	let_assert!(Ok((socket, _other)) = UnixSeqpacket::pair());
+	let_assert!(Ok(unnamed) = socket.local_addr());
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind_addr(&unnamed));
+	let_assert!(Ok(addr) = listener.local_addr());
+	let_assert!(Ok(_client) = UnixSeqpacket::connect_addr(&addr).await);
+	let_assert!(Ok(_) = listener.accept().await);
+}
+
+/// Test that we can connect with a timeout.
+#[tokio::test]
+async fn connect_timeout() {
+	use std::time::Duration;
+	use tokio_seqpacket::UnixSeqpacketListener;
+
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind(&path));
+	let_assert!(Ok(_client) = UnixSeqpacket::connect_timeout(&path, Duration::from_secs(1)).await);
+	let_assert!(Ok(_) = listener.accept().await);
+
+	// Connecting to a non-existing socket reports the real error.
+	let_assert!(Err(e) = UnixSeqpacket::connect_timeout(dir.path().join("missing.sock"), Duration::from_secs(1)).await);
+	assert!(e.kind() == std::io::ErrorKind::NotFound);
+}
+
+/// Test that a connection attempt to a listener with a full backlog times out.
+#[tokio::test]
+async fn connect_timeout_full_backlog() {
+	use std::time::Duration;
+	use tokio_seqpacket::UnixSeqpacketListener;
+
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	let_assert!(Ok(_listener) = UnixSeqpacketListener::bind_with_backlog(&path, 0));
+
+	// Fill up the backlog.
+	let mut clients = Vec::new();
+	let error = loop {
+		match UnixSeqpacket::connect_timeout(&path, Duration::from_millis(50)).await {
+			Ok(client) => clients.push(client),
+			Err(e) => break e,
+		}
+		assert!(clients.len() < 100);
+	};
+	assert!(error.kind() == std::io::ErrorKind::TimedOut);
+}
+
+/// Test a two-phase connect with `start_connect()` and `poll_connect()`.
+#[tokio::test]
+async fn start_connect() {
+	use tokio_seqpacket::UnixSeqpacketListener;
+
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	let_assert!(Ok(listener) = UnixSeqpacketListener::bind(&path));
+	let_assert!(Ok(client) = UnixSeqpacket::start_connect(&path));
+	assert!(let Ok(()) = futures::future::poll_fn(|cx| client.poll_connect(cx)).await);
+	let_assert!(Ok((peer, _addr)) = listener.accept().await);
+
+	assert!(let Ok(12) = client.send(b"Hello world!").await);
+	let mut buffer = [0u8; 128];
+	assert!(let Ok(12) = peer.recv(&mut buffer).await);
+	assert!(&buffer[..12] == b"Hello world!");
+}
+
+/// Test that a cloned socket can be used independently of the original.
+#[tokio::test]
+async fn try_clone() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let_assert!(Ok(a_clone) = a.try_clone());
+	assert!(a_clone.as_raw_fd() != a.as_raw_fd());
+
+	drop(a);
+	assert!(let Ok(5) = a_clone.send(b"hello").await);
+	let mut buffer = [0u8; 16];
+	assert!(let Ok(5) = b.recv(&mut buffer).await);
+	assert!(&buffer[..5] == b"hello");
+
+	assert!(let Ok(5) = b.send(b"world").await);
+	assert!(let Ok(5) = a_clone.recv(&mut buffer).await);
+	assert!(&buffer[..5] == b"world");
+}
+
+/// Test that we can connect a blocking socket before the runtime starts and convert it later.
+#[test]
+fn from_std() {
+	use tokio_seqpacket::blocking;
+
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+
+	let_assert!(Ok(listener) = blocking::UnixSeqpacketListener::bind(&path));
+	let_assert!(Ok(client) = blocking::UnixSeqpacket::connect(&path));
+	let_assert!(Ok((peer, _address)) = listener.accept());
+	assert!(let Ok(5) = client.send(b"hello"));
+
+	let runtime = tokio::runtime::Builder::new_current_thread()
+		.enable_all()
+		.build()
+		.unwrap();
+
+	runtime.block_on(async move {
+		let_assert!(Ok(peer) = UnixSeqpacket::from_std(peer));
+		let mut buffer = [0u8; 16];
+		assert!(let Ok(5) = peer.recv(&mut buffer).await);
+		assert!(&buffer[..5] == b"hello");
+
+		let_assert!(Ok(peer) = peer.into_std());
+		assert!(let Ok(5) = peer.send(b"world"));
+		assert!(let Ok(5) = client.recv(&mut buffer));
+		assert!(&buffer[..5] == b"world");
+	});
+}
+
+/// Test that connect_verified() checks the credentials of the peer.
+#[tokio::test]
+async fn connect_verified() {
+	use tokio_seqpacket::UnixSeqpacketListener;
+
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("listener.sock");
+	let_assert!(Ok(_listener) = UnixSeqpacketListener::bind(&path));
+
+	let uid = unsafe { libc::getuid() };
+	let_assert!(Ok(_socket) = UnixSeqpacket::connect_verified(&path, |cred| cred.uid() == uid).await);
+
+	let_assert!(Err(e) = UnixSeqpacket::connect_verified(&path, |cred| cred.uid() != uid).await);
+	assert!(e.kind() == std::io::ErrorKind::PermissionDenied);
+}
+
+/// Test that over-long socket paths are reported with a typed error.
+#[tokio::test]
+async fn path_too_long() {
+	use tokio_seqpacket::{check_socket_path, Error, ErrorReason, UnixSeqpacketListener};
+
+	let path = format!("/tmp/{}", "a".repeat(200));
+	let_assert!(Err(e) = check_socket_path(&path));
+	assert!(e.path_len() == 205);
+	assert!(e.max_len() < 205);
+	assert!(let Ok(()) = check_socket_path("/tmp/short.sock"));
+
+	let_assert!(Err(e) = UnixSeqpacket::connect(&path).await);
+	assert!(e.kind() == std::io::ErrorKind::InvalidInput);
+	let_assert!(ErrorReason::PathTooLong(inner) = Error::from(e).into_reason());
+	assert!(inner.path_len() == 205);
+
+	let_assert!(Err(e) = UnixSeqpacketListener::bind(&path));
+	assert!(let ErrorReason::PathTooLong(_) = Error::from(&e).reason());
+}
+
+/// Test that try_send() reports a full send queue instead of waiting.
+#[tokio::test]
+async fn try_send() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	assert!(let Ok(5) = a.try_send(b"hello"));
+
+	let mut buffer = [0u8; 16];
+	assert!(let Ok(5) = b.recv(&mut buffer).await);
+	assert!(&buffer[..5] == b"hello");
+
+	// Fill the send queue until the socket would block.
+	let message = [0u8; 1024];
+	let e = loop {
+		match a.try_send(&message) {
+			Ok(_) => continue,
+			Err(e) => break e,
+		}
+	};
+	assert!(e.kind() == std::io::ErrorKind::WouldBlock);
+
+	// Draining the queue on the other side makes room again.
+	assert!(let Ok(1024) = b.recv(&mut [0u8; 1024]).await);
+	while b.try_recv(&mut [0u8; 1024]).is_ok() {}
+	assert!(let Ok(1024) = a.send(&message).await);
+}
+
+/// Test that try_recv() returns queued messages and reports an empty queue instead of waiting.
+#[tokio::test]
+async fn try_recv() {
+	use std::io::IoSliceMut;
+
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let mut buffer = [0u8; 16];
+	let_assert!(Err(e) = b.try_recv(&mut buffer));
+	assert!(e.kind() == std::io::ErrorKind::WouldBlock);
+
+	assert!(let Ok(5) = a.send(b"hello").await);
+	assert!(let Ok(5) = a.send(b"world").await);
+	assert!(let Ok(5) = b.try_recv(&mut buffer));
+	assert!(&buffer[..5] == b"hello");
+
+	let mut first = [0u8; 2];
+	let mut second = [0u8; 8];
+	assert!(let Ok(5) = b.try_recv_vectored(&mut [IoSliceMut::new(&mut first), IoSliceMut::new(&mut second)]));
+	assert!(&first == b"wo");
+	assert!(&second[..3] == b"rld");
+
+	let_assert!(Err(e) = b.try_recv_vectored(&mut [IoSliceMut::new(&mut buffer)]));
+	assert!(e.kind() == std::io::ErrorKind::WouldBlock);
+}
+
+/// Test waiting for readiness and then draining all queued messages.
+#[tokio::test]
+async fn readable_writable() {
+	use std::time::Duration;
+
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	assert!(let Ok(()) = a.writable().await);
+	for _ in 0..3 {
+		assert!(let Ok(5) = a.try_send(b"hello"));
+	}
+
+	assert!(let Ok(()) = b.readable().await);
+	let mut buffer = [0u8; 16];
+	for _ in 0..3 {
+		assert!(let Ok(5) = b.try_recv(&mut buffer));
+	}
+	let_assert!(Err(e) = b.try_recv(&mut buffer));
+	assert!(e.kind() == std::io::ErrorKind::WouldBlock);
+
+	// The readiness was cleared, so waiting again must not return until a new message arrives.
+	let_assert!(Err(_) = tokio::time::timeout(Duration::from_millis(20), b.readable()).await);
+	assert!(let Ok(5) = a.send(b"world").await);
+	let_assert!(Ok(Ok(())) = tokio::time::timeout(Duration::from_secs(1), b.readable()).await);
+	assert!(let Ok(5) = b.try_recv(&mut buffer));
+	assert!(&buffer[..5] == b"world");
+}
+
+/// Test that ready() reports which of the requested states are ready.
+#[tokio::test]
+async fn ready() {
+	use tokio::io::Interest;
+
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let_assert!(Ok(ready) = b.ready(Interest::READABLE | Interest::WRITABLE).await);
+	assert!(ready.is_writable());
+	assert!(!ready.is_readable());
+
+	assert!(let Ok(5) = a.send(b"hello").await);
+	let_assert!(Ok(ready) = b.ready(Interest::READABLE).await);
+	assert!(ready.is_readable());
+	let mut buffer = [0u8; 16];
+	assert!(let Ok(5) = b.try_recv(&mut buffer));
+}
+
+/// Test running a custom I/O operation on the socket.
+#[tokio::test]
+async fn async_io() {
+	use tokio::io::Interest;
+
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let peek = |buffer: &mut [u8]| {
+		let ret = unsafe { libc::recv(b.as_raw_fd(), buffer.as_mut_ptr().cast(), buffer.len(), libc::MSG_PEEK) };
+		if ret < 0 {
+			Err(std::io::Error::last_os_error())
+		} else {
+			Ok(ret as usize)
+		}
+	};
+
+	let mut buffer = [0u8; 16];
+	let_assert!(Err(e) = b.try_io(Interest::READABLE, || peek(&mut buffer)));
+	assert!(e.kind() == std::io::ErrorKind::WouldBlock);
+
+	let sender = async {
+		tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+		a.send(b"hello").await
+	};
+	let (peeked, sent) = tokio::join!(b.async_io(Interest::READABLE, || peek(&mut buffer)), sender);
+	assert!(let Ok(5) = sent);
+	assert!(let Ok(5) = peeked);
+	assert!(&buffer[..5] == b"hello");
+
+	// The message was only peeked, so it can still be received.
+	let mut buffer = [0u8; 16];
+	assert!(let Ok(5) = b.recv(&mut buffer).await);
+}
+
+/// Test that zero-copy sends can not be enabled on unix sockets, as documented on send_with_flags().
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn zerocopy_not_supported() {
+	let_assert!(Ok((a, _b)) = UnixSeqpacket::pair());
+	let enable: libc::c_int = 1;
+	let ret = unsafe {
+		libc::setsockopt(
+			a.as_raw_fd(),
+			libc::SOL_SOCKET,
+			libc::SO_ZEROCOPY,
+			&enable as *const libc::c_int as *const libc::c_void,
+			std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+		)
+	};
+	assert!(ret == -1);
+	assert!(std::io::Error::last_os_error().raw_os_error() == Some(libc::EOPNOTSUPP));
+}
+
+/// Test passing extra flags to send and recv.
+#[tokio::test]
+async fn send_recv_with_flags() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	assert!(let Ok(12) = a.send_with_flags(b"Hello world!", libc::MSG_DONTWAIT).await);
+
+	// With MSG_PEEK | MSG_TRUNC, the message stays queued and the full size is reported.
+	let mut buffer = [0u8; 5];
+	assert!(let Ok(12) = b.recv_with_flags(&mut buffer, libc::MSG_PEEK | libc::MSG_TRUNC).await);
+	assert!(&buffer == b"Hello");
+
+	let mut buffer = [0u8; 128];
+	assert!(let Ok(12) = b.recv_with_flags(&mut buffer, 0).await);
+	assert!(&buffer[..12] == b"Hello world!");
+}
+
+/// Test that peeking leaves the message in the queue.
+#[tokio::test]
+async fn peek() {
+	use std::time::Duration;
+
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let sender = async {
+		tokio::time::sleep(Duration::from_millis(10)).await;
+		a.send(b"Hello world!").await
+	};
+
+	let mut header = [0u8; 5];
+	let (peeked, sent) = tokio::join!(b.peek(&mut header), sender);
+	assert!(let Ok(12) = sent);
+	assert!(let Ok(5) = peeked);
+	assert!(&header == b"Hello");
+
+	// Peeking again must not wait, since the message is still queued.
+	let_assert!(Ok(Ok(5)) = tokio::time::timeout(Duration::from_secs(1), b.peek(&mut header)).await);
+
+	let mut buffer = [0u8; 128];
+	assert!(let Ok(12) = b.recv(&mut buffer).await);
+	assert!(&buffer[..12] == b"Hello world!");
+	let_assert!(Err(e) = b.try_recv(&mut buffer));
+	assert!(e.kind() == std::io::ErrorKind::WouldBlock);
+}
+
+/// Test that truncated messages are reported.
+#[tokio::test]
+async fn recv_with_meta() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	assert!(let Ok(12) = a.send(b"Hello world!").await);
+	assert!(let Ok(2) = a.send(b"Hi").await);
+
+	let mut buffer = [0u8; 5];
+	let_assert!(Ok(meta) = b.recv_with_meta(&mut buffer).await);
+	assert!(meta.truncated());
+	assert!(meta.size() == 12);
+	assert!(meta.received() == 5);
+	assert!(&buffer == b"Hello");
+
+	let_assert!(Ok(meta) = b.recv_with_meta(&mut buffer).await);
+	assert!(!meta.truncated());
+	assert!(meta.size() == 2);
+	assert!(meta.received() == 2);
+	assert!(&buffer[..2] == b"Hi");
+}
+
+/// Test receiving messages of different sizes into vectors.
+#[tokio::test]
+async fn recv_to_vec() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let large = vec![0xAA; 10_000];
+	assert!(let Ok(10_000) = a.send(&large).await);
+	assert!(let Ok(2) = a.send(b"Hi").await);
+	assert!(let Ok(0) = a.send(b"").await);
+
+	let_assert!(Ok(message) = b.recv_to_vec().await);
+	assert!(message == large);
+	let_assert!(Ok(message) = b.recv_to_vec().await);
+	assert!(message == b"Hi");
+	let_assert!(Ok(message) = b.recv_to_vec().await);
+	assert!(message.is_empty());
+}
+
+/// Test querying the size of the next message without consuming it.
+#[tokio::test]
+async fn next_message_size() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	assert!(let Ok(12) = a.send(b"Hello world!").await);
+	assert!(let Ok(12) = b.next_message_size().await);
+	assert!(let Ok(12) = b.next_message_size().await);
+
+	let mut buffer = [0u8; 12];
+	assert!(let Ok(12) = b.recv(&mut buffer).await);
+	drop(a);
+	assert!(let Ok(0) = b.next_message_size().await);
+}
+
+/// Test receiving into uninitialized memory.
+#[tokio::test]
+async fn recv_uninit() {
+	use std::mem::MaybeUninit;
+
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	assert!(let Ok(12) = a.send(b"Hello world!").await);
+
+	let mut buffer: Vec<u8> = Vec::with_capacity(64 * 1024);
+	let spare: &mut [MaybeUninit<u8>] = buffer.spare_capacity_mut();
+	let_assert!(Ok(len) = b.recv_uninit(spare).await);
+	assert!(len == 12);
+	unsafe { buffer.set_len(len) };
+	assert!(buffer == b"Hello world!");
+}
+
+/// Test receiving messages into a `BytesMut`.
+#[tokio::test]
+#[cfg(feature = "bytes")]
+async fn recv_buf() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	assert!(let Ok(6) = a.send(b"Hello ").await);
+	assert!(let Ok(6) = a.send(b"world!").await);
+
+	let mut buffer = bytes::BytesMut::new();
+	assert!(let Ok(6) = b.recv_buf(&mut buffer).await);
+	assert!(let Ok(6) = b.recv_buf(&mut buffer).await);
+	assert!(&buffer[..] == b"Hello world!");
+}
+
+/// Test sending a chain of buffers as a single message.
+#[tokio::test]
+#[cfg(feature = "bytes")]
+async fn send_buf() {
+	use bytes::{Buf, Bytes};
+
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let mut chain = Bytes::from_static(b"Hello ").chain(Bytes::from_static(b"world!"));
+	assert!(let Ok(12) = a.send_buf(&mut chain).await);
+	assert!(!chain.has_remaining());
+
+	let mut buffer = [0u8; 128];
+	assert!(let Ok(12) = b.recv(&mut buffer).await);
+	assert!(&buffer[..12] == b"Hello world!");
+}
+
+/// Test sending multiple messages with a single call.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn send_batch() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	assert!(let Ok(0) = a.send_batch(&[]).await);
+	assert!(let Ok(3) = a.send_batch(&[b"one", b"two", b"three"]).await);
+
+	let mut buffer = [0u8; 16];
+	assert!(let Ok(3) = b.recv(&mut buffer).await);
+	assert!(&buffer[..3] == b"one");
+	assert!(let Ok(3) = b.recv(&mut buffer).await);
+	assert!(&buffer[..3] == b"two");
+	assert!(let Ok(5) = b.recv(&mut buffer).await);
+	assert!(&buffer[..5] == b"three");
+}
+
+/// Test receiving multiple messages with a single call.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn recv_batch() {
+	use tokio_seqpacket::RecvSlot;
+
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	assert!(let Ok(3) = a.send(b"one").await);
+	assert!(let Ok(11) = a.send(b"two or more").await);
+
+	let mut buffers = [[0u8; 8]; 4];
+	let mut slots: Vec<RecvSlot> = buffers.iter_mut().map(|x| RecvSlot::new(x)).collect();
+	assert!(let Ok(2) = b.recv_batch(&mut slots).await);
+	assert!(slots[0].data() == b"one");
+	assert!(!slots[0].truncated());
+	assert!(slots[1].data() == b"two or m");
+	assert!(slots[1].truncated());
+	assert!(slots[2].received() == 0);
+}
+
+/// Test that send and receive time out without losing messages.
+#[tokio::test]
+async fn send_recv_timeout() {
+	use std::time::Duration;
+
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let mut buffer = [0u8; 1024];
+	let_assert!(Err(e) = b.recv_timeout(&mut buffer, Duration::from_millis(10)).await);
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+
+	// A message that arrives after the timeout is still received later.
+	assert!(let Ok(5) = a.send_timeout(b"hello", Duration::from_secs(1)).await);
+	assert!(let Ok(5) = b.recv_timeout(&mut buffer, Duration::from_secs(1)).await);
+	assert!(&buffer[..5] == b"hello");
+
+	// Fill the send queue, so the next send times out.
+	while a.try_send(&buffer).is_ok() {}
+	let_assert!(Err(e) = a.send_timeout(&buffer, Duration::from_millis(10)).await);
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+}
+
+/// Test that multiple operations can share a single deadline.
+#[tokio::test]
+async fn send_recv_deadline() {
+	use std::time::Duration;
+	use tokio::time::Instant;
+
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let deadline = Instant::now() + Duration::from_millis(50);
+	assert!(let Ok(5) = a.send_deadline(b"hello", deadline).await);
+
+	let mut buffer = [0u8; 16];
+	assert!(let Ok(5) = b.recv_deadline(&mut buffer, deadline).await);
+	let_assert!(Err(e) = b.recv_deadline(&mut buffer, deadline).await);
+	assert!(e.kind() == std::io::ErrorKind::TimedOut);
+	assert!(Instant::now() >= deadline);
+}
+
+/// Test that receiving stops cleanly when the cancellation token fires.
+#[tokio::test]
+#[cfg(feature = "tokio-util")]
+async fn recv_or_cancelled() {
+	use tokio_util::sync::CancellationToken;
+
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let token = CancellationToken::new();
+	assert!(let Ok(Some(5)) = a.send_or_cancelled(&token, b"hello").await);
+
+	let mut buffer = [0u8; 16];
+	assert!(let Ok(Some(5)) = b.recv_or_cancelled(&token, &mut buffer).await);
+
+	let cancel = async {
+		tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+		token.cancel();
+	};
+	let (received, ()) = tokio::join!(b.recv_or_cancelled(&token, &mut buffer), cancel);
+	assert!(let Ok(None) = received);
+	assert!(let Ok(None) = a.send_or_cancelled(&token, b"hello").await);
+}
+
+/// Test sending with a record mark and inspecting the flags of the received message.
+#[tokio::test]
+async fn send_recv_end_of_record() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	assert!(let Ok(5) = a.send_with_flags(b"hello", libc::MSG_EOR).await);
+
+	let mut buffer = [0u8; 16];
+	let_assert!(Ok(meta) = b.recv_with_meta(&mut buffer).await);
+	assert!(meta.received() == 5);
+	assert!(!meta.truncated());
+	// Linux never reports record marks for unix sockets.
+	#[cfg(target_os = "linux")]
+	assert!(!meta.end_of_record());
+}
+
+/// Test that send_all() sends every message as a separate packet.
+#[tokio::test]
+async fn send_all() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let messages = vec![b"one".to_vec(), b"two".to_vec(), b"three".to_vec()];
+	assert!(let Ok(3) = a.send_all(&messages).await);
+
+	let mut buffer = [0u8; 16];
+	for message in &messages {
+		let_assert!(Ok(len) = b.recv(&mut buffer).await);
+		assert!(&buffer[..len] == message.as_slice());
+	}
+
+	// A message that exceeds the send buffer fails after the first message was sent.
+	let too_large = vec![0u8; 4 << 20];
+	let_assert!(Err(e) = a.send_all([&b"sent"[..], &too_large, b"not sent"]).await);
+	assert!(e.sent() == 1);
+	assert!(e.error().raw_os_error() == Some(libc::EMSGSIZE));
+	let_assert!(Ok(len) = b.recv(&mut buffer).await);
+	assert!(&buffer[..len] == b"sent");
+	let_assert!(Err(e) = b.try_recv(&mut buffer));
+	assert!(e.kind() == std::io::ErrorKind::WouldBlock);
+
+	drop(b);
+	let_assert!(Err(e) = a.send_all([b"lost"]).await);
+	assert!(e.sent() == 0);
+	let e = std::io::Error::from(e);
+	assert!(e.raw_os_error().is_none());
+	assert!(e.kind() == std::io::ErrorKind::BrokenPipe);
+}
+
+/// Test that recv_many() receives all queued messages up to the limit.
+#[tokio::test]
+async fn recv_many() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	assert!(let Ok(4) = a.send_all([&b"one"[..], b"two", b"three", b"four"]).await);
+
+	let mut messages = Vec::new();
+	assert!(let Ok(3) = b.recv_many(&mut messages, 3).await);
+	assert!(messages == [b"one".to_vec(), b"two".to_vec(), b"three".to_vec()]);
+
+	assert!(let Ok(1) = b.recv_many(&mut messages, 3).await);
+	assert!(messages.len() == 4);
+	assert!(messages[3] == b"four");
+
+	assert!(let Ok(0) = b.recv_many(&mut messages, 0).await);
+
+	drop(a);
+	messages.clear();
+	assert!(let Ok(1) = b.recv_many(&mut messages, 3).await);
+	assert!(messages[0].is_empty());
+}
+
+/// Test that pooled buffers are reused after being dropped.
+#[tokio::test]
+async fn recv_pooled() {
+	use tokio_seqpacket::BufferPool;
+
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let pool = BufferPool::new(8, 1);
+	assert!(pool.idle() == 0);
+
+	assert!(let Ok(2) = a.send_all([&b"hello"[..], b"goodbye world"]).await);
+	let_assert!(Ok(first) = b.recv_pooled(&pool).await);
+	assert!(&*first == b"hello");
+	let_assert!(Ok(second) = b.recv_pooled(&pool).await);
+	assert!(&*second == b"goodbye ");
+
+	drop(first);
+	drop(second);
+	assert!(pool.idle() == 1);
+
+	assert!(let Ok(2) = a.send(b"hi").await);
+	let_assert!(Ok(third) = b.recv_pooled(&pool).await);
+	assert!(pool.idle() == 0);
+	assert!(&*third == b"hi");
+}
+
+/// Test that recv_or_discard() skips oversized messages and reports their size.
+#[tokio::test]
+async fn recv_or_discard() {
+	use tokio_seqpacket::MessageTooLargeError;
+
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	assert!(let Ok(2) = a.send_all([&b"this message is too large"[..], b"ok"]).await);
+
+	let mut buffer = [0u8; 8];
+	let_assert!(Err(e) = b.recv_or_discard(&mut buffer).await);
+	assert!(e.kind() == std::io::ErrorKind::InvalidData);
+	let_assert!(Some(e) = e.get_ref().and_then(|e| e.downcast_ref::<MessageTooLargeError>()));
+	assert!(e.size() == 25);
+	assert!(e.capacity() == 8);
+
+	assert!(let Ok(2) = b.recv_or_discard(&mut buffer).await);
+	assert!(&buffer[..2] == b"ok");
+}
+
+/// Test getting and setting the send and receive buffer sizes.
+#[tokio::test]
+async fn buffer_size() {
+	let_assert!(Ok((a, _b)) = UnixSeqpacket::pair());
+
+	assert!(let Ok(()) = a.set_send_buffer_size(64 * 1024));
+	let_assert!(Ok(size) = a.send_buffer_size());
+	assert!(size >= 64 * 1024);
+
+	assert!(let Ok(()) = a.set_recv_buffer_size(64 * 1024));
+	let_assert!(Ok(size) = a.recv_buffer_size());
+	assert!(size >= 64 * 1024);
+}
+
+/// Test getting the security label of the peer.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn peer_security_context() {
+	let_assert!(Ok((a, _b)) = UnixSeqpacket::pair());
+
+	// Without a security module, the kernel has no label to report.
+	match a.peer_security_context() {
+		Ok(label) => {
+			assert!(!label.is_empty());
+			assert!(!std::os::unix::ffi::OsStrExt::as_bytes(label.as_os_str()).contains(&0));
+		},
+		Err(e) => assert!(e.raw_os_error() == Some(libc::ENOPROTOOPT)),
+	}
+}
+
+/// Test getting the supplementary groups of the peer.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn peer_groups() {
+	let_assert!(Ok((a, _b)) = UnixSeqpacket::pair());
+	let_assert!(Ok(mut groups) = a.peer_groups());
+
+	let count = unsafe { libc::getgroups(0, std::ptr::null_mut()) };
+	let mut expected = vec![0; count as usize];
+	assert!(unsafe { libc::getgroups(count, expected.as_mut_ptr()) } == count);
+
+	groups.sort_unstable();
+	expected.sort_unstable();
+	assert!(groups == expected);
+}
+
+/// Test getting a pidfd for the peer.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn peer_pidfd() {
+	use std::os::unix::io::AsRawFd;
+
+	let_assert!(Ok((a, _b)) = UnixSeqpacket::pair());
+	let pidfd = match a.peer_pidfd() {
+		Ok(pidfd) => pidfd,
+		// Kernels older than 6.5 do not support SO_PEERPIDFD.
+		Err(e) => {
+			assert!(e.raw_os_error() == Some(libc::ENOPROTOOPT));
+			return;
+		},
+	};
+
+	// The pidfd refers to our own process.
+	let_assert!(Ok(info) = std::fs::read_to_string(format!("/proc/self/fdinfo/{}", pidfd.as_raw_fd())));
+	let pid_line = format!("Pid:\t{}", std::process::id());
+	assert!(info.lines().any(|line| line == pid_line));
+}
+
+/// Test getting and setting the mark of a socket.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn mark() {
+	let_assert!(Ok((a, _b)) = UnixSeqpacket::pair());
+	assert!(let Ok(0) = a.mark());
+
+	// Setting the mark requires CAP_NET_ADMIN.
+	match a.set_mark(42) {
+		Ok(()) => assert!(let Ok(42) = a.mark()),
+		Err(e) => assert!(e.raw_os_error() == Some(libc::EPERM)),
+	}
+}
+
+/// Test getting and setting the priority of a socket.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn priority() {
+	let_assert!(Ok((a, _b)) = UnixSeqpacket::pair());
+	assert!(let Ok(0) = a.priority());
+	assert!(let Ok(()) = a.set_priority(6));
+	assert!(let Ok(6) = a.priority());
+}
+
+/// Test getting and setting the busy poll timeout of a socket.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn busy_poll() {
+	use tokio_seqpacket::UnixSeqpacketBuilder;
+
+	let_assert!(Ok((a, _b)) = UnixSeqpacket::pair());
+
+	// Increasing the timeout requires CAP_NET_ADMIN and kernel support for busy polling.
+	match a.set_busy_poll(50) {
+		Ok(()) => {
+			assert!(let Ok(50) = a.busy_poll());
+			let_assert!(Ok((c, _d)) = UnixSeqpacketBuilder::new().busy_poll(25).pair());
+			assert!(let Ok(25) = c.busy_poll());
+		},
+		Err(e) => assert!(e.raw_os_error() == Some(libc::EPERM) || e.raw_os_error() == Some(libc::ENOPROTOOPT)),
+	}
+}
+
+/// Test accessing socket options through a `SockRef`.
+#[tokio::test]
+#[cfg(feature = "socket2-06")]
+async fn socket_ref() {
+	let_assert!(Ok((a, _b)) = UnixSeqpacket::pair());
+	assert!(let Ok(()) = a.socket_ref().set_send_buffer_size(64 * 1024));
+	let_assert!(Ok(size) = a.send_buffer_size());
+	assert!(size >= 64 * 1024);
+	assert!(let Ok(socket2_06::Type::SEQPACKET) = a.socket_ref().r#type());
+}
+
+/// Test getting the number of bytes in the send and receive queues.
+#[tokio::test]
+async fn pending_bytes() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	assert!(let Ok(0) = b.pending_recv_bytes());
+
+	assert!(let Ok(5) = a.send(b"hello").await);
+	assert!(let Ok(6) = a.send(b"world!").await);
+	assert!(let Ok(11) = b.pending_recv_bytes());
+	#[cfg(target_os = "linux")]
+	{
+		let_assert!(Ok(queued) = a.pending_send_bytes());
+		assert!(queued >= 11);
+	}
+
+	let mut buffer = [0u8; 16];
+	assert!(let Ok(5) = b.recv(&mut buffer).await);
+	assert!(let Ok(6) = b.pending_recv_bytes());
+	assert!(let Ok(6) = b.recv(&mut buffer).await);
+	assert!(let Ok(0) = b.pending_recv_bytes());
+	#[cfg(target_os = "linux")]
+	assert!(let Ok(0) = a.pending_send_bytes());
+}
+
+/// Test reading information about the peer process.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn peer_process() {
+	let_assert!(Ok((a, _b)) = UnixSeqpacket::pair());
+	let_assert!(Ok(process) = a.peer_process());
+
+	assert!(process.pid() as u32 == std::process::id());
+	let_assert!(Ok(exe) = std::env::current_exe());
+	assert!(process.exe() == Some(exe.as_path()));
+	let args: Vec<_> = std::env::args_os().collect();
+	assert!(process.cmdline() == args.as_slice());
+	assert!(process.start_time() > 0);
+}
+
+/// Test getting the credentials of the peer including the supplementary groups.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn peer_cred_full() {
+	let_assert!(Ok((a, _b)) = UnixSeqpacket::pair());
+	let_assert!(Ok(cred) = a.peer_cred_full());
+	let_assert!(Ok(groups) = a.peer_groups());
+
+	assert!(cred.ucred() == a.peer_cred().unwrap());
+	assert!(cred.uid() == unsafe { libc::getuid() });
+	assert!(cred.groups() == groups.as_slice());
+	assert!(cred.in_group(unsafe { libc::getgid() }));
+}
+
+/// Test formatting the credentials of the peer.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn ucred_display() {
+	let_assert!(Ok((a, _b)) = UnixSeqpacket::pair());
+	let_assert!(Ok(cred) = a.peer_cred());
+	let expected = format!(
+		"pid={} uid={} gid={}",
+		std::process::id(),
+		unsafe { libc::getuid() },
+		unsafe { libc::getgid() }
+	);
+	assert!(cred.to_string() == expected);
+}
+
+/// Test that the credentials can be serialized and deserialized.
+#[test]
+#[cfg(feature = "serde")]
+fn ucred_serde() {
+	fn assert_serde<T: serde::Serialize + serde::de::DeserializeOwned>() {}
+	assert_serde::<tokio_seqpacket::UCred>();
+}
+
+/// Test classifying errors with the crate error type.
+#[tokio::test]
+async fn error_classification() {
+	use std::error::Error as _;
+	use tokio_seqpacket::{Error, ErrorReason, Operation};
+
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	assert!(let Ok(25) = a.send(b"this message is too large").await);
+
+	let mut buffer = [0u8; 8];
+	let_assert!(Err(e) = b.recv_or_discard(&mut buffer).await);
+	let e = Error::new(Operation::Recv, e);
+	assert!(e.operation() == Some(Operation::Recv));
+	assert!(e.to_string().starts_with("failed to receive: "));
+	let_assert!(ErrorReason::MessageTooLarge(inner) = e.reason());
+	assert!(inner.size() == 25);
+	let_assert!(Some(source) = e.source());
+	assert!(let Some(_) = source.downcast_ref::<tokio_seqpacket::MessageTooLargeError>());
+
+	// Converting back to an I/O error keeps the reason.
+	let e = std::io::Error::from(e);
+	assert!(e.kind() == std::io::ErrorKind::InvalidData);
+	let e = Error::from(e);
+	assert!(e.operation() == None);
+	assert!(let ErrorReason::MessageTooLarge(_) = e.reason());
+
+	drop(b);
+	let_assert!(Err(e) = a.send(b"hello").await);
+	let e = Error::from(e);
+	assert!(let ErrorReason::Disconnected(_) = e.reason());
+	assert!(e.raw_os_error() == Some(libc::EPIPE));
+
+	// Classifying by reference keeps the OS error, and the OS error is the source.
+	let_assert!(Err(e) = UnixSeqpacket::connect("/nonexistent/socket").await);
+	let classified = Error::from(&e);
+	assert!(classified.operation() == None);
+	assert!(classified.raw_os_error() == Some(libc::ENOENT));
+	let_assert!(Some(source) = classified.source());
+	let_assert!(Some(source) = source.downcast_ref::<std::io::Error>());
+	assert!(source.raw_os_error() == Some(libc::ENOENT));
+	let e = Error::new(Operation::Connect, e);
+	assert!(e.to_string().starts_with("failed to connect: "));
+}
+
+/// Test that the debug output includes the connection state.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn debug_output() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let debug = format!("{:?}", a);
+	assert!(debug.contains("connected: true"));
+	assert!(debug.contains("peer_addr: Some("));
+	assert!(debug.contains("shutdown: None"));
+
+	assert!(let Ok(()) = a.shutdown(std::net::Shutdown::Read));
+	assert!(format!("{:?}", a).contains("shutdown: Some(Read)"));
+
+	drop(b);
+	assert!(format!("{:?}", a).contains("shutdown: Some(Both)"));
+}
+
+/// Test that the statistics of a socket track sent and received messages.
+#[tokio::test]
+async fn stats() {
+	let_assert!(Ok((mut a, mut b)) = UnixSeqpacket::pair());
+	assert!(a.stats() == None);
+	a.enable_stats();
+	b.enable_stats();
+	let_assert!(Some(stats) = b.stats());
+	assert!(stats.messages_received == 0);
+	assert!(stats.last_activity == None);
+
+	assert!(let Ok(5) = a.send(b"hello").await);
+	assert!(let Ok(20) = a.send(&[0u8; 20]).await);
+	let mut buffer = [0u8; 8];
+	assert!(let Ok(5) = b.peek(&mut buffer).await);
+	assert!(let Ok(5) = b.recv(&mut buffer).await);
+	assert!(let Ok(8) = b.recv(&mut buffer).await);
+
+	let_assert!(Some(stats) = a.stats());
+	assert!(stats.messages_sent == 2);
+	assert!(stats.bytes_sent == 25);
+	assert!(stats.last_activity.is_some());
+
+	let_assert!(Some(stats) = b.stats());
+	assert!(stats.messages_received == 2);
+	assert!(stats.bytes_received == 13);
+	assert!(stats.messages_truncated == 1);
+	assert!(stats.messages_sent == 0);
+	assert!(stats.last_activity.is_some());
+}
+
+/// Test that every socket has a unique cookie.
+#[tokio::test]
+#[cfg(target_os = "linux")]
+async fn cookie() {
+	let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+	let_assert!(Ok(cookie_a) = a.cookie());
+	let_assert!(Ok(cookie_b) = b.cookie());
+	assert!(cookie_a != 0);
+	assert!(cookie_a != cookie_b);
+	let_assert!(Ok(again) = a.cookie());
+	assert!(again == cookie_a);
+}
+
+/// Test receiving messages through a stream.
+#[tokio::test]
+async fn message_stream() {
+	use futures::StreamExt;
+	use tokio_seqpacket::{AllocationStrategy, Error, ErrorReason};
+
+	for allocation in [AllocationStrategy::Exact, AllocationStrategy::MaxSize] {
+		let_assert!(Ok((a, b)) = UnixSeqpacket::pair());
+		assert!(let Ok(5) = a.send(b"hello").await);
+		assert!(let Ok(20) = a.send(&[1u8; 20]).await);
+		assert!(let Ok(5) = a.send(b"world").await);
+		drop(a);
+
+		let mut messages = b.messages().max_size(8).allocation(allocation);
+		let_assert!(Some(Ok(message)) = messages.next().await);
+		assert!(message == b"hello");
+		let_assert!(Some(Err(e)) = messages.next().await);
+		let_assert!(ErrorReason::MessageTooLarge(e) = Error::from(e).into_reason());
+		assert!(e.size() == 20);
+		let_assert!(Some(Ok(message)) = messages.next().await);
+		assert!(message == b"world");
+		assert!(let None = messages.next().await);
+		assert!(let None = messages.next().await);
+	}
+}
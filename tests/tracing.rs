@@ -0,0 +1,74 @@
+#![cfg(feature = "tracing")]
+
+use assert2::{assert, let_assert};
+use std::sync::{Arc, Mutex};
+use tokio_seqpacket::{UnixSeqpacket, UnixSeqpacketListener};
+
+/// Subscriber that records the messages of all events.
+#[derive(Clone, Default)]
+struct Recorder {
+	messages: Arc<Mutex<Vec<String>>>,
+}
+
+/// Visitor that extracts the message of an event.
+struct MessageVisitor<'a>(&'a mut String);
+
+impl tracing::field::Visit for MessageVisitor<'_> {
+	fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+		if field.name() == "message" {
+			*self.0 = format!("{:?}", value);
+		}
+	}
+}
+
+impl tracing::Subscriber for Recorder {
+	fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+		true
+	}
+
+	fn new_span(&self, _span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+		tracing::span::Id::from_u64(1)
+	}
+
+	fn record(&self, _span: &tracing::span::Id, _values: &tracing::span::Record<'_>) {}
+
+	fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+
+	fn event(&self, event: &tracing::Event<'_>) {
+		let mut message = String::new();
+		event.record(&mut MessageVisitor(&mut message));
+		self.messages.lock().unwrap().push(message);
+	}
+
+	fn enter(&self, _span: &tracing::span::Id) {}
+
+	fn exit(&self, _span: &tracing::span::Id) {}
+}
+
+/// Test that connecting, accepting, sending and receiving emit events.
+#[test]
+fn tracing_events() {
+	let recorder = Recorder::default();
+	let messages = recorder.messages.clone();
+
+	tracing::subscriber::with_default(recorder, || {
+		let_assert!(Ok(runtime) = tokio::runtime::Builder::new_current_thread().enable_all().build());
+		runtime.block_on(async {
+			let dir = tempfile::tempdir().unwrap();
+			let path = dir.path().join("listener.sock");
+			let_assert!(Ok(listener) = UnixSeqpacketListener::bind(&path));
+			let_assert!(Ok(client) = UnixSeqpacket::connect(&path).await);
+			let_assert!(Ok((server, _addr)) = listener.accept().await);
+
+			assert!(let Ok(5) = client.send(b"hello").await);
+			let mut buffer = [0u8; 16];
+			assert!(let Ok(5) = server.recv(&mut buffer).await);
+		});
+	});
+
+	let messages = messages.lock().unwrap();
+	assert!(messages.iter().any(|x| x == "connected"));
+	assert!(messages.iter().any(|x| x == "accepted connection"));
+	assert!(messages.iter().any(|x| x == "sent message"));
+	assert!(messages.iter().any(|x| x == "received message"));
+}